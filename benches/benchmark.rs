@@ -1,5 +1,6 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
 use squeal::*;
+use std::hint::black_box;
 
 fn generate() -> String {
     Query {
@@ -12,18 +13,22 @@ fn generate() -> String {
             Op::O("<>"),
             Box::new(Term::Atom("b")),
         )),
-        group_by: Some(vec!["a", "b"]),
+        group_by: Some(vec![Term::Atom("a"), Term::Atom("b")]),
         having: Some(Having::new(Term::Condition(
             Box::new(Term::Atom("a")),
             Op::O("<>"),
             Box::new(Term::Atom("b")),
         ))),
+        window_defs: vec![],
         order_by: Some(OrderBy {
             columns: vec![OrderedColumn::Asc("a"), OrderedColumn::Desc("b")],
         }),
         limit: Some(19),
         offset: Some(10),
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     }
     .sql()
 }
@@ -52,6 +57,82 @@ fn fluent_generation() -> String {
     result.sql()
 }
 
+/// Builds a realistic large query with the fluent API (three CTEs, ten joins, a 50-item IN
+/// list and nested subqueries) and hands it to `f`.
+fn with_large_query(ids: &[String], f: impl FnOnce(&Query)) {
+    let id_refs: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+
+    let mut recent = Q();
+    let recent_orders = recent
+        .select(vec!["id", "user_id", "total"])
+        .from("orders")
+        .where_(gt("created_at", "NOW() - INTERVAL '30 days'"))
+        .build();
+    let mut big = Q();
+    let big_spenders = big
+        .select(vec!["user_id", "SUM(total) AS spent"])
+        .from("recent_orders")
+        .group_by(vec!["user_id"])
+        .having(gt("SUM(total)", "1000"))
+        .build();
+    let mut flagged = Q();
+    let flagged_users = flagged
+        .select(vec!["user_id"])
+        .from("fraud_flags")
+        .where_(eq("resolved", "FALSE"))
+        .build();
+
+    let mut refunds = Q();
+    let refund_exists = refunds
+        .select(vec!["1"])
+        .from("refunds")
+        .where_(eq("refunds.order_id", "o.id"))
+        .build();
+    let mut items = Q();
+    let item_count = items
+        .select(vec!["COUNT(*)"])
+        .from("order_items")
+        .where_(and(
+            eq("order_items.order_id", "o.id"),
+            not(exists(refund_exists)),
+        ))
+        .build();
+
+    let mut qb = Q();
+    let query = qb
+        .with("recent_orders", recent_orders)
+        .with("big_spenders", big_spenders)
+        .with("flagged_users", flagged_users)
+        .select_expressions(vec![
+            SelectExpression::Column("u.id"),
+            SelectExpression::Column("u.email"),
+            SelectExpression::Column("bs.spent"),
+            SelectExpression::Subquery(Box::new(item_count), Some("item_count")),
+        ])
+        .from("users u")
+        .inner_join("big_spenders bs", eq("bs.user_id", "u.id"))
+        .inner_join("recent_orders o", eq("o.user_id", "u.id"))
+        .left_join("addresses a", eq("a.user_id", "u.id"))
+        .left_join("countries c", eq("c.code", "a.country_code"))
+        .left_join("profiles p", eq("p.user_id", "u.id"))
+        .left_join("plans pl", eq("pl.id", "p.plan_id"))
+        .left_join("teams t", eq("t.id", "u.team_id"))
+        .left_join("organizations org", eq("org.id", "t.org_id"))
+        .left_join("flagged_users f", eq("f.user_id", "u.id"))
+        .left_join("sessions s", eq("s.user_id", "u.id"))
+        .where_(and(
+            in_("u.id", id_refs),
+            and(is_null("f.user_id"), not(eq("c.code", "'XX'"))),
+        ))
+        .order_by(vec![
+            OrderedColumn::Desc("bs.spent"),
+            OrderedColumn::Asc("u.id"),
+        ])
+        .limit(100)
+        .build();
+    f(&query);
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("generate", |b| {
         b.iter(|| {
@@ -63,6 +144,22 @@ fn criterion_benchmark(c: &mut Criterion) {
             fluent_generation();
         })
     });
+
+    let ids: Vec<String> = (1..=50).map(|i| i.to_string()).collect();
+    let mut group = c.benchmark_group("large query");
+    group.bench_function("fluent build", |b| {
+        b.iter(|| {
+            with_large_query(&ids, |q| {
+                black_box(q);
+            })
+        })
+    });
+    with_large_query(&ids, |query| {
+        // Report throughput in rendered bytes, so criterion shows how fast SQL text is produced.
+        group.throughput(Throughput::Bytes(query.sql().len() as u64));
+        group.bench_function("render", |b| b.iter(|| black_box(query.sql())));
+    });
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -200,18 +200,54 @@
 //! assert_eq!(query.sql(), "SELECT * FROM users WHERE email = $1");
 //! ```
 //!
+//! Use `bind()` instead of `param()` to record the value along with its placeholder, then
+//! `render()` to get the SQL and the values in placeholder order:
+//!
+//! ```
+//! use squeal::*;
+//!
+//! let mut qb = Q();
+//! let email = qb.bind("alice@example.com");
+//! let (sql, values) = qb.select(vec!["*"])
+//!     .from("users")
+//!     .where_(eq("email", &email))
+//!     .build()
+//!     .render().unwrap();
+//!
+//! assert_eq!(sql, "SELECT * FROM users WHERE email = $1");
+//! assert_eq!(values, vec![Value::Text("alice@example.com".to_string())]);
+//! ```
+//!
 //! ## More Information
 //!
 //! See the [README](https://github.com/upside-down-research/squeal) for complete documentation
 //! and examples.
 
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
 pub mod queries;
 
-pub use queries::create_table::{CreateTable, T, TableBuilder};
+pub use queries::comment::{Comment, CommentTarget};
+pub use queries::create_index::CreateIndex;
+pub use queries::create_table::{
+    ColumnConstraint, ColumnDef, CreateTable, ForeignKey, ForeignKeyAction, T, TableBuilder,
+    TableConstraint,
+};
+pub use queries::create_table_as::CreateTableAs;
 pub use queries::delete::{D, Delete, DeleteBuilder};
 pub use queries::drop_table::DropTable;
+pub use queries::explain::{Explain, ExplainFormat, ExplainOptions};
 pub use queries::insert::{I, Insert, InsertBuilder, InsertSource, OnConflict};
+pub use queries::prepare::{Deallocate, Execute, Prepare};
+pub use queries::script::Script;
 pub use queries::select::{Columns, Select, SelectExpression};
+pub use queries::set_operation::{SetBranch, SetOperator, SetQuery};
+pub use queries::stats::QueryStats;
+pub use queries::table_stmt::TableStmt;
 pub use queries::update::{U, Update, UpdateBuilder};
 
 /// The Sql trait is implemented by all objects that can be used in a query.
@@ -232,12 +268,14 @@ pub trait Build {
 
 /// The Parameterized trait provides PostgreSQL parameter placeholder generation.
 /// Implemented by all query builder structs to provide consistent param() API.
+///
+/// Binding values is done with the bind() and bind_as() methods of each builder.
 pub trait Parameterized {
     /// Returns the next PostgreSQL parameter placeholder ($1, $2, $3, etc.)
     /// Each builder maintains its own isolated counter.
     fn param(&mut self) -> String;
 }
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Distinct<'a> {
     All,
     On(Vec<&'a str>),
@@ -247,7 +285,7 @@ pub enum Distinct<'a> {
 /// It is used in the Term struct.
 ///
 /// The Op::O variant is an escape hatch to allow you to use any operator you want.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Op<'a> {
     /// Logical AND operator
     And,
@@ -269,6 +307,8 @@ pub enum Op<'a> {
     Like,
     /// IN operator for set membership
     In,
+    /// NOT IN operator for set non-membership
+    NotIn,
     /// EXISTS operator for subquery existence testing
     Exists,
     /// NOT EXISTS operator for subquery non-existence testing
@@ -277,6 +317,20 @@ pub enum Op<'a> {
     Any,
     /// ALL operator for comparing against all values in a subquery
     All,
+    /// OVERLAPS operator for comparing two time periods
+    Overlaps,
+    /// IS DISTINCT FROM, inequality that treats NULLs as equal to each other
+    IsDistinctFrom,
+    /// IS NOT DISTINCT FROM, equality that treats NULLs as equal to each other
+    IsNotDistinctFrom,
+    /// Addition (+)
+    Plus,
+    /// Subtraction (-)
+    Minus,
+    /// Multiplication (*)
+    Multiply,
+    /// Division (/)
+    Divide,
     /// Custom operator escape hatch
     O(&'a str),
 }
@@ -294,16 +348,70 @@ impl<'a> Sql for Op<'a> {
             Op::LessOrEqual => "<=",
             Op::Like => "LIKE",
             Op::In => "IN",
+            Op::NotIn => "NOT IN",
             Op::Exists => "EXISTS",
             Op::NotExists => "NOT EXISTS",
             Op::Any => "ANY",
             Op::All => "ALL",
+            Op::Overlaps => "OVERLAPS",
+            Op::IsDistinctFrom => "IS DISTINCT FROM",
+            Op::IsNotDistinctFrom => "IS NOT DISTINCT FROM",
+            Op::Plus => "+",
+            Op::Minus => "-",
+            Op::Multiply => "*",
+            Op::Divide => "/",
             Op::O(s) => s,
         }
         .to_string()
     }
 }
 
+/// Parses an operator from its SQL spelling, the inverse of `Op::sql()`. Keywords match
+/// case-insensitively and `<>` is read as `!=`; anything unrecognized becomes `Op::O`.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert!(parse_op("<=") == Op::LessOrEqual);
+/// assert!(parse_op("not in") == Op::NotIn);
+/// assert!(parse_op("@>") == Op::O("@>"));
+/// let t = Term::Condition(Box::new(Term::Atom("age")), parse_op("<="), Box::new(Term::Atom("30")));
+/// assert_eq!(t.sql(), "age <= 30");
+/// ```
+pub fn parse_op(s: &str) -> Op<'_> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "AND" => Op::And,
+        "OR" => Op::Or,
+        "=" => Op::Equals,
+        "!=" | "<>" => Op::NotEquals,
+        ">" => Op::GreaterThan,
+        "<" => Op::LessThan,
+        ">=" => Op::GreaterOrEqual,
+        "<=" => Op::LessOrEqual,
+        "LIKE" => Op::Like,
+        "IN" => Op::In,
+        "NOT IN" => Op::NotIn,
+        "EXISTS" => Op::Exists,
+        "NOT EXISTS" => Op::NotExists,
+        "ANY" => Op::Any,
+        "ALL" => Op::All,
+        "OVERLAPS" => Op::Overlaps,
+        "IS DISTINCT FROM" => Op::IsDistinctFrom,
+        "IS NOT DISTINCT FROM" => Op::IsNotDistinctFrom,
+        "+" => Op::Plus,
+        "-" => Op::Minus,
+        "*" => Op::Multiply,
+        "/" => Op::Divide,
+        _ => Op::O(s),
+    }
+}
+
+impl<'a> From<&'a str> for Op<'a> {
+    fn from(s: &'a str) -> Self {
+        parse_op(s)
+    }
+}
+
 /// The Term enum is used to specify a condition in a query (WHERE clause).
 /// It is used in the Query struct.
 ///
@@ -352,18 +460,98 @@ impl<'a> Sql for Op<'a> {
 ///
 ///
 ///
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Term<'a> {
     /// An atom is a single identifier.
     Atom(&'a str),
+    /// A raw SQL fragment, rendered verbatim. It is neither escaped nor validated, so never
+    /// build one from untrusted input.
+    Raw(Cow<'a, str>),
     /// A condition is a combination of two terms and an operator.
     Condition(Box<Term<'a>>, Op<'a>, Box<Term<'a>>),
     /// A parenthesized term.
     Parens(Box<Term<'a>>),
     /// A null term.
     Null,
+    /// A row constructor, rendered as `(a, b, ...)`.
+    Row(Vec<Term<'a>>),
     /// A subquery that can be used in WHERE clauses
     Subquery(Box<Query<'a>>),
+    /// EXISTS (subquery)
+    Exists(Box<Query<'a>>),
+    /// NOT EXISTS (subquery)
+    NotExists(Box<Query<'a>>),
+    /// A quantified comparison against a subquery: `left op ANY (subquery)`, or `ALL` when
+    /// `all` is true.
+    AnyAll {
+        /// The left operand, parenthesized when it is itself a binary expression
+        left: Box<Term<'a>>,
+        /// The comparison operator
+        op: Op<'a>,
+        /// ALL instead of ANY
+        all: bool,
+        /// The subquery producing the values to compare against
+        subquery: Box<Query<'a>>,
+    },
+    /// A pattern match: `expr [NOT] LIKE|ILIKE pattern [ESCAPE 'c']`.
+    Like {
+        /// The value being matched
+        expr: Box<Term<'a>>,
+        /// The pattern, rendered as is
+        pattern: Box<Term<'a>>,
+        /// The escape character, quoted on render
+        escape: Option<&'a str>,
+        /// Use ILIKE instead of LIKE
+        case_insensitive: bool,
+        /// Render NOT LIKE / NOT ILIKE
+        negated: bool,
+    },
+    /// A reference-counted term. Cloning it shares the subtree instead of copying it, so one
+    /// large expression can appear in several places cheaply. Renders exactly like the inner term.
+    /// It is an Arc rather than an Rc so that terms and queries stay Send and Sync.
+    Shared(Arc<Term<'a>>),
+    /// A function call: `name(args)`
+    Function(&'a str, Vec<Term<'a>>),
+    /// `expr [NOT] IN (a, b, ...)`
+    InList {
+        /// The value being tested
+        expr: Box<Term<'a>>,
+        /// The candidate values
+        list: Vec<Term<'a>>,
+        /// Render NOT IN
+        negated: bool,
+    },
+    /// `expr [NOT] BETWEEN low AND high`
+    Between {
+        /// The value being tested
+        expr: Box<Term<'a>>,
+        /// The lower bound, inclusive
+        low: Box<Term<'a>>,
+        /// The upper bound, inclusive
+        high: Box<Term<'a>>,
+        /// Render NOT BETWEEN
+        negated: bool,
+    },
+    /// `expr IS [NOT] NULL`
+    IsNull {
+        /// The value being tested
+        expr: Box<Term<'a>>,
+        /// Render IS NOT NULL
+        negated: bool,
+    },
+    /// Array element access: `arr[idx]`
+    Subscript(Box<Term<'a>>, Box<Term<'a>>),
+    /// Array slice: `arr[lo:hi]`, either bound may be omitted
+    Slice(Box<Term<'a>>, Option<Box<Term<'a>>>, Option<Box<Term<'a>>>),
+    /// TRIM([LEADING|TRAILING|BOTH] [chars] FROM expr)
+    Trim {
+        /// Which end(s) of the string to trim
+        side: TrimSide,
+        /// The characters to remove; whitespace when absent
+        chars: Option<Box<Term<'a>>>,
+        /// The string to trim
+        from: Box<Term<'a>>,
+    },
     Not(Box<Term<'a>>),
     Cast(Box<Term<'a>>, &'a str),
     PgCast(Box<Term<'a>>, &'a str),
@@ -372,6 +560,19 @@ pub enum Term<'a> {
     NullIf(Box<Term<'a>>, Box<Term<'a>>),
     Concat(Vec<Term<'a>>),
     Substring(Box<Term<'a>>, Option<Box<Term<'a>>>, Option<Box<Term<'a>>>),
+    /// `OVERLAY(target PLACING replacement FROM start [FOR len])`
+    Overlay {
+        /// The string to modify
+        target: Box<Term<'a>>,
+        /// The text placed into it
+        placing: Box<Term<'a>>,
+        /// The 1-based position where the replacement starts
+        from: Box<Term<'a>>,
+        /// How many characters are replaced; defaults to the replacement's length
+        for_: Option<Box<Term<'a>>>,
+    },
+    /// `POSITION(substring IN string)`
+    Position(Box<Term<'a>>, Box<Term<'a>>),
     Upper(Box<Term<'a>>),
     Lower(Box<Term<'a>>),
     Now,
@@ -379,11 +580,20 @@ pub enum Term<'a> {
     Interval(&'a str),
     DateAdd(Box<Term<'a>>, Box<Term<'a>>),
     DateSub(Box<Term<'a>>, Box<Term<'a>>),
+    /// A window function call: `term OVER (...)` or `term OVER name`.
+    Over(Box<Term<'a>>, Window<'a>),
+    /// An aggregate function call with optional DISTINCT and ORDER BY inside the parentheses.
+    Aggregate(&'a str, Vec<Term<'a>>, AggOptions<'a>),
+    /// An ordered-set aggregate: `agg WITHIN GROUP (ORDER BY ...)`.
+    WithinGroup(Box<Term<'a>>, OrderBy<'a>),
 }
 
 impl<'a> Sql for CaseExpression<'a> {
     fn sql(&self) -> String {
         let mut s = "CASE".to_string();
+        if let Some(operand) = &self.operand {
+            s.push_str(&format!(" {}", operand.sql()));
+        }
         for wt in &self.when_thens {
             s.push_str(&format!(" WHEN {} THEN {}", wt.when.sql(), wt.then.sql()));
         }
@@ -395,27 +605,107 @@ impl<'a> Sql for CaseExpression<'a> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct WhenThen<'a> {
     pub when: Term<'a>,
     pub then: Term<'a>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct CaseExpression<'a> {
+    /// The operand of a simple CASE (`CASE x WHEN 1 THEN ...`); None for a searched CASE,
+    /// whose WHEN branches are conditions
+    pub operand: Option<Box<Term<'a>>>,
     pub when_thens: Vec<WhenThen<'a>>,
     pub else_term: Option<Box<Term<'a>>>,
 }
 
+/// The end(s) of a string that TRIM removes characters from
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum TrimSide {
+    /// Trim the start of the string
+    Leading,
+    /// Trim the end of the string
+    Trailing,
+    /// Trim both ends of the string
+    Both,
+}
+
+impl Sql for TrimSide {
+    fn sql(&self) -> String {
+        match self {
+            TrimSide::Leading => "LEADING",
+            TrimSide::Trailing => "TRAILING",
+            TrimSide::Both => "BOTH",
+        }
+        .to_string()
+    }
+}
+
+/// The AggOptions struct holds the modifiers that can appear inside an aggregate call,
+/// as in `COUNT(DISTINCT x)` or `STRING_AGG(name, ', ' ORDER BY name ASC)`.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct AggOptions<'a> {
+    /// Aggregate only distinct input values
+    pub distinct: bool,
+    /// The order in which input values are fed to the aggregate
+    pub order_by: Option<OrderBy<'a>>,
+}
+
 impl<'a> Sql for Term<'a> {
     fn sql(&self) -> String {
         match &self {
             Term::Atom(s) => s.to_string(),
-            Term::Condition(t1, op, t2) => format!("{} {} {}", t1.sql(), op.sql(), t2.sql()),
+            Term::Raw(s) => s.to_string(),
+            Term::Condition(..) => condition_chain_sql(self),
             Term::Null => "".to_string(),
             Term::Parens(t) => format!("({})", t.sql()),
+            Term::Row(terms) => {
+                let terms_sql: Vec<String> = terms.iter().map(|t| t.sql()).collect();
+                format!("({})", terms_sql.join(", "))
+            }
             Term::Subquery(q) => format!("({})", q.sql()),
-            Term::Not(t) => format!("NOT {}", t.sql()),
+            Term::Exists(q) => format!("EXISTS ({})", q.sql()),
+            Term::Like {
+                expr,
+                pattern,
+                escape,
+                case_insensitive,
+                negated,
+            } => {
+                let mut s = expr.sql();
+                if *negated {
+                    s.push_str(" NOT");
+                }
+                s.push_str(if *case_insensitive {
+                    " ILIKE "
+                } else {
+                    " LIKE "
+                });
+                s.push_str(&pattern.sql());
+                if let Some(e) = escape {
+                    s.push_str(&format!(" ESCAPE '{}'", e.replace('\'', "''")));
+                }
+                s
+            }
+            Term::NotExists(q) => format!("NOT EXISTS ({})", q.sql()),
+            Term::AnyAll {
+                left,
+                op,
+                all,
+                subquery,
+            } => {
+                let left = match left.as_ref() {
+                    Term::Condition(..) => format!("({})", left.sql()),
+                    _ => left.sql(),
+                };
+                let quantifier = if *all { "ALL" } else { "ANY" };
+                format!("{} {} {} ({})", left, op.sql(), quantifier, subquery.sql())
+            }
+            Term::Not(t) => match unshared(t) {
+                Term::Condition(_, Op::And | Op::Or, _) => format!("NOT ({})", t.sql()),
+                _ => format!("NOT {}", t.sql()),
+            },
             Term::Cast(t, ty) => format!("CAST({} AS {})", t.sql(), ty),
             Term::PgCast(t, ty) => format!("{}::{}", t.sql(), ty),
             Term::Case(c) => c.sql(),
@@ -439,363 +729,3397 @@ impl<'a> Sql for Term<'a> {
                 s.push(')');
                 s
             }
+            Term::Overlay {
+                target,
+                placing,
+                from,
+                for_,
+            } => {
+                let mut s = format!(
+                    "OVERLAY({} PLACING {} FROM {}",
+                    target.sql(),
+                    placing.sql(),
+                    from.sql()
+                );
+                if let Some(f) = for_ {
+                    s.push_str(&format!(" FOR {}", f.sql()));
+                }
+                s.push(')');
+                s
+            }
+            Term::Position(sub, string) => format!("POSITION({} IN {})", sub.sql(), string.sql()),
             Term::Upper(t) => format!("UPPER({})", t.sql()),
             Term::Lower(t) => format!("LOWER({})", t.sql()),
+            Term::Shared(t) => t.sql(),
+            Term::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let list_sql: Vec<String> = list.iter().map(|t| t.sql()).collect();
+                let not = if *negated { "NOT " } else { "" };
+                format!("{} {}IN ({})", expr.sql(), not, list_sql.join(", "))
+            }
+            Term::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let not = if *negated { "NOT " } else { "" };
+                format!(
+                    "{} {}BETWEEN {} AND {}",
+                    expr.sql(),
+                    not,
+                    low.sql(),
+                    high.sql()
+                )
+            }
+            Term::IsNull { expr, negated } => {
+                let not = if *negated { "NOT " } else { "" };
+                format!("{} IS {}NULL", expr.sql(), not)
+            }
+            Term::Function(name, args) => {
+                let args_sql: Vec<String> = args.iter().map(|t| t.sql()).collect();
+                format!("{}({})", name, args_sql.join(", "))
+            }
+            Term::Subscript(arr, idx) => format!("{}[{}]", subscript_base_sql(arr), idx.sql()),
+            Term::Slice(arr, lo, hi) => format!(
+                "{}[{}:{}]",
+                subscript_base_sql(arr),
+                lo.as_ref().map(|t| t.sql()).unwrap_or_default(),
+                hi.as_ref().map(|t| t.sql()).unwrap_or_default()
+            ),
+            Term::Trim { side, chars, from } => match (side, chars) {
+                (TrimSide::Both, None) => format!("TRIM({})", from.sql()),
+                (side, None) => format!("TRIM({} FROM {})", side.sql(), from.sql()),
+                (side, Some(c)) => {
+                    format!("TRIM({} {} FROM {})", side.sql(), c.sql(), from.sql())
+                }
+            },
             Term::Now => "NOW()".to_string(),
             Term::CurrentDate => "CURRENT_DATE".to_string(),
             Term::Interval(s) => format!("INTERVAL '{}'", s),
             Term::DateAdd(t1, t2) => format!("{} + {}", t1.sql(), t2.sql()),
             Term::DateSub(t1, t2) => format!("{} - {}", t1.sql(), t2.sql()),
+            Term::Over(t, w) => format!("{} OVER {}", t.sql(), w.sql()),
+            Term::Aggregate(name, args, options) => {
+                let args_sql: Vec<String> = args.iter().map(|t| t.sql()).collect();
+                let mut s = format!("{}(", name);
+                if options.distinct {
+                    s.push_str("DISTINCT ");
+                }
+                s.push_str(&args_sql.join(", "));
+                if let Some(order_by) = &options.order_by {
+                    s.push_str(&format!(" ORDER BY {}", order_by.columns_sql()));
+                }
+                s.push(')');
+                s
+            }
+            Term::WithinGroup(agg, order_by) => {
+                format!(
+                    "{} WITHIN GROUP (ORDER BY {})",
+                    agg.sql(),
+                    order_by.columns_sql()
+                )
+            }
         }
     }
 }
 
+/// Renders the array being subscripted. Column references and other subscripts can be
+/// subscripted directly; any other expression must be parenthesized, as in `(ARRAY[1, 2])[1]`.
+fn subscript_base_sql(term: &Term) -> String {
+    match term {
+        Term::Atom(_) | Term::Raw(_) | Term::Parens(_) | Term::Subscript(..) | Term::Slice(..) => {
+            term.sql()
+        }
+        _ => format!("({})", term.sql()),
+    }
+}
+
+/// Renders a condition without recursing down its left-hand side.
+///
+/// Repeated `and()`/`or()`/`and_where()` calls nest to the left, so a machine-generated filter
+/// with thousands of conditions would otherwise overflow the stack. The left spine is walked
+/// iteratively and the right-hand operands, which are shallow in practice, are rendered as usual.
+fn condition_chain_sql(term: &Term) -> String {
+    let mut rights = Vec::new();
+    let mut left = term;
+    while let Term::Condition(l, op, r) = left {
+        rights.push((op, r));
+        left = l;
+    }
+    let mut result = left.sql();
+    for (op, r) in rights.iter().rev() {
+        result.push(' ');
+        result.push_str(&op.sql());
+        result.push(' ');
+        result.push_str(&r.sql());
+    }
+    result
+}
+
+/// A string slice converts to a [`Term::Atom`], so helpers taking `impl Into<Term>` accept
+/// column names and pre-built terms interchangeably.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = eq(lower(Term::Atom("email")), "'a@example.com'");
+/// assert_eq!(t.sql(), "LOWER(email) = 'a@example.com'");
+/// ```
+impl<'a> From<&'a str> for Term<'a> {
+    fn from(s: &'a str) -> Self {
+        Term::Atom(s)
+    }
+}
+
+/// Borrows a String, typically a placeholder returned by param() or bind(), as a [`Term::Atom`]
+impl<'a> From<&'a String> for Term<'a> {
+    fn from(s: &'a String) -> Self {
+        Term::Atom(s.as_str())
+    }
+}
+
+/// Takes ownership of a String as a [`Term::Raw`], for SQL built at runtime
+impl<'a> From<String> for Term<'a> {
+    fn from(s: String) -> Self {
+        Term::Raw(Cow::Owned(s))
+    }
+}
+
 // Helper functions for building WHERE clauses ergonomically
 
 /// Creates an equality condition (=)
-pub fn eq<'a>(left: &'a str, right: &'a str) -> Term<'a> {
-    Term::Condition(
-        Box::new(Term::Atom(left)),
-        Op::Equals,
-        Box::new(Term::Atom(right)),
-    )
+pub fn eq<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
+    Term::Condition(Box::new(left.into()), Op::Equals, Box::new(right.into()))
 }
 
 /// Creates a not-equals condition (!=)
-pub fn ne<'a>(left: &'a str, right: &'a str) -> Term<'a> {
-    Term::Condition(
-        Box::new(Term::Atom(left)),
-        Op::NotEquals,
-        Box::new(Term::Atom(right)),
-    )
+pub fn ne<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
+    Term::Condition(Box::new(left.into()), Op::NotEquals, Box::new(right.into()))
 }
 
 /// Creates a greater-than condition (>)
-pub fn gt<'a>(left: &'a str, right: &'a str) -> Term<'a> {
+pub fn gt<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
     Term::Condition(
-        Box::new(Term::Atom(left)),
+        Box::new(left.into()),
         Op::GreaterThan,
-        Box::new(Term::Atom(right)),
+        Box::new(right.into()),
     )
 }
 
 /// Creates a less-than condition (<)
-pub fn lt<'a>(left: &'a str, right: &'a str) -> Term<'a> {
-    Term::Condition(
-        Box::new(Term::Atom(left)),
-        Op::LessThan,
-        Box::new(Term::Atom(right)),
-    )
+pub fn lt<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
+    Term::Condition(Box::new(left.into()), Op::LessThan, Box::new(right.into()))
 }
 
 /// Creates a greater-than-or-equal condition (>=)
-pub fn gte<'a>(left: &'a str, right: &'a str) -> Term<'a> {
+pub fn gte<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
     Term::Condition(
-        Box::new(Term::Atom(left)),
+        Box::new(left.into()),
         Op::GreaterOrEqual,
-        Box::new(Term::Atom(right)),
+        Box::new(right.into()),
     )
 }
 
 /// Creates a less-than-or-equal condition (<=)
-pub fn lte<'a>(left: &'a str, right: &'a str) -> Term<'a> {
+pub fn lte<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
     Term::Condition(
-        Box::new(Term::Atom(left)),
+        Box::new(left.into()),
         Op::LessOrEqual,
-        Box::new(Term::Atom(right)),
+        Box::new(right.into()),
     )
 }
 
 /// Creates a LIKE condition
-pub fn like<'a>(left: &'a str, right: &'a str) -> Term<'a> {
-    Term::Condition(
-        Box::new(Term::Atom(left)),
-        Op::Like,
-        Box::new(Term::Atom(right)),
-    )
+pub fn like<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
+    Term::Like {
+        expr: Box::new(left.into()),
+        pattern: Box::new(right.into()),
+        escape: None,
+        case_insensitive: false,
+        negated: false,
+    }
 }
 
-/// Combines two terms with AND
-pub fn and<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
-    Term::Condition(Box::new(left), Op::And, Box::new(right))
+/// Creates a case-insensitive ILIKE condition
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(ilike("name", "'al%'").sql(), "name ILIKE 'al%'");
+/// ```
+pub fn ilike<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
+    Term::Like {
+        expr: Box::new(left.into()),
+        pattern: Box::new(right.into()),
+        escape: None,
+        case_insensitive: true,
+        negated: false,
+    }
 }
 
-/// Combines two terms with OR
-pub fn or<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
-    Term::Condition(Box::new(left), Op::Or, Box::new(right))
+/// Creates a LIKE condition with an ESCAPE character, for patterns that match `%` or `_`
+/// literally. The escape character is quoted for you; the pattern is rendered as given.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(
+///     like_escape("discount", "'100\\%'", "\\").sql(),
+///     "discount LIKE '100\\%' ESCAPE '\\'"
+/// );
+/// ```
+pub fn like_escape<'a>(
+    left: impl Into<Term<'a>>,
+    right: impl Into<Term<'a>>,
+    escape: &'a str,
+) -> Term<'a> {
+    Term::Like {
+        expr: Box::new(left.into()),
+        pattern: Box::new(right.into()),
+        escape: Some(escape),
+        case_insensitive: false,
+        negated: false,
+    }
 }
 
-/// Negates a term with NOT
-pub fn not<'a>(term: Term<'a>) -> Term<'a> {
-    Term::Not(Box::new(term))
+/// Creates an ILIKE condition with an ESCAPE character.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(ilike_escape("code", "'a!_b'", "!").sql(), "code ILIKE 'a!_b' ESCAPE '!'");
+/// ```
+pub fn ilike_escape<'a>(
+    left: impl Into<Term<'a>>,
+    right: impl Into<Term<'a>>,
+    escape: &'a str,
+) -> Term<'a> {
+    Term::Like {
+        expr: Box::new(left.into()),
+        pattern: Box::new(right.into()),
+        escape: Some(escape),
+        case_insensitive: true,
+        negated: false,
+    }
 }
 
-/// Creates a CAST expression
-pub fn cast<'a>(term: Term<'a>, type_name: &'a str) -> Term<'a> {
-    Term::Cast(Box::new(term), type_name)
+/// Builds a LIKE condition matching `text` literally, with `%` added before and/or after.
+/// Backslashes, `%` and `_` in the text are escaped with a backslash and the pattern is quoted.
+fn like_literal<'a>(left: Term<'a>, text: &str, leading: bool, trailing: bool) -> Term<'a> {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    if leading {
+        pattern.push('%');
+    }
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    if trailing {
+        pattern.push('%');
+    }
+    Term::Like {
+        expr: Box::new(left),
+        pattern: Box::new(Term::Raw(Cow::Owned(crate::text(&pattern)))),
+        escape: Some("\\"),
+        case_insensitive: false,
+        negated: false,
+    }
 }
 
-/// Creates a PostgreSQL-style CAST expression (::)
-pub fn pg_cast<'a>(term: Term<'a>, type_name: &'a str) -> Term<'a> {
-    Term::PgCast(Box::new(term), type_name)
+/// Creates a LIKE condition matching rows where the column contains `text` anywhere. Wildcard
+/// characters in `text` are escaped, so user input is matched literally.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(contains("name", "foo%bar").sql(), "name LIKE '%foo\\%bar%' ESCAPE '\\'");
+/// assert_eq!(contains("name", "it's").sql(), "name LIKE '%it''s%' ESCAPE '\\'");
+/// ```
+pub fn contains<'a>(left: impl Into<Term<'a>>, text: &str) -> Term<'a> {
+    like_literal(left.into(), text, true, true)
 }
 
-/// Creates a CASE expression
-pub fn case<'a>(when_thens: Vec<WhenThen<'a>>, else_term: Option<Term<'a>>) -> Term<'a> {
-    Term::Case(CaseExpression {
-        when_thens,
-        else_term: else_term.map(Box::new),
-    })
+/// Creates a LIKE condition matching rows where the column starts with `text`, matched
+/// literally
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(starts_with("sku", "AB_").sql(), "sku LIKE 'AB\\_%' ESCAPE '\\'");
+/// ```
+pub fn starts_with<'a>(left: impl Into<Term<'a>>, text: &str) -> Term<'a> {
+    like_literal(left.into(), text, false, true)
 }
 
-/// Creates a COALESCE expression
-pub fn coalesce<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
-    Term::Coalesce(terms)
+/// Creates a LIKE condition matching rows where the column ends with `text`, matched literally
+pub fn ends_with<'a>(left: impl Into<Term<'a>>, text: &str) -> Term<'a> {
+    like_literal(left.into(), text, true, false)
 }
 
-/// Creates a NULLIF expression
-pub fn nullif<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
-    Term::NullIf(Box::new(left), Box::new(right))
+/// Combines two operands with an arithmetic operator. Operands that are themselves conditions
+/// are parenthesized so the tree's grouping survives rendering.
+fn arithmetic<'a>(left: Term<'a>, op: Op<'a>, right: Term<'a>) -> Term<'a> {
+    let operand = |t: Term<'a>| match t {
+        Term::Condition(..) => Term::Parens(Box::new(t)),
+        _ => t,
+    };
+    Term::Condition(Box::new(operand(left)), op, Box::new(operand(right)))
 }
 
-/// Creates a CONCAT expression
-pub fn concat<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
-    Term::Concat(terms)
+/// Creates an addition, `left + right`
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = mul(add(Term::Atom("a"), Term::Atom("b")), Term::Atom("2"));
+/// assert_eq!(t.sql(), "(a + b) * 2");
+/// ```
+pub fn add<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    arithmetic(left, Op::Plus, right)
 }
 
-/// Creates a SUBSTRING expression
-pub fn substring<'a>(term: Term<'a>, from: Option<Term<'a>>, for_: Option<Term<'a>>) -> Term<'a> {
-    Term::Substring(Box::new(term), from.map(Box::new), for_.map(Box::new))
+/// Creates a subtraction, `left - right`
+pub fn sub<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    arithmetic(left, Op::Minus, right)
 }
 
-/// Creates a UPPER expression
-pub fn upper<'a>(term: Term<'a>) -> Term<'a> {
-    Term::Upper(Box::new(term))
+/// Creates a multiplication, `left * right`
+pub fn mul<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    arithmetic(left, Op::Multiply, right)
 }
 
-/// Creates a LOWER expression
-pub fn lower<'a>(term: Term<'a>) -> Term<'a> {
-    Term::Lower(Box::new(term))
+/// Creates a division, `left / right`
+pub fn div<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    arithmetic(left, Op::Divide, right)
 }
 
-/// Creates a NOW() expression
-pub fn now<'a>() -> Term<'a> {
-    Term::Now
+/// References the value proposed for insertion in an ON CONFLICT DO UPDATE clause,
+/// `EXCLUDED.column`
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(excluded("email").sql(), "EXCLUDED.email");
+/// ```
+pub fn excluded<'a>(column: &str) -> Term<'a> {
+    Term::Raw(Cow::Owned(format!("EXCLUDED.{}", column)))
 }
 
-/// Creates a CURRENT_DATE expression
-pub fn current_date<'a>() -> Term<'a> {
-    Term::CurrentDate
+/// Creates a row constructor, as in `(a, b)`
+pub fn row<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
+    Term::Row(terms)
 }
 
-/// Creates an INTERVAL expression
-pub fn interval<'a>(s: &'a str) -> Term<'a> {
-    Term::Interval(s)
+/// Creates an OVERLAPS condition testing whether two time periods intersect
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = overlaps(
+///     Term::Atom("starts_at"),
+///     Term::Atom("ends_at"),
+///     Term::Atom("$1"),
+///     Term::Atom("$2"),
+/// );
+/// assert_eq!(t.sql(), "(starts_at, ends_at) OVERLAPS ($1, $2)");
+/// ```
+pub fn overlaps<'a>(
+    a_start: Term<'a>,
+    a_end: Term<'a>,
+    b_start: Term<'a>,
+    b_end: Term<'a>,
+) -> Term<'a> {
+    Term::Condition(
+        Box::new(Term::Row(vec![a_start, a_end])),
+        Op::Overlaps,
+        Box::new(Term::Row(vec![b_start, b_end])),
+    )
 }
 
-/// Creates a date addition expression
-pub fn date_add<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
-    Term::DateAdd(Box::new(left), Box::new(right))
+/// Combines two terms with AND. An OR condition on either side is parenthesized, since AND
+/// binds more tightly than OR.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = and(or(eq("a", "1"), eq("b", "2")), eq("c", "3"));
+/// assert_eq!(t.sql(), "(a = 1 OR b = 2) AND c = 3");
+/// ```
+pub fn and<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    Term::Condition(
+        Box::new(and_operand(left)),
+        Op::And,
+        Box::new(and_operand(right)),
+    )
 }
 
-/// Creates a date subtraction expression
-pub fn date_sub<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
-    Term::DateSub(Box::new(left), Box::new(right))
+/// Parenthesizes an OR condition so it keeps its meaning as an operand of AND. A shared OR
+/// is wrapped too; one already in parentheses is left as is.
+fn and_operand(term: Term) -> Term {
+    match unshared(&term) {
+        Term::Condition(_, Op::Or, _) => Term::Parens(Box::new(term)),
+        _ => term,
+    }
 }
 
-/// Wraps a term in parentheses
-pub fn parens<'a>(term: Term<'a>) -> Term<'a> {
-    Term::Parens(Box::new(term))
+/// Combines two terms with OR
+pub fn or<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    Term::Condition(Box::new(left), Op::Or, Box::new(right))
 }
 
-// Convenience functions for common SQL patterns
-
-/// Creates an IN clause
-/// Example: in_("status", vec!["'active'", "'pending'"]) => "status IN ('active', 'pending')"
-pub fn in_<'a>(column: &'a str, values: Vec<&'a str>) -> Term<'a> {
-    let values_str = values.join(", ");
-    Term::Atom(Box::leak(
-        format!("{} IN ({})", column, values_str).into_boxed_str(),
-    ))
+/// Negates a term.
+///
+/// Predicates with a negated form are flipped in place, giving the idiomatic `NOT IN`
+/// (for lists and subqueries),
+/// `NOT BETWEEN`, `NOT LIKE`, `IS NOT NULL` and `NOT EXISTS`, and a double negation cancels
+/// out. Anything else gets a `NOT` prefix, with AND/OR chains parenthesized so the NOT covers
+/// the whole condition. A shared term is negated by what it holds.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(not(in_("status", vec!["'a'", "'b'"])).sql(), "status NOT IN ('a', 'b')");
+/// assert_eq!(not(is_null("email")).sql(), "email IS NOT NULL");
+/// assert_eq!(not(eq("a", "1")).sql(), "NOT a = 1");
+/// assert_eq!(not(or(eq("a", "1"), eq("b", "2"))).sql(), "NOT (a = 1 OR b = 2)");
+/// ```
+pub fn not<'a>(term: Term<'a>) -> Term<'a> {
+    match term {
+        Term::Shared(shared) => not(Arc::unwrap_or_clone(shared)),
+        Term::InList {
+            expr,
+            list,
+            negated,
+        } => Term::InList {
+            expr,
+            list,
+            negated: !negated,
+        },
+        Term::Between {
+            expr,
+            low,
+            high,
+            negated,
+        } => Term::Between {
+            expr,
+            low,
+            high,
+            negated: !negated,
+        },
+        Term::IsNull { expr, negated } => Term::IsNull {
+            expr,
+            negated: !negated,
+        },
+        Term::Like {
+            expr,
+            pattern,
+            escape,
+            case_insensitive,
+            negated,
+        } => Term::Like {
+            expr,
+            pattern,
+            escape,
+            case_insensitive,
+            negated: !negated,
+        },
+        Term::Exists(q) => Term::NotExists(q),
+        Term::NotExists(q) => Term::Exists(q),
+        Term::Condition(l, Op::In, r) => Term::Condition(l, Op::NotIn, r),
+        Term::Condition(l, Op::NotIn, r) => Term::Condition(l, Op::In, r),
+        Term::Condition(l, Op::IsDistinctFrom, r) => Term::Condition(l, Op::IsNotDistinctFrom, r),
+        Term::Condition(l, Op::IsNotDistinctFrom, r) => Term::Condition(l, Op::IsDistinctFrom, r),
+        Term::Not(inner) => *inner,
+        Term::Condition(_, Op::And | Op::Or, _) => {
+            Term::Not(Box::new(Term::Parens(Box::new(term))))
+        }
+        _ => Term::Not(Box::new(term)),
+    }
 }
 
-/// Creates a BETWEEN clause
-/// Example: between("age", "18", "65") => "age BETWEEN 18 AND 65"
-pub fn between<'a>(column: &'a str, low: &'a str, high: &'a str) -> Term<'a> {
-    Term::Atom(Box::leak(
-        format!("{} BETWEEN {} AND {}", column, low, high).into_boxed_str(),
-    ))
-}
+impl<'a> Term<'a> {
+    /// Combines this term with another using AND, as and() does
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let t = eq("a", "1").and(gt("b", "2")).or(is_null("c"));
+    /// assert_eq!(t.sql(), "a = 1 AND b > 2 OR c IS NULL");
+    /// ```
+    pub fn and(self, other: Term<'a>) -> Term<'a> {
+        and(self, other)
+    }
 
-/// Creates an IS NULL condition
-/// Example: is_null("deleted_at") => "deleted_at IS NULL"
-pub fn is_null<'a>(column: &'a str) -> Term<'a> {
-    Term::Atom(Box::leak(format!("{} IS NULL", column).into_boxed_str()))
-}
+    /// Combines this term with another using OR, as or() does
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let t = eq("a", "1").or(eq("b", "2")).and(flag("active"));
+    /// assert_eq!(t.sql(), "(a = 1 OR b = 2) AND active");
+    /// ```
+    pub fn or(self, other: Term<'a>) -> Term<'a> {
+        or(self, other)
+    }
 
-/// Creates an IS NOT NULL condition
-/// Example: is_not_null("created_at") => "created_at IS NOT NULL"
-pub fn is_not_null<'a>(column: &'a str) -> Term<'a> {
-    Term::Atom(Box::leak(
-        format!("{} IS NOT NULL", column).into_boxed_str(),
-    ))
-}
+    /// Negates this term, as not() does
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// assert_eq!(in_("id", vec!["1", "2"]).not().sql(), "id NOT IN (1, 2)");
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Term<'a> {
+        not(self)
+    }
 
-// Nested query helpers
+    /// Renders this term as a `WHERE ...` fragment, or an empty string for `Term::Null`
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let active = eq("active", "true").and(is_null("deleted_at"));
+    /// assert_eq!(active.where_sql(), "WHERE active = true AND deleted_at IS NULL");
+    /// assert_eq!(Term::Null.where_sql(), "");
+    /// ```
+    pub fn where_sql(&self) -> String {
+        match self {
+            Term::Null => String::new(),
+            _ => format!("WHERE {}", self.sql()),
+        }
+    }
+}
 
-/// Creates an EXISTS condition with a subquery
-/// Example: exists(subquery) => "EXISTS (SELECT ...)"
-pub fn exists<'a>(subquery: Query<'a>) -> Term<'a> {
-    let sql = format!("EXISTS ({})", subquery.sql());
-    Term::Atom(Box::leak(sql.into_boxed_str()))
+impl<'a> Term<'a> {
+    /// Returns the terms directly inside this one, in render order. Subqueries have their own
+    /// scope and are not entered; see subquery().
+    pub(crate) fn children(&self) -> Vec<&Term<'a>> {
+        match self {
+            Term::Condition(l, _, r)
+            | Term::NullIf(l, r)
+            | Term::DateAdd(l, r)
+            | Term::DateSub(l, r)
+            | Term::Subscript(l, r)
+            | Term::Position(l, r)
+            | Term::Like {
+                expr: l,
+                pattern: r,
+                ..
+            } => vec![l, r],
+            Term::Parens(t)
+            | Term::Not(t)
+            | Term::Cast(t, _)
+            | Term::PgCast(t, _)
+            | Term::Upper(t)
+            | Term::Lower(t)
+            | Term::WithinGroup(t, _)
+            | Term::AnyAll { left: t, .. }
+            | Term::IsNull { expr: t, .. } => vec![t],
+            Term::Shared(t) => vec![t],
+            Term::Over(t, window) => std::iter::once(&**t)
+                .chain(window.frame_offsets())
+                .collect(),
+            Term::Function(_, terms)
+            | Term::Aggregate(_, terms, _)
+            | Term::Coalesce(terms)
+            | Term::Concat(terms)
+            | Term::Row(terms) => terms.iter().collect(),
+            Term::InList { expr, list, .. } => std::iter::once(&**expr).chain(list).collect(),
+            Term::Between {
+                expr, low, high, ..
+            } => vec![expr, low, high],
+            Term::Slice(t, from, for_) | Term::Substring(t, from, for_) => std::iter::once(t)
+                .chain(from.iter().chain(for_))
+                .map(|t| &**t)
+                .collect(),
+            Term::Overlay {
+                target,
+                placing,
+                from,
+                for_,
+            } => [target, placing, from]
+                .into_iter()
+                .chain(for_)
+                .map(|t| &**t)
+                .collect(),
+            Term::Trim { chars, from, .. } => chars.iter().chain([from]).map(|t| &**t).collect(),
+            Term::Case(c) => c
+                .operand
+                .iter()
+                .map(|t| &**t)
+                .chain(c.when_thens.iter().flat_map(|wt| [&wt.when, &wt.then]))
+                .chain(c.else_term.iter().map(|t| &**t))
+                .collect(),
+            Term::Atom(_)
+            | Term::Raw(_)
+            | Term::Null
+            | Term::Subquery(_)
+            | Term::Exists(_)
+            | Term::NotExists(_)
+            | Term::Now
+            | Term::CurrentDate
+            | Term::Interval(_) => vec![],
+        }
+    }
+
+    /// The mutable counterpart of children(). A shared subtree is copied first if it is
+    /// referenced elsewhere, so the change stays local to this term.
+    pub(crate) fn children_mut(&mut self) -> Vec<&mut Term<'a>> {
+        match self {
+            Term::Condition(l, _, r)
+            | Term::NullIf(l, r)
+            | Term::DateAdd(l, r)
+            | Term::DateSub(l, r)
+            | Term::Subscript(l, r)
+            | Term::Position(l, r)
+            | Term::Like {
+                expr: l,
+                pattern: r,
+                ..
+            } => vec![l, r],
+            Term::Parens(t)
+            | Term::Not(t)
+            | Term::Cast(t, _)
+            | Term::PgCast(t, _)
+            | Term::Upper(t)
+            | Term::Lower(t)
+            | Term::WithinGroup(t, _)
+            | Term::AnyAll { left: t, .. }
+            | Term::IsNull { expr: t, .. } => vec![t],
+            Term::Shared(t) => vec![Arc::make_mut(t)],
+            Term::Over(t, window) => std::iter::once(&mut **t)
+                .chain(window.frame_offsets_mut())
+                .collect(),
+            Term::Function(_, terms)
+            | Term::Aggregate(_, terms, _)
+            | Term::Coalesce(terms)
+            | Term::Concat(terms)
+            | Term::Row(terms) => terms.iter_mut().collect(),
+            Term::InList { expr, list, .. } => std::iter::once(&mut **expr).chain(list).collect(),
+            Term::Between {
+                expr, low, high, ..
+            } => vec![expr, low, high],
+            Term::Slice(t, from, for_) | Term::Substring(t, from, for_) => std::iter::once(t)
+                .chain(from.iter_mut().chain(for_))
+                .map(|t| &mut **t)
+                .collect(),
+            Term::Overlay {
+                target,
+                placing,
+                from,
+                for_,
+            } => [target, placing, from]
+                .into_iter()
+                .chain(for_)
+                .map(|t| &mut **t)
+                .collect(),
+            Term::Trim { chars, from, .. } => {
+                chars.iter_mut().chain([from]).map(|t| &mut **t).collect()
+            }
+            Term::Case(c) => c
+                .operand
+                .iter_mut()
+                .map(|t| &mut **t)
+                .chain(
+                    c.when_thens
+                        .iter_mut()
+                        .flat_map(|wt| [&mut wt.when, &mut wt.then]),
+                )
+                .chain(c.else_term.iter_mut().map(|t| &mut **t))
+                .collect(),
+            Term::Atom(_)
+            | Term::Raw(_)
+            | Term::Null
+            | Term::Subquery(_)
+            | Term::Exists(_)
+            | Term::NotExists(_)
+            | Term::Now
+            | Term::CurrentDate
+            | Term::Interval(_) => vec![],
+        }
+    }
+
+    /// Returns the subquery this term wraps, if any.
+    pub(crate) fn subquery(&self) -> Option<&Query<'a>> {
+        match self {
+            Term::Subquery(query)
+            | Term::Exists(query)
+            | Term::NotExists(query)
+            | Term::AnyAll {
+                subquery: query, ..
+            } => Some(query),
+            _ => None,
+        }
+    }
+
+    /// The mutable counterpart of subquery().
+    pub(crate) fn subquery_mut(&mut self) -> Option<&mut Query<'a>> {
+        match self {
+            Term::Subquery(query)
+            | Term::Exists(query)
+            | Term::NotExists(query)
+            | Term::AnyAll {
+                subquery: query, ..
+            } => Some(query),
+            _ => None,
+        }
+    }
 }
 
-/// Creates a NOT EXISTS condition with a subquery
-/// Example: not_exists(subquery) => "NOT EXISTS (SELECT ...)"
-pub fn not_exists<'a>(subquery: Query<'a>) -> Term<'a> {
-    let sql = format!("NOT EXISTS ({})", subquery.sql());
-    Term::Atom(Box::leak(sql.into_boxed_str()))
+/// Visits `term` and every term below it in pre-order, left to right. Returning false from
+/// `visit` skips the children of that term.
+///
+/// The walk keeps its own stack, so the long left-leaning chains built by repeated and()/or()
+/// calls cannot overflow the call stack.
+pub(crate) fn walk_terms<'t, 'a>(term: &'t Term<'a>, mut visit: impl FnMut(&'t Term<'a>) -> bool) {
+    let mut stack = vec![term];
+    while let Some(term) = stack.pop() {
+        if visit(term) {
+            stack.extend(term.children().into_iter().rev());
+        }
+    }
 }
 
-/// Creates an IN condition with a subquery
-/// Example: in_subquery("user_id", subquery) => "user_id IN (SELECT ...)"
-pub fn in_subquery<'a>(column: &'a str, subquery: Query<'a>) -> Term<'a> {
+/// The mutable counterpart of walk_terms(). The children are taken after `visit` runs, so a
+/// replaced term is walked in its new form.
+pub(crate) fn walk_terms_mut<'a>(
+    term: &mut Term<'a>,
+    mut visit: impl FnMut(&mut Term<'a>) -> bool,
+) {
+    let mut stack = vec![term];
+    while let Some(term) = stack.pop() {
+        if visit(term) {
+            stack.extend(term.children_mut().into_iter().rev());
+        }
+    }
+}
+
+/// Renders an optional predicate as a `WHERE ...` fragment, empty when there is none
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let filter: Option<Term> = Some(gt("age", "18"));
+/// assert_eq!(filter.where_clause_sql(), "WHERE age > 18");
+/// assert_eq!(None::<Term>.where_clause_sql(), "");
+/// ```
+pub trait WhereClauseSql {
+    /// Returns `WHERE <term>`, or an empty string
+    fn where_clause_sql(&self) -> String;
+}
+
+impl<'a> WhereClauseSql for Option<Term<'a>> {
+    fn where_clause_sql(&self) -> String {
+        self.as_ref().map(Term::where_sql).unwrap_or_default()
+    }
+}
+
+/// Creates a CAST expression
+pub fn cast<'a>(term: Term<'a>, type_name: &'a str) -> Term<'a> {
+    Term::Cast(Box::new(term), type_name)
+}
+
+/// Creates a PostgreSQL-style CAST expression (::)
+pub fn pg_cast<'a>(term: Term<'a>, type_name: &'a str) -> Term<'a> {
+    Term::PgCast(Box::new(term), type_name)
+}
+
+/// Creates a CASE expression
+pub fn case<'a>(when_thens: Vec<WhenThen<'a>>, else_term: Option<Term<'a>>) -> Term<'a> {
+    Term::Case(CaseExpression {
+        operand: None,
+        when_thens,
+        else_term: else_term.map(Box::new),
+    })
+}
+
+/// Creates a simple CASE expression, which compares one operand against each WHEN value
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = case_of(
+///     Term::Atom("status"),
+///     vec![
+///         WhenThen { when: Term::Atom("'active'"), then: Term::Atom("1") },
+///         WhenThen { when: Term::Atom("'paused'"), then: Term::Atom("2") },
+///     ],
+///     Some(Term::Atom("0")),
+/// );
+/// assert_eq!(t.sql(), "CASE status WHEN 'active' THEN 1 WHEN 'paused' THEN 2 ELSE 0 END");
+/// ```
+pub fn case_of<'a>(
+    expr: Term<'a>,
+    when_thens: Vec<WhenThen<'a>>,
+    else_term: Option<Term<'a>>,
+) -> Term<'a> {
+    Term::Case(CaseExpression {
+        operand: Some(Box::new(expr)),
+        when_thens,
+        else_term: else_term.map(Box::new),
+    })
+}
+
+/// Creates a COALESCE expression
+pub fn coalesce<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
+    Term::Coalesce(terms)
+}
+
+/// Substitutes `fallback` when `term` is NULL, `COALESCE(term, fallback)`
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = default_to(Term::Atom("nickname"), Term::Atom("name"));
+/// assert_eq!(t.sql(), "COALESCE(nickname, name)");
+/// ```
+pub fn default_to<'a>(term: impl Into<Term<'a>>, fallback: impl Into<Term<'a>>) -> Term<'a> {
+    Term::Coalesce(vec![term.into(), fallback.into()])
+}
+
+/// Creates a NULL-safe equality condition, `a IS NOT DISTINCT FROM b`, which is true when both
+/// sides are NULL and false when only one is
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = nullsafe_eq("manager_id", "$1");
+/// assert_eq!(t.sql(), "manager_id IS NOT DISTINCT FROM $1");
+/// assert_eq!(not(t).sql(), "manager_id IS DISTINCT FROM $1");
+/// ```
+pub fn nullsafe_eq<'a>(left: impl Into<Term<'a>>, right: impl Into<Term<'a>>) -> Term<'a> {
     Term::Condition(
-        Box::new(Term::Atom(column)),
-        Op::In,
-        Box::new(Term::Subquery(Box::new(subquery))),
+        Box::new(left.into()),
+        Op::IsNotDistinctFrom,
+        Box::new(right.into()),
+    )
+}
+
+/// Creates a NULLIF expression
+pub fn nullif<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    Term::NullIf(Box::new(left), Box::new(right))
+}
+
+/// Creates a CONCAT expression
+pub fn concat<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
+    Term::Concat(terms)
+}
+
+/// Creates a SUBSTRING expression
+pub fn substring<'a>(term: Term<'a>, from: Option<Term<'a>>, for_: Option<Term<'a>>) -> Term<'a> {
+    Term::Substring(Box::new(term), from.map(Box::new), for_.map(Box::new))
+}
+
+/// Creates a regular-expression SUBSTRING, `SUBSTRING(string FROM pattern)`, which returns
+/// the part of the string matching the POSIX pattern (or its first parenthesized group)
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = substring_regex(Term::Atom("email"), Term::Atom("'@(.*)$'"));
+/// assert_eq!(t.sql(), "SUBSTRING(email FROM '@(.*)$')");
+/// ```
+pub fn substring_regex<'a>(term: Term<'a>, pattern: Term<'a>) -> Term<'a> {
+    Term::Substring(Box::new(term), Some(Box::new(pattern)), None)
+}
+
+/// Creates an OVERLAY expression, which replaces part of a string
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = overlay(Term::Atom("code"), Term::Atom("'X'"), Term::Atom("2"), Some(Term::Atom("1")));
+/// assert_eq!(t.sql(), "OVERLAY(code PLACING 'X' FROM 2 FOR 1)");
+/// ```
+pub fn overlay<'a>(
+    target: Term<'a>,
+    placing: Term<'a>,
+    from: Term<'a>,
+    for_: Option<Term<'a>>,
+) -> Term<'a> {
+    Term::Overlay {
+        target: Box::new(target),
+        placing: Box::new(placing),
+        from: Box::new(from),
+        for_: for_.map(Box::new),
+    }
+}
+
+/// Creates a POSITION expression, the 1-based index of `substring` in `string` (0 if absent)
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = position(Term::Atom("'@'"), Term::Atom("email"));
+/// assert_eq!(t.sql(), "POSITION('@' IN email)");
+/// ```
+pub fn position<'a>(substring: Term<'a>, string: Term<'a>) -> Term<'a> {
+    Term::Position(Box::new(substring), Box::new(string))
+}
+
+/// Creates a UPPER expression
+pub fn upper<'a>(term: Term<'a>) -> Term<'a> {
+    Term::Upper(Box::new(term))
+}
+
+/// Creates a LOWER expression
+pub fn lower<'a>(term: Term<'a>) -> Term<'a> {
+    Term::Lower(Box::new(term))
+}
+
+/// Creates a GREATEST expression, the largest non-null argument
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = greatest(vec![Term::Atom("stock"), Term::Atom("0")]);
+/// assert_eq!(t.sql(), "GREATEST(stock, 0)");
+/// ```
+pub fn greatest<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
+    func("GREATEST", terms)
+}
+
+/// Creates a LEAST expression, the smallest non-null argument
+pub fn least<'a>(terms: Vec<Term<'a>>) -> Term<'a> {
+    func("LEAST", terms)
+}
+
+/// Creates an ABS expression
+pub fn abs<'a>(term: Term<'a>) -> Term<'a> {
+    func("ABS", vec![term])
+}
+
+/// Creates a ROUND expression, rounding to `places` decimal places or to an integer when None
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(round(Term::Atom("price"), Some(2)).sql(), "ROUND(price, 2)");
+/// assert_eq!(round(Term::Atom("price"), None).sql(), "ROUND(price)");
+/// ```
+pub fn round<'a>(term: Term<'a>, places: Option<i32>) -> Term<'a> {
+    match places {
+        Some(places) => func(
+            "ROUND",
+            vec![term, Term::Raw(Cow::Owned(places.to_string()))],
+        ),
+        None => func("ROUND", vec![term]),
+    }
+}
+
+/// Creates a CEIL expression
+pub fn ceil<'a>(term: Term<'a>) -> Term<'a> {
+    func("CEIL", vec![term])
+}
+
+/// Creates a FLOOR expression
+pub fn floor<'a>(term: Term<'a>) -> Term<'a> {
+    func("FLOOR", vec![term])
+}
+
+/// Creates a MOD expression, the remainder of `dividend / divisor`. Named `mod_` because
+/// `mod` is a keyword.
+pub fn mod_<'a>(dividend: Term<'a>, divisor: Term<'a>) -> Term<'a> {
+    func("MOD", vec![dividend, divisor])
+}
+
+/// Wraps a term so that clones of it share one copy of the subtree.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let bucket = share(case(
+///     vec![WhenThen { when: lt("age", "18"), then: Term::Atom("'minor'") }],
+///     Some(Term::Atom("'adult'")),
+/// ));
+/// let mut qb = Q();
+/// let query = qb.select_expressions(vec![SelectExpression::Expr(bucket.clone(), Some("bucket"))])
+///     .from("users")
+///     .where_(Term::Condition(Box::new(bucket), Op::NotEquals, Box::new(Term::Atom("'minor'"))))
+///     .build();
+/// assert_eq!(
+///     query.sql(),
+///     "SELECT CASE WHEN age < 18 THEN 'minor' ELSE 'adult' END AS bucket FROM users \
+///      WHERE CASE WHEN age < 18 THEN 'minor' ELSE 'adult' END != 'minor'"
+/// );
+/// ```
+pub fn share<'a>(term: Term<'a>) -> Term<'a> {
+    match term {
+        Term::Shared(_) => term,
+        _ => Term::Shared(Arc::new(term)),
+    }
+}
+
+/// Returns the term a chain of Shared wrappers holds.
+fn unshared<'t, 'a>(term: &'t Term<'a>) -> &'t Term<'a> {
+    match term {
+        Term::Shared(t) => unshared(t),
+        _ => term,
+    }
+}
+
+/// Creates a function call, `name(args)`
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = func("date_trunc", vec![Term::Atom("'month'"), Term::Atom("created_at")]);
+/// assert_eq!(t.sql(), "date_trunc('month', created_at)");
+/// ```
+pub fn func<'a>(name: &'a str, args: Vec<Term<'a>>) -> Term<'a> {
+    Term::Function(name, args)
+}
+
+/// Creates a `jsonb_set(target, path, value, create_missing)` call, which replaces the value
+/// at `path` inside a jsonb document.
+///
+/// The path is given as its keys (or array indexes) and rendered as a correctly quoted text
+/// array literal, so keys containing commas, braces, quotes or spaces are safe. `value` must
+/// be a jsonb expression, e.g. a quoted JSON literal.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = jsonb_set(Term::Atom("data"), vec!["a", "b"], Term::Atom("'\"x\"'"), true);
+/// assert_eq!(t.sql(), "jsonb_set(data, '{a,b}', '\"x\"', true)");
+/// ```
+pub fn jsonb_set<'a>(
+    target: Term<'a>,
+    path: Vec<&str>,
+    value: Term<'a>,
+    create_missing: bool,
+) -> Term<'a> {
+    let elements: Vec<String> = path
+        .iter()
+        .map(|key| {
+            let needs_quotes = key.is_empty()
+                || key.eq_ignore_ascii_case("null")
+                || key
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '{' | '}' | ',' | '"' | '\\'));
+            if needs_quotes {
+                format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+            } else {
+                key.to_string()
+            }
+        })
+        .collect();
+    let path_literal = format!("'{{{}}}'", elements.join(",").replace('\'', "''"));
+    Term::Function(
+        "jsonb_set",
+        vec![
+            target,
+            Term::Raw(Cow::Owned(path_literal)),
+            value,
+            Term::Atom(if create_missing { "true" } else { "false" }),
+        ],
     )
 }
 
-/// Creates a comparison with ANY (subquery)
-/// Example: any("price", Op::GreaterThan, subquery) => "price > ANY (SELECT ...)"
-pub fn any<'a>(column: &'a str, op: Op<'a>, subquery: Query<'a>) -> Term<'a> {
-    let sql = format!("{} {} ANY ({})", column, op.sql(), subquery.sql());
-    Term::Atom(Box::leak(sql.into_boxed_str()))
+/// Creates an `unnest(array)` call, which expands an array to a set of rows
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(unnest(Term::Atom("tags")).sql(), "unnest(tags)");
+/// ```
+pub fn unnest<'a>(array: Term<'a>) -> Term<'a> {
+    Term::Function("unnest", vec![array])
+}
+
+/// Creates a `generate_series(start, stop[, step])` call. Use it as a FROM source with
+/// from_function() to produce a row per value, e.g. a calendar of days.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let series = generate_series(
+///     pg_cast(Term::Atom("'2024-01-01'"), "date"),
+///     pg_cast(Term::Atom("'2024-12-31'"), "date"),
+///     Some(interval("1 day")),
+/// );
+/// let mut qb = Q();
+/// let query = qb.select(vec!["day"]).from_function(series, "d", vec!["day"], false).build();
+/// assert_eq!(
+///     query.sql(),
+///     "SELECT day FROM generate_series('2024-01-01'::date, '2024-12-31'::date, INTERVAL '1 day') AS d (day)"
+/// );
+/// ```
+pub fn generate_series<'a>(start: Term<'a>, stop: Term<'a>, step: Option<Term<'a>>) -> Term<'a> {
+    let mut args = vec![start, stop];
+    args.extend(step);
+    Term::Function("generate_series", args)
+}
+
+/// Creates an array element access, `arr[idx]`. PostgreSQL arrays are 1-based.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let first_tag = subscript(Term::Atom("tags"), Term::Atom("1"));
+/// assert_eq!(first_tag.sql(), "tags[1]");
+/// let cell = subscript(subscript(Term::Atom("matrix"), Term::Atom("2")), Term::Atom("3"));
+/// assert_eq!(cell.sql(), "matrix[2][3]");
+/// ```
+pub fn subscript<'a>(arr: Term<'a>, idx: Term<'a>) -> Term<'a> {
+    Term::Subscript(Box::new(arr), Box::new(idx))
+}
+
+/// Creates an array slice, `arr[lo:hi]`. An omitted bound extends to that end of the array.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let s = slice(Term::Atom("scores"), Some(Term::Atom("1")), Some(Term::Atom("3")));
+/// assert_eq!(s.sql(), "scores[1:3]");
+/// let tail = slice(Term::Atom("scores"), Some(Term::Atom("2")), None);
+/// assert_eq!(tail.sql(), "scores[2:]");
+/// ```
+pub fn slice<'a>(arr: Term<'a>, lo: Option<Term<'a>>, hi: Option<Term<'a>>) -> Term<'a> {
+    Term::Slice(Box::new(arr), lo.map(Box::new), hi.map(Box::new))
+}
+
+/// Creates a TRIM expression removing whitespace from both ends
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(trim(Term::Atom("name")).sql(), "TRIM(name)");
+/// ```
+pub fn trim<'a>(term: Term<'a>) -> Term<'a> {
+    Term::Trim {
+        side: TrimSide::Both,
+        chars: None,
+        from: Box::new(term),
+    }
+}
+
+/// Creates a TRIM(LEADING ...) expression, removing `chars` (or whitespace) from the start
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = trim_leading(Term::Atom("account_no"), Some(Term::Atom("'0'")));
+/// assert_eq!(t.sql(), "TRIM(LEADING '0' FROM account_no)");
+/// ```
+pub fn trim_leading<'a>(term: Term<'a>, chars: Option<Term<'a>>) -> Term<'a> {
+    Term::Trim {
+        side: TrimSide::Leading,
+        chars: chars.map(Box::new),
+        from: Box::new(term),
+    }
+}
+
+/// Creates a TRIM(TRAILING ...) expression, removing `chars` (or whitespace) from the end
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = trim_trailing(Term::Atom("path"), Some(Term::Atom("'/'")));
+/// assert_eq!(t.sql(), "TRIM(TRAILING '/' FROM path)");
+/// ```
+pub fn trim_trailing<'a>(term: Term<'a>, chars: Option<Term<'a>>) -> Term<'a> {
+    Term::Trim {
+        side: TrimSide::Trailing,
+        chars: chars.map(Box::new),
+        from: Box::new(term),
+    }
+}
+
+/// Creates a TRIM(BOTH ...) expression, removing `chars` (or whitespace) from both ends
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = trim_both(Term::Atom("code"), Some(Term::Atom("'x'")));
+/// assert_eq!(t.sql(), "TRIM(BOTH 'x' FROM code)");
+/// ```
+pub fn trim_both<'a>(term: Term<'a>, chars: Option<Term<'a>>) -> Term<'a> {
+    Term::Trim {
+        side: TrimSide::Both,
+        chars: chars.map(Box::new),
+        from: Box::new(term),
+    }
+}
+
+/// Creates a NOW() expression
+pub fn now<'a>() -> Term<'a> {
+    Term::Now
+}
+
+/// Creates a CURRENT_DATE expression
+pub fn current_date<'a>() -> Term<'a> {
+    Term::CurrentDate
+}
+
+/// Creates an INTERVAL expression from a raw interval string, as in `interval("1 day")`.
+/// Prefer [`Interval`], interval_days() or interval_hours() when the amount is computed.
+pub fn interval<'a>(s: &'a str) -> Term<'a> {
+    Term::Interval(s)
+}
+
+/// Creates an INTERVAL of whole days
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(interval_days(30).sql(), "INTERVAL '30 days'");
+/// ```
+pub fn interval_days<'a>(days: i64) -> Term<'a> {
+    Interval::default().days(days).into()
+}
+
+/// Creates an INTERVAL of whole hours
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(interval_hours(1).sql(), "INTERVAL '1 hour'");
+/// ```
+pub fn interval_hours<'a>(hours: i64) -> Term<'a> {
+    Interval::default().hours(hours).into()
+}
+
+/// A typed interval, rendered as `INTERVAL '1 year 2 mons 3 days'`.
+///
+/// Zero components are left out; an all-zero interval renders as `INTERVAL '0 secs'`.
+/// Converts into a [`Term`] for use with date_add() and date_sub().
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let i = Interval::default().years(1).months(2).days(3);
+/// assert_eq!(i.sql(), "INTERVAL '1 year 2 mons 3 days'");
+/// assert_eq!(
+///     date_sub(now(), i.into()).sql(),
+///     "NOW() - INTERVAL '1 year 2 mons 3 days'"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    /// Whole years
+    pub years: i64,
+    /// Whole months
+    pub months: i64,
+    /// Whole days
+    pub days: i64,
+    /// Whole hours
+    pub hours: i64,
+    /// Whole minutes
+    pub minutes: i64,
+    /// Whole seconds
+    pub seconds: i64,
+}
+
+impl Interval {
+    /// Sets the years component
+    pub fn years(mut self, years: i64) -> Interval {
+        self.years = years;
+        self
+    }
+
+    /// Sets the months component
+    pub fn months(mut self, months: i64) -> Interval {
+        self.months = months;
+        self
+    }
+
+    /// Sets the days component
+    pub fn days(mut self, days: i64) -> Interval {
+        self.days = days;
+        self
+    }
+
+    /// Sets the hours component
+    pub fn hours(mut self, hours: i64) -> Interval {
+        self.hours = hours;
+        self
+    }
+
+    /// Sets the minutes component
+    pub fn minutes(mut self, minutes: i64) -> Interval {
+        self.minutes = minutes;
+        self
+    }
+
+    /// Sets the seconds component
+    pub fn seconds(mut self, seconds: i64) -> Interval {
+        self.seconds = seconds;
+        self
+    }
+}
+
+impl Sql for Interval {
+    fn sql(&self) -> String {
+        let parts: Vec<String> = [
+            (self.years, "year", "years"),
+            (self.months, "mon", "mons"),
+            (self.days, "day", "days"),
+            (self.hours, "hour", "hours"),
+            (self.minutes, "min", "mins"),
+            (self.seconds, "sec", "secs"),
+        ]
+        .iter()
+        .filter(|(n, _, _)| *n != 0)
+        .map(|(n, one, many)| format!("{} {}", n, if n.abs() == 1 { one } else { many }))
+        .collect();
+        if parts.is_empty() {
+            "INTERVAL '0 secs'".to_string()
+        } else {
+            format!("INTERVAL '{}'", parts.join(" "))
+        }
+    }
+}
+
+impl<'a> From<Interval> for Term<'a> {
+    fn from(interval: Interval) -> Self {
+        Term::Raw(Cow::Owned(interval.sql()))
+    }
+}
+
+/// Creates a date addition expression
+pub fn date_add<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    Term::DateAdd(Box::new(left), Box::new(right))
+}
+
+/// Creates a date subtraction expression
+pub fn date_sub<'a>(left: Term<'a>, right: Term<'a>) -> Term<'a> {
+    Term::DateSub(Box::new(left), Box::new(right))
+}
+
+/// Creates a raw SQL fragment that is passed through verbatim.
+///
+/// This is an escape hatch for syntax the builder does not model yet. The fragment is
+/// **not** escaped or validated in any way; never pass untrusted input to it.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let term = and(eq("id", "1"), raw("tsv @@ to_tsquery('cat')"));
+/// assert_eq!(term.sql(), "id = 1 AND tsv @@ to_tsquery('cat')");
+///
+/// let owned = raw(format!("score > {}", 10));
+/// assert_eq!(owned.sql(), "score > 10");
+/// ```
+pub fn raw<'a>(s: impl Into<Cow<'a, str>>) -> Term<'a> {
+    Term::Raw(s.into())
+}
+
+/// Creates an aggregate function call with the given options
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let term = aggregate(
+///     "ARRAY_AGG",
+///     vec![Term::Atom("tag")],
+///     AggOptions { distinct: true, order_by: Some(OrderBy { columns: vec![OrderedColumn::Asc("tag")] }) },
+/// );
+/// assert_eq!(term.sql(), "ARRAY_AGG(DISTINCT tag ORDER BY tag ASC)");
+/// ```
+pub fn aggregate<'a>(name: &'a str, args: Vec<Term<'a>>, options: AggOptions<'a>) -> Term<'a> {
+    Term::Aggregate(name, args, options)
+}
+
+/// Creates a COUNT(DISTINCT ...) expression
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(count_distinct(Term::Atom("user_id")).sql(), "COUNT(DISTINCT user_id)");
+/// ```
+pub fn count_distinct<'a>(term: Term<'a>) -> Term<'a> {
+    Term::Aggregate(
+        "COUNT",
+        vec![term],
+        AggOptions {
+            distinct: true,
+            order_by: None,
+        },
+    )
+}
+
+/// Creates a STRING_AGG expression
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let options = AggOptions {
+///     distinct: false,
+///     order_by: Some(OrderBy { columns: vec![OrderedColumn::Asc("name")] }),
+/// };
+/// let term = string_agg(Term::Atom("name"), Term::Atom("', '"), options);
+/// assert_eq!(term.sql(), "STRING_AGG(name, ', ' ORDER BY name ASC)");
+/// ```
+pub fn string_agg<'a>(term: Term<'a>, delimiter: Term<'a>, options: AggOptions<'a>) -> Term<'a> {
+    Term::Aggregate("STRING_AGG", vec![term, delimiter], options)
+}
+
+/// Creates an ARRAY_AGG expression
+pub fn array_agg<'a>(term: Term<'a>, options: AggOptions<'a>) -> Term<'a> {
+    Term::Aggregate("ARRAY_AGG", vec![term], options)
+}
+
+/// Applies an ordered-set aggregate to input sorted by `order_by`,
+/// `agg WITHIN GROUP (ORDER BY ...)`
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let median = within_group(
+///     percentile_cont(0.5),
+///     OrderBy { columns: vec![OrderedColumn::Asc("response_time")] },
+/// );
+/// assert_eq!(
+///     median.sql(),
+///     "PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY response_time ASC)"
+/// );
+/// ```
+pub fn within_group<'a>(agg: Term<'a>, order_by: OrderBy<'a>) -> Term<'a> {
+    Term::WithinGroup(Box::new(agg), order_by)
+}
+
+/// Creates a `PERCENTILE_CONT(fraction)` call, the interpolated percentile, for use with
+/// within_group()
+pub fn percentile_cont<'a>(fraction: f64) -> Term<'a> {
+    Term::Function(
+        "PERCENTILE_CONT",
+        vec![Term::Raw(Cow::Owned(float(fraction)))],
+    )
+}
+
+/// Creates a `MODE()` call, the most frequent input value, for use with within_group()
+pub fn mode<'a>() -> Term<'a> {
+    Term::Function("MODE", vec![])
+}
+
+/// Applies a window function over an inline window specification
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let spec = WindowSpec {
+///     partition_by: vec!["dept"],
+///     order_by: Some(OrderBy { columns: vec![OrderedColumn::Desc("salary")] }),
+///     frame: None,
+/// };
+/// let term = over(Term::Atom("rank()"), spec);
+/// assert_eq!(term.sql(), "rank() OVER (PARTITION BY dept ORDER BY salary DESC)");
+/// ```
+pub fn over<'a>(term: Term<'a>, spec: WindowSpec<'a>) -> Term<'a> {
+    Term::Over(Box::new(term), Window::Spec(spec))
+}
+
+/// Applies a window function over a window defined in the query's WINDOW clause
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let term = over_named(Term::Atom("sum(amount)"), "w");
+/// assert_eq!(term.sql(), "sum(amount) OVER w");
+/// ```
+pub fn over_named<'a>(term: Term<'a>, name: &'a str) -> Term<'a> {
+    Term::Over(Box::new(term), Window::Named(name))
+}
+
+/// Wraps a term in parentheses
+pub fn parens<'a>(term: Term<'a>) -> Term<'a> {
+    Term::Parens(Box::new(term))
+}
+
+// Convenience functions for common SQL patterns
+
+/// Creates an IN clause
+/// Example: in_("status", vec!["'active'", "'pending'"]) => "status IN ('active', 'pending')"
+pub fn in_<'a>(column: &'a str, values: Vec<&'a str>) -> Term<'a> {
+    Term::InList {
+        expr: Box::new(Term::Atom(column)),
+        list: values.into_iter().map(Term::Atom).collect(),
+        negated: false,
+    }
+}
+
+/// Creates a BETWEEN clause
+/// Example: between("age", "18", "65") => "age BETWEEN 18 AND 65"
+pub fn between<'a>(column: &'a str, low: &'a str, high: &'a str) -> Term<'a> {
+    Term::Between {
+        expr: Box::new(Term::Atom(column)),
+        low: Box::new(Term::Atom(low)),
+        high: Box::new(Term::Atom(high)),
+        negated: false,
+    }
+}
+
+/// Creates an IS NULL condition
+/// Example: is_null("deleted_at") => "deleted_at IS NULL"
+pub fn is_null<'a>(column: &'a str) -> Term<'a> {
+    Term::IsNull {
+        expr: Box::new(Term::Atom(column)),
+        negated: false,
+    }
+}
+
+/// Creates an IS NOT NULL condition
+/// Example: is_not_null("created_at") => "created_at IS NOT NULL"
+pub fn is_not_null<'a>(column: &'a str) -> Term<'a> {
+    Term::IsNull {
+        expr: Box::new(Term::Atom(column)),
+        negated: true,
+    }
+}
+
+/// Uses a boolean column as a predicate on its own, `WHERE active` rather than
+/// `WHERE active = true`, which is the idiomatic way to write it.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let mut qb = Q();
+/// let query = qb.select(vec!["id"]).from("users").where_(flag("active")).build();
+/// assert_eq!(query.sql(), "SELECT id FROM users WHERE active");
+/// ```
+pub fn flag<'a>(column: &'a str) -> Term<'a> {
+    Term::Atom(column)
+}
+
+/// Negates a boolean column used as a predicate, `NOT active`. Note that, unlike
+/// `active IS NOT TRUE`, this does not match rows where the column is NULL.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(flag_not("archived").sql(), "NOT archived");
+/// ```
+pub fn flag_not<'a>(column: &'a str) -> Term<'a> {
+    not(flag(column))
+}
+
+// Nested query helpers
+
+/// Builds a query inline with the fluent API, for use wherever a subquery is expected
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let t = in_subquery(
+///     "id",
+///     subquery(|q| q.select(vec!["user_id"]).from("orders").where_(gt("total", "100"))),
+/// );
+/// assert_eq!(t.sql(), "id IN (SELECT user_id FROM orders WHERE total > 100)");
+/// ```
+pub fn subquery<'a>(
+    build: impl for<'b> FnOnce(&'b mut QueryBuilder<'a>) -> &'b mut QueryBuilder<'a>,
+) -> Query<'a> {
+    let mut qb = Q();
+    build(&mut qb).build()
+}
+
+/// Wraps a data-modifying statement so that running it returns the number of rows it
+/// changed: `WITH affected AS (stmt) SELECT count(*) FROM affected`.
+///
+/// A statement without a RETURNING clause gets `RETURNING 1`, since the CTE only yields the
+/// rows its statement returns.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let update = U("users").set(vec![("active", "false")]).where_(lt("last_seen", "'2024-01-01'")).build();
+/// assert_eq!(
+///     count_affected(update).sql(),
+///     "WITH affected AS (UPDATE users SET active = false WHERE last_seen < '2024-01-01' RETURNING 1) \
+///      SELECT count(*) FROM affected"
+/// );
+/// ```
+pub fn count_affected<'a>(stmt: impl Into<CteBody<'a>>) -> Query<'a> {
+    let one = || Some(Columns::Selected(vec!["1"]));
+    let mut body = stmt.into();
+    match &mut body {
+        CteBody::Insert(insert) if insert.returning.is_none() => insert.returning = one(),
+        CteBody::Update(update) if update.returning.is_none() => update.returning = one(),
+        CteBody::Delete(delete) if delete.returning.is_none() => delete.returning = one(),
+        _ => {}
+    }
+    Q().with("affected", body)
+        .select(vec!["count(*)"])
+        .from("affected")
+        .build()
+}
+
+/// Wraps a data-modifying statement so that running it returns an aggregate over the rows it
+/// returns in a single row: `WITH affected AS (stmt) SELECT agg FROM affected`.
+///
+/// The aggregate reads the statement's RETURNING columns, so the statement should have one.
+/// Values bound in the statement are carried over, so render() on the result returns them.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let update = U("jobs")
+///     .set(vec![("state", "'queued'")])
+///     .where_(eq("state", "'failed'"))
+///     .returning(Columns::Selected(vec!["id"]))
+///     .build();
+/// assert_eq!(
+///     returning_aggregated(update, array_agg(Term::Atom("id"), AggOptions::default())).sql(),
+///     "WITH affected AS (UPDATE jobs SET state = 'queued' WHERE state = 'failed' RETURNING id) \
+///      SELECT ARRAY_AGG(id) FROM affected"
+/// );
+/// ```
+pub fn returning_aggregated<'a>(stmt: impl Into<CteBody<'a>>, agg: Term<'a>) -> Query<'a> {
+    Q().with("affected", stmt.into())
+        .select_expressions(vec![SelectExpression::Expr(agg, None)])
+        .from("affected")
+        .build()
+}
+
+/// Creates a `TABLE name` statement, PostgreSQL's shorthand for `SELECT * FROM name`. The
+/// returned TableStmt accepts only ORDER BY, LIMIT, OFFSET and locking clauses, and can be used
+/// as a set-operation branch or a CTE body.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(table_stmt("users").sql(), "TABLE users");
+/// assert_eq!(
+///     table_stmt("a").union(table_stmt("b")).sql(),
+///     "(TABLE a) UNION (TABLE b)"
+/// );
+/// ```
+pub fn table_stmt<'a>(name: &'a str) -> TableStmt<'a> {
+    TableStmt::new(name)
+}
+
+/// Uses a query as a scalar value, `(SELECT ...)`, in a select list or comparison. The query
+/// should project exactly one column; try_scalar_subquery() checks this.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let top = scalar_subquery(Q().select(vec!["max(price)"]).from("items").build());
+/// assert_eq!(top.sql(), "(SELECT max(price) FROM items)");
+/// ```
+pub fn scalar_subquery<'a>(query: Query<'a>) -> Term<'a> {
+    Term::Subquery(Box::new(query))
+}
+
+/// Like scalar_subquery(), but fails with [`BuildError::NonScalarSubquery`] unless the query
+/// projects exactly one column or expression. A wildcard is rejected, since it may expand to
+/// several columns.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let ok = try_scalar_subquery(Q().select(vec!["max(price)"]).from("items").build());
+/// assert!(ok.is_ok());
+/// let err = try_scalar_subquery(Q().select(vec!["id", "price"]).from("items").build());
+/// assert_eq!(
+///     err.err(),
+///     Some(BuildError::NonScalarSubquery { columns: vec!["id".to_string(), "price".to_string()] })
+/// );
+/// ```
+pub fn try_scalar_subquery<'a>(query: Query<'a>) -> Result<Term<'a>, BuildError> {
+    let columns: Vec<String> = match &query.select {
+        Some(select) => match &select.cols {
+            Columns::Star => vec!["*".to_string()],
+            Columns::Selected(cols) => cols.iter().map(|c| c.to_string()).collect(),
+            Columns::Expressions(exprs) => exprs.iter().map(|e| e.sql()).collect(),
+        },
+        None => vec!["*".to_string()],
+    };
+    let wildcard = |c: &String| c == "*" || c.ends_with(".*");
+    if columns.len() != 1 || columns.iter().any(wildcard) {
+        return Err(BuildError::NonScalarSubquery { columns });
+    }
+    Ok(scalar_subquery(query))
+}
+
+/// Keeps the first `keep_top_n` rows of each partition, the top-N-per-group pattern.
+///
+/// A window result cannot be filtered in the WHERE clause of the query that computes it, so
+/// this appends `row_number() OVER (...) AS <rank_alias>` to the select list of `query` and
+/// filters on it in a wrapping query. Pick a `rank_alias` that no column of `query` uses; the
+/// outer `SELECT *` includes it.
+///
+/// A query with DISTINCT, GROUP BY or HAVING is first wrapped in `SELECT * FROM (...) AS d`,
+/// so the row number is computed over its result rows rather than making every row distinct
+/// or being evaluated before grouping. `partition_by` and `order_by` then name its output
+/// columns.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let query = rank_filter(
+///     Q().select(vec!["*"]).from("employees").build(),
+///     vec!["dept"],
+///     vec![OrderedColumn::Desc("salary")],
+///     3,
+///     "salary_rank",
+/// );
+/// assert_eq!(
+///     query.sql(),
+///     "SELECT * FROM (SELECT *, row_number() OVER (PARTITION BY dept ORDER BY salary DESC) AS salary_rank \
+///      FROM employees) AS s WHERE salary_rank <= 3"
+/// );
+/// ```
+pub fn rank_filter<'a>(
+    mut query: Query<'a>,
+    partition_by: Vec<&'a str>,
+    order_by: Vec<OrderedColumn<'a>>,
+    keep_top_n: u64,
+    rank_alias: &'a str,
+) -> Query<'a> {
+    let distinct = query.select.as_ref().is_some_and(|s| s.distinct.is_some());
+    if distinct || query.group_by.is_some() || query.having.is_some() {
+        query = Q().select(vec!["*"]).from_subquery(query, "d").build();
+    }
+    let row_number = over(
+        Term::Function("row_number", vec![]),
+        WindowSpec {
+            partition_by,
+            order_by: Some(OrderBy { columns: order_by }),
+            frame: None,
+        },
+    );
+    let select = query.select.get_or_insert(Select::new(Columns::Star, None));
+    let mut exprs = match std::mem::replace(&mut select.cols, Columns::Star) {
+        Columns::Star => vec![SelectExpression::Star],
+        Columns::Selected(cols) => cols.into_iter().map(SelectExpression::Column).collect(),
+        Columns::Expressions(exprs) => exprs,
+    };
+    exprs.push(SelectExpression::Expr(row_number, Some(rank_alias)));
+    select.cols = Columns::Expressions(exprs);
+    Q().select(vec!["*"])
+        .from_subquery(query, "s")
+        .where_(lte(rank_alias, keep_top_n.to_string()))
+        .build()
+}
+
+/// Creates an EXISTS condition with a subquery
+/// Example: exists(subquery) => "EXISTS (SELECT ...)"
+pub fn exists<'a>(subquery: Query<'a>) -> Term<'a> {
+    Term::Exists(Box::new(subquery))
+}
+
+/// Creates a NOT EXISTS condition with a subquery
+/// Example: not_exists(subquery) => "NOT EXISTS (SELECT ...)"
+pub fn not_exists<'a>(subquery: Query<'a>) -> Term<'a> {
+    Term::NotExists(Box::new(subquery))
+}
+
+/// Creates an IN condition with a subquery
+/// Example: in_subquery("user_id", subquery) => "user_id IN (SELECT ...)"
+pub fn in_subquery<'a>(column: &'a str, subquery: Query<'a>) -> Term<'a> {
+    Term::Condition(
+        Box::new(Term::Atom(column)),
+        Op::In,
+        Box::new(Term::Subquery(Box::new(subquery))),
+    )
+}
+
+/// Creates a NOT IN condition with a subquery
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let banned = subquery(|q| q.select(vec!["user_id"]).from("bans"));
+/// let t = not_in_subquery("id", banned);
+/// assert_eq!(t.sql(), "id NOT IN (SELECT user_id FROM bans)");
+/// assert_eq!(not(t).sql(), "id IN (SELECT user_id FROM bans)");
+/// ```
+pub fn not_in_subquery<'a>(column: &'a str, subquery: Query<'a>) -> Term<'a> {
+    Term::Condition(
+        Box::new(Term::Atom(column)),
+        Op::NotIn,
+        Box::new(Term::Subquery(Box::new(subquery))),
+    )
+}
+
+/// Compares a row of columns with the single row returned by a subquery,
+/// `(a, b) = (SELECT x, y ...)`. The subquery must return at most one row.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let latest = subquery(|q| {
+///     q.select(vec!["version", "revision"])
+///         .from("releases")
+///         .order_by(vec![OrderedColumn::Desc("published_at")])
+///         .limit(1)
+/// });
+/// let t = row_eq_subquery(vec!["version", "revision"], latest);
+/// assert_eq!(
+///     t.sql(),
+///     "(version, revision) = (SELECT version, revision FROM releases ORDER BY published_at DESC LIMIT 1)"
+/// );
+/// ```
+pub fn row_eq_subquery<'a>(columns: Vec<&'a str>, subquery: Query<'a>) -> Term<'a> {
+    Term::Condition(
+        Box::new(Term::Row(columns.into_iter().map(Term::Atom).collect())),
+        Op::Equals,
+        Box::new(Term::Subquery(Box::new(subquery))),
+    )
+}
+
+/// Creates a comparison with ANY (subquery). The left operand may be a column name or any
+/// expression; a binary expression is parenthesized.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let prices = subquery(|q| q.select(vec!["price"]).from("competitor_prices"));
+/// let t = any(
+///     Term::Condition(Box::new(Term::Atom("base")), Op::Plus, Box::new(Term::Atom("tax"))),
+///     Op::LessThan,
+///     prices,
+/// );
+/// assert_eq!(t.sql(), "(base + tax) < ANY (SELECT price FROM competitor_prices)");
+/// ```
+pub fn any<'a>(left: impl Into<Term<'a>>, op: Op<'a>, subquery: Query<'a>) -> Term<'a> {
+    Term::AnyAll {
+        left: Box::new(left.into()),
+        op,
+        all: false,
+        subquery: Box::new(subquery),
+    }
+}
+
+/// Creates a comparison with ALL (subquery)
+/// Example: all("price", Op::LessThan, subquery) => "price < ALL (SELECT ...)"
+pub fn all<'a>(left: impl Into<Term<'a>>, op: Op<'a>, subquery: Query<'a>) -> Term<'a> {
+    Term::AnyAll {
+        left: Box::new(left.into()),
+        op,
+        all: true,
+        subquery: Box::new(subquery),
+    }
+}
+
+/// Returns the `DEFAULT` keyword, for resetting a column to its default value in
+/// UPDATE ... SET or for taking the default in INSERT ... VALUES.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let update = U("orders").set(vec![("status", default_value())]).where_(eq("id", "7")).build();
+/// assert_eq!(update.sql(), "UPDATE orders SET status = DEFAULT WHERE id = 7");
+/// ```
+pub fn default_value() -> &'static str {
+    "DEFAULT"
+}
+
+// Identifier helpers
+
+/// PostgreSQL's reserved keywords, which cannot be used as bare identifiers.
+const RESERVED_KEYWORDS: [&str; 77] = [
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "column",
+    "constraint",
+    "create",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "from",
+    "grant",
+    "group",
+    "having",
+    "in",
+    "initially",
+    "intersect",
+    "into",
+    "lateral",
+    "leading",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "not",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "select",
+    "session_user",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "when",
+    "where",
+    "window",
+];
+
+/// Quotes an identifier if PostgreSQL would not read it back unchanged: when it contains
+/// uppercase or special characters, starts with a digit, or is a reserved keyword. Embedded
+/// double quotes are doubled. Plain lowercase names are returned as is.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(quote_ident("events"), "events");
+/// assert_eq!(quote_ident("Events"), "\"Events\"");
+/// assert_eq!(quote_ident("order"), "\"order\"");
+/// assert_eq!(quote_ident("my \"table\""), "\"my \"\"table\"\"\"");
+/// ```
+pub fn quote_ident(name: &str) -> Cow<'_, str> {
+    let mut chars = name.chars();
+    let plain = match chars.next() {
+        Some(c) if c.is_ascii_lowercase() || c == '_' => {
+            chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$')
+        }
+        _ => false,
+    };
+    if plain && !RESERVED_KEYWORDS.contains(&name) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("\"{}\"", name.replace('"', "\"\"")))
+    }
+}
+
+/// Creates a schema-qualified table source, quoting each part independently as needed
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(schema_table("analytics", "events").sql(), "analytics.events");
+/// assert_eq!(schema_table("Analytics", "Events").sql(), "\"Analytics\".\"Events\"");
+/// ```
+pub fn schema_table<'a>(schema: &'a str, table: &'a str) -> FromSource<'a> {
+    FromSource::Qualified(schema, table)
+}
+
+/// References a column of an outer query from inside a subquery, `outer_table.column`
+///
+/// The reference is a plain column reference: it never consumes a placeholder, and
+/// qualify_columns() on the inner query leaves it pointing at the outer table.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// // Per-row order count as a correlated subquery in the SELECT list
+/// let mut inner = Q();
+/// let order_count = inner
+///     .select(vec!["COUNT(*)"])
+///     .from("orders")
+///     .where_(Term::Condition(
+///         Box::new(Term::Atom("orders.user_id")),
+///         Op::Equals,
+///         Box::new(correlated("users", "id")),
+///     ))
+///     .build();
+///
+/// let mut outer = Q();
+/// let query = outer
+///     .select_expressions(vec![
+///         SelectExpression::Column("id"),
+///         SelectExpression::Subquery(Box::new(order_count), Some("order_count")),
+///     ])
+///     .from("users")
+///     .build();
+/// assert_eq!(
+///     query.sql(),
+///     "SELECT id, (SELECT COUNT(*) FROM orders WHERE orders.user_id = users.id) AS order_count FROM users"
+/// );
+/// ```
+pub fn correlated<'a>(outer_table: &str, column: &str) -> Term<'a> {
+    Term::Raw(Cow::Owned(format!(
+        "{}.{}",
+        quote_ident(outer_table),
+        quote_ident(column)
+    )))
+}
+
+// Literal helpers
+
+/// Formats an integer as a SQL literal, for use with values() or set()
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(num(-42), "-42");
+/// ```
+pub fn num(n: i64) -> String {
+    n.to_string()
+}
+
+/// Formats a float as a SQL literal. The output never depends on the locale and always has a
+/// decimal point or exponent; NaN and the infinities become quoted `float8` literals.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(float(19.99), "19.99");
+/// assert_eq!(float(2.0), "2.0");
+/// assert_eq!(float(f64::INFINITY), "'Infinity'::float8");
+/// ```
+pub fn float(f: f64) -> String {
+    if f.is_nan() {
+        "'NaN'::float8".to_string()
+    } else if f.is_infinite() {
+        let sign = if f < 0.0 { "-" } else { "" };
+        format!("'{}Infinity'::float8", sign)
+    } else {
+        format!("{:?}", f)
+    }
+}
+
+/// Formats a boolean as a SQL literal
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(boolean(false), "FALSE");
+/// ```
+pub fn boolean(b: bool) -> String {
+    if b { "TRUE" } else { "FALSE" }.to_string()
+}
+
+/// Formats a string as a quoted SQL literal, doubling any embedded single quotes.
+/// This assumes `standard_conforming_strings` is on, the PostgreSQL default.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let name = text("O'Brien");
+/// let insert = I("users").columns(vec!["name"]).values(vec![&name]).build();
+/// assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES ('O''Brien')");
+/// ```
+pub fn text(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Formats a DATE typed literal, quoting the value as text() does
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(date_lit("2024-01-01"), "DATE '2024-01-01'");
+/// let t = gte("created_at", date_lit("2024-01-01"));
+/// assert_eq!(t.sql(), "created_at >= DATE '2024-01-01'");
+/// ```
+pub fn date_lit(s: &str) -> String {
+    format!("DATE {}", text(s))
+}
+
+/// Formats a TIME typed literal
+pub fn time_lit(s: &str) -> String {
+    format!("TIME {}", text(s))
+}
+
+/// Formats a TIMESTAMP (without time zone) typed literal
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(timestamp_lit("2024-01-01 12:00:00"), "TIMESTAMP '2024-01-01 12:00:00'");
+/// ```
+pub fn timestamp_lit(s: &str) -> String {
+    format!("TIMESTAMP {}", text(s))
+}
+
+/// Formats a TIMESTAMPTZ typed literal
+pub fn timestamptz_lit(s: &str) -> String {
+    format!("TIMESTAMPTZ {}", text(s))
+}
+
+// PostgreSQL parameter helpers
+
+/// Returns a PostgreSQL parameter placeholder
+/// Example: p(1) => "$1", p(2) => "$2"
+pub fn p(n: usize) -> String {
+    format!("${}", n)
+}
+
+/// PostgreSQL parameter counter for auto-sequencing
+/// Automatically generates $1, $2, $3, etc. as you call seq()
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let mut pg = PgParams::new();
+/// let p1 = pg.seq();  // "$1"
+/// let p2 = pg.seq();  // "$2"
+/// let mut qb = Q();
+/// let query = qb
+///     .select(vec!["*"])
+///     .from("users")
+///     .where_(and(
+///         eq("id", &p1),
+///         eq("status", &p2)
+///     ))
+///     .build();
+/// assert_eq!(query.sql(), "SELECT * FROM users WHERE id = $1 AND status = $2");
+/// ```
+///
+/// In the Question style, seq() and bind() hand out bare `?` placeholders, and values() returns
+/// the values in the order they were handed out. Positional placeholders are bound in order of
+/// appearance, so they must be written into the SQL in that same order. The builders'
+/// param_style() does not have this restriction: render() reorders the values to match.
+pub struct PgParams {
+    slots: Vec<Option<Value>>,
+    style: ParamStyle,
+}
+
+/// The ParamStyle enum selects how placeholders are written.
+///
+/// PostgreSQL uses numbered `$n` placeholders; some adapters and drivers only understand
+/// positional `?` placeholders, which are bound strictly in order of appearance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ParamStyle {
+    /// Numbered placeholders: $1, $2, $3, ...
+    #[default]
+    Dollar,
+    /// Positional placeholders: ?, ?, ?, ...
+    Question,
+}
+
+impl PgParams {
+    /// Creates a new parameter counter starting at 0
+    pub fn new() -> Self {
+        PgParams {
+            slots: Vec::new(),
+            style: ParamStyle::Dollar,
+        }
+    }
+
+    /// Creates a new parameter counter that writes placeholders in the given style
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut pg = PgParams::with_style(ParamStyle::Question);
+    /// assert_eq!(pg.seq(), "?");
+    /// assert_eq!(pg.seq(), "?");
+    /// ```
+    pub fn with_style(style: ParamStyle) -> Self {
+        PgParams {
+            style,
+            ..PgParams::new()
+        }
+    }
+
+    /// Changes the placeholder style used for subsequent placeholders
+    pub fn set_style(&mut self, style: ParamStyle) {
+        self.style = style;
+    }
+
+    /// Returns the next parameter placeholder ($1, $2, $3, etc., or ? in the Question style)
+    pub fn seq(&mut self) -> String {
+        self.next(None)
+    }
+
+    /// Returns the next parameter placeholder and records the value bound to it.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut pg = PgParams::new();
+    /// assert_eq!(pg.bind(42), "$1");
+    /// assert_eq!(pg.bind("alice"), "$2");
+    /// assert_eq!(pg.values(), Ok(vec![Value::Int(42), Value::Text("alice".to_string())]));
+    /// ```
+    pub fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.next(Some(value.into()))
+    }
+
+    /// Records a slot for the next placeholder and returns it.
+    fn next(&mut self, value: Option<Value>) -> String {
+        let numbered = self.slot(value);
+        match self.style {
+            ParamStyle::Dollar => numbered,
+            ParamStyle::Question => "?".to_string(),
+        }
+    }
+
+    /// Records a slot for the next placeholder and returns it numbered, whatever the style.
+    /// Builders use this and leave the conversion to `?` to the built statement, which knows
+    /// where each placeholder ends up.
+    pub(crate) fn slot(&mut self, value: Option<Value>) -> String {
+        self.slots.push(value);
+        format!("${}", self.slots.len())
+    }
+
+    /// Returns the values recorded by bind(), in placeholder order.
+    ///
+    /// Values only line up with placeholders when every placeholder was bound, so mixing seq()
+    /// and bind() is an error. Without any bind() the result is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut pg = PgParams::new();
+    /// pg.seq();
+    /// pg.bind(42);
+    /// assert_eq!(pg.values(), Err(BuildError::UnboundParams { placeholders: vec![1] }));
+    /// ```
+    pub fn values(&self) -> Result<Vec<Value>, BuildError> {
+        self.params().values()
+    }
+
+    /// Returns how many placeholders have been handed out so far, bound or not.
+    pub fn count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the slots recorded so far, for the statement being built.
+    pub(crate) fn params(&self) -> Params {
+        Params {
+            slots: self.slots.clone(),
+            offset: 0,
+            style: self.style,
+        }
+    }
+}
+
+/// Shifts every numbered `$n` placeholder in `sql` up by `offset`, so a statement rendered with
+/// its own counter can be embedded after another statement's parameters.
+///
+/// Only a `$n` standing on its own is renumbered: string literals (including `E'...'`), quoted
+/// identifiers, dollar-quoted bodies, comments and identifiers such as `a$1` are left untouched,
+/// as are `?` placeholders, which are positional.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(
+///     renumber_params("SELECT '$1', a$1, $$ $1 $$ FROM t WHERE a = $1 AND b = $2", 3),
+///     "SELECT '$1', a$1, $$ $1 $$ FROM t WHERE a = $4 AND b = $5"
+/// );
+/// ```
+pub fn renumber_params(sql: &str, offset: usize) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut last = 0;
+    for (span, n) in placeholder_spans(sql) {
+        result.push_str(&sql[last..span.start]);
+        result.push_str(&format!("${}", n + offset));
+        last = span.end;
+    }
+    result.push_str(&sql[last..]);
+    result
+}
+
+impl Default for PgParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The Params struct holds the parameters of a built statement: one slot per placeholder
+/// handed out by its builder, with the value bound to it, if any.
+///
+/// A statement nested in another one, such as a subquery or a CTE body, hands its slots to the
+/// outer statement when that one is built. Its placeholders are then renumbered to follow the
+/// outer statement's own, so render() on the outer statement returns every value in
+/// placeholder order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Params {
+    slots: Vec<Option<Value>>,
+    offset: usize,
+    style: ParamStyle,
+}
+
+impl Params {
+    /// Returns the number of placeholders, bound or not.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns true when the statement has no placeholders of its own.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns the bound values in placeholder order.
+    ///
+    /// When some placeholders were bound and others were not, the values cannot be lined up
+    /// and the unbound placeholders are reported. When nothing was bound the result is empty,
+    /// leaving the caller to supply the values.
+    pub fn values(&self) -> Result<Vec<Value>, BuildError> {
+        if self.slots.iter().all(Option::is_none) {
+            return Ok(vec![]);
+        }
+        let unbound: Vec<usize> = (1..)
+            .zip(&self.slots)
+            .filter(|(_, slot)| slot.is_none())
+            .map(|(n, _)| n + self.offset)
+            .collect();
+        if unbound.is_empty() {
+            Ok(self.slots.iter().flatten().cloned().collect())
+        } else {
+            Err(BuildError::UnboundParams {
+                placeholders: unbound,
+            })
+        }
+    }
+
+    /// Returns the style the placeholders are rendered in.
+    pub fn style(&self) -> ParamStyle {
+        self.style
+    }
+
+    /// Applies the renumbering of a nested statement and the placeholder style to the SQL
+    /// rendered by the statement these params belong to.
+    pub(crate) fn finish(&self, sql: String) -> String {
+        let sql = match self.offset {
+            0 => sql,
+            offset => renumber_params(&sql, offset),
+        };
+        match self.style {
+            ParamStyle::Dollar => sql,
+            ParamStyle::Question => positional(&sql),
+        }
+    }
+
+    /// Pairs the SQL rendered with numbered placeholders with the values to send along, and
+    /// converts it to the placeholder style.
+    ///
+    /// Positional `?` placeholders are bound in order of appearance, so in the Question style
+    /// the values are reordered to match, and repeated where a placeholder appears twice.
+    /// Unbound placeholders are only accepted when nothing was bound and they already appear
+    /// in the order they were handed out, so the caller can supply the values in that order.
+    pub(crate) fn render(&self, numbered: String) -> Result<(String, Vec<Value>), BuildError> {
+        if self.style == ParamStyle::Dollar {
+            let values = self.values()?;
+            return Ok((numbered, values));
+        }
+        let order = placeholders(&numbered);
+        let in_order = order.iter().copied().eq(1..=order.len());
+        let values = if in_order && self.slots.iter().all(Option::is_none) {
+            vec![]
+        } else {
+            let mut unbound = BTreeSet::new();
+            let mut values = Vec::with_capacity(order.len());
+            for n in order {
+                match n.checked_sub(1).and_then(|i| self.slots.get(i)) {
+                    Some(Some(value)) => values.push(value.clone()),
+                    _ => {
+                        unbound.insert(n);
+                    }
+                }
+            }
+            if !unbound.is_empty() {
+                return Err(BuildError::UnboundParams {
+                    placeholders: unbound.into_iter().collect(),
+                });
+            }
+            values
+        };
+        Ok((positional(&numbered), values))
+    }
+
+    /// Takes over the slots of a statement nested in the one these params belong to, and
+    /// renumbers its placeholders to follow the slots already here. A nested statement
+    /// without placeholders of its own is left alone, so placeholders it borrowed from the
+    /// outer builder keep their numbers.
+    pub(crate) fn adopt(&mut self, nested: &mut Params) {
+        if nested.slots.is_empty() {
+            return;
+        }
+        nested.offset = self.slots.len();
+        nested.style = ParamStyle::Dollar;
+        self.slots.extend(nested.slots.iter().cloned());
+    }
+
+    /// Adopts the params of every subquery in `term`.
+    pub(crate) fn adopt_term(&mut self, term: &mut Term) {
+        // Walking mutably unshares Term::Shared subtrees, so only do it when there is work
+        let mut nested = false;
+        walk_terms(term, |t| {
+            nested |= t.subquery().is_some_and(|q| !q.params.is_empty());
+            !nested
+        });
+        if nested {
+            walk_terms_mut(term, |t| {
+                if let Some(query) = t.subquery_mut() {
+                    self.adopt(&mut query.params);
+                }
+                true
+            });
+        }
+    }
+
+    /// Adopts the params of a FROM or JOIN source.
+    pub(crate) fn adopt_source(&mut self, source: &mut FromSource) {
+        match source {
+            FromSource::Subquery(query, _) | FromSource::AliasedSubquery { query, .. } => {
+                self.adopt(&mut query.params)
+            }
+            FromSource::FunctionCall { func, .. } => self.adopt_term(func),
+            FromSource::Table(_)
+            | FromSource::Sampled(..)
+            | FromSource::Only(_)
+            | FromSource::WithDescendants(_)
+            | FromSource::Qualified(..) => {}
+        }
+    }
+
+    /// Adopts the params of the subqueries in a select list or RETURNING clause.
+    pub(crate) fn adopt_columns(&mut self, cols: &mut Columns) {
+        if let Columns::Expressions(exprs) = cols {
+            for expr in exprs {
+                match expr {
+                    SelectExpression::Subquery(query, _) => self.adopt(&mut query.params),
+                    SelectExpression::Expr(term, _) => self.adopt_term(term),
+                    SelectExpression::Column(_)
+                    | SelectExpression::Star
+                    | SelectExpression::TableStar(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Adopts the params of a CTE body.
+    pub(crate) fn adopt_cte(&mut self, body: &mut CteBody) {
+        match body {
+            CteBody::Select(query) => self.adopt(&mut query.params),
+            CteBody::Insert(insert) => self.adopt(&mut insert.params),
+            CteBody::Update(update) => self.adopt(&mut update.params),
+            CteBody::Delete(delete) => self.adopt(&mut delete.params),
+            CteBody::Table(_) => {}
+        }
+    }
+}
+
+/// The Value enum is a parameter value recorded by bind(), to be handed to the database driver
+/// alongside the rendered SQL.
+///
+/// Floats are compared and hashed by their bit pattern, so `NaN` equals itself and `0.0` does
+/// not equal `-0.0`. This keeps Value usable as part of a map key.
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// SQL NULL
+    Null,
+    /// A boolean
+    Bool(bool),
+    /// A 64-bit integer
+    Int(i64),
+    /// A 64-bit float
+    Float(f64),
+    /// A text value
+    Text(String),
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::Text(a), Value::Text(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Int(i) => i.hash(state),
+            Value::Float(f) => f.to_bits().hash(state),
+            Value::Text(t) => t.hash(state),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::Int(v.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::Float(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Text(v.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Text(v)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+/// The BuildError enum describes problems detected in a statement before it is sent to the
+/// database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The `$n` placeholders do not form a contiguous `1..=max` sequence.
+    /// `missing` lists the indices that never appear, `duplicates` those that appear more than once.
+    InvalidParams {
+        /// Placeholder indices absent from the rendered SQL
+        missing: Vec<usize>,
+        /// Placeholder indices used more than once
+        duplicates: Vec<usize>,
+    },
+    /// The leading ORDER BY columns do not match the DISTINCT ON expressions, which
+    /// Postgres rejects.
+    DistinctOnOrderMismatch {
+        /// The DISTINCT ON expressions
+        distinct_on: Vec<String>,
+        /// The ORDER BY columns, in order
+        order_by: Vec<String>,
+    },
+    /// A SELECT DISTINCT orders by expressions that are not in its select list, which
+    /// Postgres rejects.
+    DistinctOrderByNotSelected {
+        /// The ORDER BY columns missing from the select list
+        columns: Vec<String>,
+    },
+    /// A DELETE or UPDATE has no WHERE clause and was not explicitly opted in to affecting
+    /// every row with all().
+    MissingWhere {
+        /// The target table
+        table: String,
+    },
+    /// A CTE reads from a CTE that is only defined after it in the WITH clause.
+    CteOrder {
+        /// The CTE containing the forward reference
+        cte: String,
+        /// The later CTE it references
+        references: String,
+    },
+    /// A HAVING condition references a column that is neither aggregated nor in GROUP BY.
+    InvalidHaving {
+        /// The offending column reference
+        column: String,
+    },
+    /// A row of an INSERT has a different number of values than there are columns.
+    RowArityMismatch {
+        /// The zero-based index of the offending row
+        row_index: usize,
+        /// The number of columns
+        expected: usize,
+        /// The number of values in the row
+        got: usize,
+    },
+    /// A subquery used as a scalar value projects something other than exactly one column.
+    NonScalarSubquery {
+        /// The rendered select list items; `*` for a wildcard or a TABLE query
+        columns: Vec<String>,
+    },
+    /// keyset_paginate() was given a different number of last-seen values than keys.
+    KeysetArity {
+        /// The number of keys
+        keys: usize,
+        /// The number of last-seen values
+        values: usize,
+    },
+    /// keyset_paginate() was given no keys to order and page by.
+    EmptyKeyset,
+    /// Some placeholders have no bound value, so the values cannot be lined up with them.
+    /// This happens when param() and bind() were mixed, or when unbound positional `?`
+    /// placeholders do not appear in the order they were handed out.
+    UnboundParams {
+        /// The placeholders without a value
+        placeholders: Vec<usize>,
+    },
+    /// FETCH FIRST ... WITH TIES has no ORDER BY to decide ties by, which Postgres rejects.
+    TiesWithoutOrderBy,
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::InvalidParams {
+                missing,
+                duplicates,
+            } => write!(
+                f,
+                "invalid parameter placeholders: missing {:?}, duplicated {:?}",
+                missing, duplicates
+            ),
+            BuildError::DistinctOnOrderMismatch {
+                distinct_on,
+                order_by,
+            } => write!(
+                f,
+                "ORDER BY must start with the DISTINCT ON expressions {:?}, found {:?}",
+                distinct_on, order_by
+            ),
+            BuildError::DistinctOrderByNotSelected { columns } => write!(
+                f,
+                "SELECT DISTINCT orders by {:?}, which must appear in the select list",
+                columns
+            ),
+            BuildError::MissingWhere { table } => write!(
+                f,
+                "statement on {} has no WHERE clause; call all() to affect every row",
+                table
+            ),
+            BuildError::CteOrder { cte, references } => write!(
+                f,
+                "CTE {} references {}, which is defined after it",
+                cte, references
+            ),
+            BuildError::InvalidHaving { column } => write!(
+                f,
+                "HAVING references {}, which is neither aggregated nor grouped",
+                column
+            ),
+            BuildError::RowArityMismatch {
+                row_index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "row {} has {} values but {} columns were given",
+                row_index, got, expected
+            ),
+            BuildError::NonScalarSubquery { columns } => write!(
+                f,
+                "scalar subquery must select exactly one column, found {:?}",
+                columns
+            ),
+            BuildError::KeysetArity { keys, values } => write!(
+                f,
+                "keyset pagination has {} keys but {} last-seen values",
+                keys, values
+            ),
+            BuildError::EmptyKeyset => write!(f, "keyset pagination needs at least one key"),
+            BuildError::UnboundParams { placeholders } => write!(
+                f,
+                "placeholders {:?} have no bound value to line up with",
+                placeholders
+            ),
+            BuildError::TiesWithoutOrderBy => {
+                write!(f, "FETCH FIRST ... WITH TIES requires an ORDER BY")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// PostgreSQL accepts at most this many parameters in one statement.
+const MAX_PARAMS: usize = 65535;
+
+/// Returns true for a byte that can continue an identifier. Bytes of multi-byte UTF-8
+/// characters count, since PostgreSQL allows non-ASCII letters in identifiers.
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// Finds the `$n` placeholders in the given SQL, returning the byte range and index of each in
+/// order of appearance.
+///
+/// String literals (including `E'...'` with backslash escapes), quoted identifiers,
+/// dollar-quoted bodies and comments are skipped, and a `$` that continues an identifier, as
+/// in `a$1`, is not a placeholder.
+fn placeholder_spans(sql: &str) -> Vec<(Range<usize>, usize)> {
+    let bytes = sql.as_bytes();
+    let len = bytes.len();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < len {
+        let prev_ident = i > 0 && is_ident_byte(bytes[i - 1]);
+        match bytes[i] {
+            b'\'' => {
+                let escapes = i > 0
+                    && matches!(bytes[i - 1], b'E' | b'e')
+                    && !(i > 1 && is_ident_byte(bytes[i - 2]));
+                i += 1;
+                while i < len {
+                    match bytes[i] {
+                        b'\\' if escapes => i += 2,
+                        b'\'' if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+                        b'\'' => break,
+                        _ => i += 1,
+                    }
+                }
+                i += 1;
+            }
+            b'"' => {
+                i += 1;
+                while i < len && bytes[i] != b'"' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < len && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let mut depth = 0;
+                while i < len {
+                    if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'*') {
+                        depth += 1;
+                        i += 2;
+                    } else if bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            b'$' if !prev_ident => {
+                let start = i + 1;
+                let mut end = start;
+                while end < len && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+                if end > start {
+                    if let Ok(n) = sql[start..end].parse::<usize>() {
+                        found.push((i..end, n));
+                    }
+                    i = end;
+                    continue;
+                }
+                // A dollar-quoted body: $$...$$ or $tag$...$tag$
+                while end < len && is_ident_byte(bytes[end]) && bytes[end] != b'$' {
+                    end += 1;
+                }
+                if end < len && bytes[end] == b'$' {
+                    let tag = &sql[i..=end];
+                    i = match sql[end + 1..].find(tag) {
+                        Some(close) => end + 1 + close + tag.len(),
+                        None => len,
+                    };
+                } else {
+                    i = end;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    found
+}
+
+/// Rewrites every `$n` placeholder in the given SQL as a positional `?`.
+fn positional(sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut last = 0;
+    for (span, _) in placeholder_spans(sql) {
+        result.push_str(&sql[last..span.start]);
+        result.push('?');
+        last = span.end;
+    }
+    result.push_str(&sql[last..]);
+    result
+}
+
+/// Returns the index of every `$n` placeholder in the given SQL, in order of appearance.
+fn placeholders(sql: &str) -> Vec<usize> {
+    placeholder_spans(sql).into_iter().map(|(_, n)| n).collect()
+}
+
+/// Checks that the placeholders in the given SQL are exactly `$1..=$max`, each used once.
+fn validate_placeholders(sql: &str) -> Result<(), BuildError> {
+    let mut seen = BTreeSet::new();
+    let mut duplicates = BTreeSet::new();
+    for n in placeholders(sql) {
+        if !seen.insert(n) {
+            duplicates.insert(n);
+        }
+    }
+    let max = seen.last().copied().unwrap_or(0);
+    // Indices past the protocol limit can never be valid, so the gaps are only listed up to it
+    let missing: Vec<usize> = (1..=max.min(MAX_PARAMS))
+        .filter(|n| !seen.contains(n))
+        .collect();
+    if missing.is_empty() && duplicates.is_empty() && max <= MAX_PARAMS {
+        Ok(())
+    } else {
+        Err(BuildError::InvalidParams {
+            missing,
+            duplicates: duplicates.into_iter().collect(),
+        })
+    }
+}
+
+/// The Having struct is used to specify the having clause in a query.
+/// It is used in the Query struct.
+///
+/// It is constructed with a Term, similar to a Where clause.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Having<'a> {
+    /// The condition for the HAVING clause
+    pub term: Term<'a>,
+}
+
+impl<'a> Having<'a> {
+    /// Creates a new Having clause with the given term
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let having = Having::new(gt("count", "5"));
+    /// assert_eq!(having.sql(), "count > 5");
+    /// ```
+    pub fn new(t: Term<'a>) -> Having<'a> {
+        Having { term: t }
+    }
+}
+
+impl<'a> Sql for Having<'a> {
+    fn sql(&self) -> String {
+        self.term.sql().to_string()
+    }
+}
+
+/// The OrderedColumn enum is used to specify the order by clause in a query.
+/// It is used in the OrderBy struct.
+/// It is used to specify the columns, and optionally, whether they are ascending or descending.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// assert_eq!(OrderedColumn::from("id").sql(), "id");
+/// assert_eq!(OrderedColumn::Desc("created_at").nulls_last().sql(), "created_at DESC NULLS LAST");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum OrderedColumn<'a> {
+    /// Ascending order
+    Asc(&'a str),
+    /// Descending order
+    Desc(&'a str),
+    /// No explicit direction, so the database default (ascending) applies
+    Column(&'a str),
+    /// Ascending order with explicit NULL placement
+    AscNulls(&'a str, NullsOrder),
+    /// Descending order with explicit NULL placement
+    DescNulls(&'a str, NullsOrder),
+}
+
+/// Where NULLs sort relative to other values: `NULLS FIRST` or `NULLS LAST`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NullsOrder {
+    /// NULLS FIRST, the default for descending order
+    First,
+    /// NULLS LAST, the default for ascending order
+    Last,
+}
+
+impl Sql for NullsOrder {
+    fn sql(&self) -> String {
+        match self {
+            NullsOrder::First => "NULLS FIRST",
+            NullsOrder::Last => "NULLS LAST",
+        }
+        .to_string()
+    }
+}
+
+impl<'a> OrderedColumn<'a> {
+    /// Returns the column or expression being sorted
+    pub fn column(&self) -> &'a str {
+        match self {
+            OrderedColumn::Asc(col)
+            | OrderedColumn::Desc(col)
+            | OrderedColumn::Column(col)
+            | OrderedColumn::AscNulls(col, _)
+            | OrderedColumn::DescNulls(col, _) => col,
+        }
+    }
+
+    /// Returns true when the column sorts in descending order
+    pub fn is_desc(&self) -> bool {
+        matches!(self, OrderedColumn::Desc(_) | OrderedColumn::DescNulls(..))
+    }
+
+    /// Sorts NULLs before other values, keeping the direction; a bare column becomes ascending
+    pub fn nulls_first(self) -> OrderedColumn<'a> {
+        self.with_nulls(NullsOrder::First)
+    }
+
+    /// Sorts NULLs after other values, keeping the direction; a bare column becomes ascending
+    pub fn nulls_last(self) -> OrderedColumn<'a> {
+        self.with_nulls(NullsOrder::Last)
+    }
+
+    fn with_nulls(self, nulls: NullsOrder) -> OrderedColumn<'a> {
+        if self.is_desc() {
+            OrderedColumn::DescNulls(self.column(), nulls)
+        } else {
+            OrderedColumn::AscNulls(self.column(), nulls)
+        }
+    }
+}
+
+impl<'a> Sql for OrderedColumn<'a> {
+    fn sql(&self) -> String {
+        match self {
+            OrderedColumn::Asc(s) => format!("{} ASC", s),
+            OrderedColumn::Desc(s) => format!("{} DESC", s),
+            OrderedColumn::Column(s) => s.to_string(),
+            OrderedColumn::AscNulls(s, nulls) => format!("{} ASC {}", s, nulls.sql()),
+            OrderedColumn::DescNulls(s, nulls) => format!("{} DESC {}", s, nulls.sql()),
+        }
+    }
+}
+
+/// A bare column name sorts in the database's default direction
+impl<'a> From<&'a str> for OrderedColumn<'a> {
+    fn from(column: &'a str) -> Self {
+        OrderedColumn::Column(column)
+    }
+}
+
+/// The OrderBy struct is used to specify the order by clause in a query.
+/// It is used in the Query struct.
+/// It is used to specify the columns, and optionally, whether they are ascending or descending.
+/// Each column can be ascending or descending
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct OrderBy<'a> {
+    /// List of columns with their sort order
+    pub columns: Vec<OrderedColumn<'a>>,
+}
+
+impl<'a> OrderBy<'a> {
+    /// Renders just the sort keys, `col ASC, col2 DESC`, for contexts that write the
+    /// `ORDER BY` keyword themselves
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let order_by = OrderBy {
+    ///     columns: vec![OrderedColumn::Asc("created_at"), OrderedColumn::Desc("id")],
+    /// };
+    /// assert_eq!(order_by.columns_sql(), "created_at ASC, id DESC");
+    /// assert_eq!(order_by.sql(), "ORDER BY created_at ASC, id DESC");
+    /// ```
+    pub fn columns_sql(&self) -> String {
+        self.columns
+            .iter()
+            .map(|c| c.sql())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl<'a> Sql for OrderBy<'a> {
+    fn sql(&self) -> String {
+        format!("ORDER BY {}", self.columns_sql())
+    }
+}
+
+/// The WindowSpec struct describes the window a window function operates over.
+/// It renders without the surrounding parentheses, which are added by the enclosing context.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let spec = WindowSpec {
+///     partition_by: vec!["user_id"],
+///     order_by: Some(OrderBy { columns: vec![OrderedColumn::Asc("created_at")] }),
+///     frame: Some(Frame::between(
+///         FrameMode::Rows,
+///         FrameBound::UnboundedPreceding,
+///         FrameBound::CurrentRow,
+///     )),
+/// };
+/// assert_eq!(
+///     spec.sql(),
+///     "PARTITION BY user_id ORDER BY created_at ASC ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct WindowSpec<'a> {
+    /// The columns to PARTITION BY, if any
+    pub partition_by: Vec<&'a str>,
+    /// The ORDER BY within each partition, if any
+    pub order_by: Option<OrderBy<'a>>,
+    /// The frame clause, e.g. `ROWS BETWEEN 1 PRECEDING AND CURRENT ROW`
+    pub frame: Option<Frame<'a>>,
+}
+
+impl<'a> Sql for WindowSpec<'a> {
+    fn sql(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.partition_by.is_empty() {
+            parts.push(format!("PARTITION BY {}", self.partition_by.join(", ")));
+        }
+        if let Some(order_by) = &self.order_by {
+            parts.push(format!("ORDER BY {}", order_by.columns_sql()));
+        }
+        if let Some(frame) = &self.frame {
+            parts.push(frame.sql());
+        }
+        parts.join(" ")
+    }
+}
+
+/// The unit a window frame is measured in
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameMode {
+    /// Physical rows
+    Rows,
+    /// Rows with equal ORDER BY values (peers) are treated as one
+    Range,
+    /// Groups of peer rows
+    Groups,
+}
+
+impl Sql for FrameMode {
+    fn sql(&self) -> String {
+        match self {
+            FrameMode::Rows => "ROWS",
+            FrameMode::Range => "RANGE",
+            FrameMode::Groups => "GROUPS",
+        }
+        .to_string()
+    }
+}
+
+/// One end of a window frame
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum FrameBound<'a> {
+    /// UNBOUNDED PRECEDING: the first row of the partition
+    UnboundedPreceding,
+    /// `n PRECEDING`
+    Preceding(u64),
+    /// `offset PRECEDING` with an expression offset, e.g. `INTERVAL '7 days' PRECEDING` in a
+    /// RANGE frame
+    PrecedingBy(Box<Term<'a>>),
+    /// CURRENT ROW
+    CurrentRow,
+    /// `n FOLLOWING`
+    Following(u64),
+    /// `offset FOLLOWING` with an expression offset
+    FollowingBy(Box<Term<'a>>),
+    /// UNBOUNDED FOLLOWING: the last row of the partition
+    UnboundedFollowing,
+}
+
+impl<'a> FrameBound<'a> {
+    /// Creates an `offset PRECEDING` bound with an expression offset
+    pub fn preceding_by(offset: impl Into<Term<'a>>) -> FrameBound<'a> {
+        FrameBound::PrecedingBy(Box::new(offset.into()))
+    }
+
+    /// Creates an `offset FOLLOWING` bound with an expression offset
+    pub fn following_by(offset: impl Into<Term<'a>>) -> FrameBound<'a> {
+        FrameBound::FollowingBy(Box::new(offset.into()))
+    }
+}
+
+impl<'a> Sql for FrameBound<'a> {
+    fn sql(&self) -> String {
+        match self {
+            FrameBound::UnboundedPreceding => "UNBOUNDED PRECEDING".to_string(),
+            FrameBound::Preceding(n) => format!("{} PRECEDING", n),
+            FrameBound::PrecedingBy(offset) => format!("{} PRECEDING", offset.sql()),
+            FrameBound::CurrentRow => "CURRENT ROW".to_string(),
+            FrameBound::Following(n) => format!("{} FOLLOWING", n),
+            FrameBound::FollowingBy(offset) => format!("{} FOLLOWING", offset.sql()),
+            FrameBound::UnboundedFollowing => "UNBOUNDED FOLLOWING".to_string(),
+        }
+    }
 }
 
-/// Creates a comparison with ALL (subquery)
-/// Example: all("price", Op::LessThan, subquery) => "price < ALL (SELECT ...)"
-pub fn all<'a>(column: &'a str, op: Op<'a>, subquery: Query<'a>) -> Term<'a> {
-    let sql = format!("{} {} ALL ({})", column, op.sql(), subquery.sql());
-    Term::Atom(Box::leak(sql.into_boxed_str()))
+/// The rows a window frame leaves out, from its EXCLUDE clause
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameExclusion {
+    /// EXCLUDE CURRENT ROW
+    CurrentRow,
+    /// EXCLUDE GROUP: the current row and its peers
+    Group,
+    /// EXCLUDE TIES: the peers of the current row, but not the row itself
+    Ties,
+    /// EXCLUDE NO OTHERS, the default, spelled out
+    NoOthers,
 }
 
-// PostgreSQL parameter helpers
-
-/// Returns a PostgreSQL parameter placeholder
-/// Example: p(1) => "$1", p(2) => "$2"
-pub fn p(n: usize) -> String {
-    format!("${}", n)
+impl Sql for FrameExclusion {
+    fn sql(&self) -> String {
+        match self {
+            FrameExclusion::CurrentRow => "EXCLUDE CURRENT ROW",
+            FrameExclusion::Group => "EXCLUDE GROUP",
+            FrameExclusion::Ties => "EXCLUDE TIES",
+            FrameExclusion::NoOthers => "EXCLUDE NO OTHERS",
+        }
+        .to_string()
+    }
 }
 
-/// PostgreSQL parameter counter for auto-sequencing
-/// Automatically generates $1, $2, $3, etc. as you call seq()
+/// The Frame struct is the frame clause of a window specification. Without an end bound it
+/// renders the short form, e.g. `RANGE UNBOUNDED PRECEDING`, which ends at the current row.
 ///
 /// # Example
 /// ```
 /// use squeal::*;
-/// let mut pg = PgParams::new();
-/// let p1 = pg.seq();  // "$1"
-/// let p2 = pg.seq();  // "$2"
-/// let mut qb = Q();
-/// let query = qb
-///     .select(vec!["*"])
-///     .from("users")
-///     .where_(and(
-///         eq("id", &p1),
-///         eq("status", &p2)
-///     ))
-///     .build();
-/// assert_eq!(query.sql(), "SELECT * FROM users WHERE id = $1 AND status = $2");
+/// let moving = Frame::between(FrameMode::Rows, FrameBound::Preceding(1), FrameBound::CurrentRow);
+/// assert_eq!(moving.sql(), "ROWS BETWEEN 1 PRECEDING AND CURRENT ROW");
+/// let running = Frame::new(FrameMode::Range, FrameBound::UnboundedPreceding);
+/// assert_eq!(running.sql(), "RANGE UNBOUNDED PRECEDING");
+/// let week = Frame::between(FrameMode::Range, FrameBound::preceding_by(interval_days(7)), FrameBound::CurrentRow)
+///     .exclude(FrameExclusion::CurrentRow);
+/// assert_eq!(
+///     week.sql(),
+///     "RANGE BETWEEN INTERVAL '7 days' PRECEDING AND CURRENT ROW EXCLUDE CURRENT ROW"
+/// );
 /// ```
-pub struct PgParams {
-    count: usize,
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Frame<'a> {
+    /// ROWS, RANGE or GROUPS
+    pub mode: FrameMode,
+    /// The frame start
+    pub start: FrameBound<'a>,
+    /// The frame end; when None the frame ends at the current row
+    pub end: Option<FrameBound<'a>>,
+    /// The EXCLUDE clause, if any
+    pub exclude: Option<FrameExclusion>,
 }
 
-impl PgParams {
-    /// Creates a new parameter counter starting at 0
-    pub fn new() -> Self {
-        PgParams { count: 0 }
+impl<'a> Frame<'a> {
+    /// Creates a frame with only a start bound
+    pub fn new(mode: FrameMode, start: FrameBound<'a>) -> Frame<'a> {
+        Frame {
+            mode,
+            start,
+            end: None,
+            exclude: None,
+        }
     }
 
-    /// Returns the next parameter placeholder ($1, $2, $3, etc.)
-    pub fn seq(&mut self) -> String {
-        self.count += 1;
-        format!("${}", self.count)
+    /// Creates a `BETWEEN start AND end` frame
+    pub fn between(mode: FrameMode, start: FrameBound<'a>, end: FrameBound<'a>) -> Frame<'a> {
+        Frame {
+            mode,
+            start,
+            end: Some(end),
+            exclude: None,
+        }
+    }
+
+    /// Sets the EXCLUDE clause
+    pub fn exclude(mut self, exclusion: FrameExclusion) -> Frame<'a> {
+        self.exclude = Some(exclusion);
+        self
     }
 }
 
-impl Default for PgParams {
-    fn default() -> Self {
-        Self::new()
+impl<'a> Sql for Frame<'a> {
+    fn sql(&self) -> String {
+        let mut result = match &self.end {
+            Some(end) => format!(
+                "{} BETWEEN {} AND {}",
+                self.mode.sql(),
+                self.start.sql(),
+                end.sql()
+            ),
+            None => format!("{} {}", self.mode.sql(), self.start.sql()),
+        };
+        if let Some(exclude) = &self.exclude {
+            result.push_str(&format!(" {}", exclude.sql()));
+        }
+        result
     }
 }
 
-/// The Having struct is used to specify the having clause in a query.
-/// It is used in the Query struct.
-///
-/// It is constructed with a Term, similar to a Where clause.
-#[derive(Clone)]
-pub struct Having<'a> {
-    /// The condition for the HAVING clause
-    pub term: Term<'a>,
+/// The Window enum is the target of an OVER clause: either an inline specification or the name
+/// of a window defined in the query's WINDOW clause.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Window<'a> {
+    /// An inline window specification, rendered as `(...)`
+    Spec(WindowSpec<'a>),
+    /// A reference to a named window, rendered as the bare name
+    Named(&'a str),
 }
 
-impl<'a> Having<'a> {
-    /// Creates a new Having clause with the given term
-    ///
-    /// # Example
-    /// ```
-    /// use squeal::*;
-    /// let having = Having::new(gt("count", "5"));
-    /// assert_eq!(having.sql(), "count > 5");
-    /// ```
-    pub fn new(t: Term<'a>) -> Having<'a> {
-        Having { term: t }
+impl<'a> Sql for Window<'a> {
+    fn sql(&self) -> String {
+        match self {
+            Window::Spec(spec) => format!("({})", spec.sql()),
+            Window::Named(name) => name.to_string(),
+        }
     }
 }
 
-impl<'a> Sql for Having<'a> {
+impl<'a> Window<'a> {
+    /// Returns the expression offsets of an inline specification's frame bounds.
+    fn frame_offsets(&self) -> Vec<&Term<'a>> {
+        match self {
+            Window::Spec(WindowSpec {
+                frame: Some(frame), ..
+            }) => std::iter::once(&frame.start)
+                .chain(&frame.end)
+                .filter_map(|bound| match bound {
+                    FrameBound::PrecedingBy(offset) | FrameBound::FollowingBy(offset) => {
+                        Some(&**offset)
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the expression offsets of an inline specification's frame bounds, mutably.
+    fn frame_offsets_mut(&mut self) -> Vec<&mut Term<'a>> {
+        match self {
+            Window::Spec(WindowSpec {
+                frame: Some(frame), ..
+            }) => std::iter::once(&mut frame.start)
+                .chain(&mut frame.end)
+                .filter_map(|bound| match bound {
+                    FrameBound::PrecedingBy(offset) | FrameBound::FollowingBy(offset) => {
+                        Some(&mut **offset)
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// The sampling method of a TABLESAMPLE clause
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum SampleMethod {
+    /// Row-level sampling; slower but more uniform
+    Bernoulli,
+    /// Block-level sampling; faster but clumpier
+    System,
+}
+
+impl Sql for SampleMethod {
     fn sql(&self) -> String {
-        self.term.sql().to_string()
+        match self {
+            SampleMethod::Bernoulli => "BERNOULLI",
+            SampleMethod::System => "SYSTEM",
+        }
+        .to_string()
     }
 }
 
-/// The OrderedColumn enum is used to specify the order by clause in a query.
-/// It is used in the OrderBy struct.
-/// It is used to specify the columns, and optionally, whether they are ascending or descending.
+/// The TableSample struct represents a `TABLESAMPLE method (percentage) [REPEATABLE (seed)]`
+/// clause, used to read a random subset of a large table.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let sample = TableSample { method: SampleMethod::System, fraction: 10.0, repeatable: Some(42) };
+/// assert_eq!(sample.sql(), "TABLESAMPLE SYSTEM (10) REPEATABLE (42)");
+/// ```
+///
+/// Equality and hashing compare `fraction` by its bit pattern, so that TableSample (and the
+/// queries containing it) can be used as a map key. A NaN or infinite fraction renders as a
+/// quoted `float8` literal, which Postgres rejects as out of range rather than as a syntax
+/// error.
 #[derive(Clone)]
-pub enum OrderedColumn<'a> {
-    /// Ascending order
-    Asc(&'a str),
-    /// Descending order
-    Desc(&'a str),
+pub struct TableSample {
+    /// The sampling method
+    pub method: SampleMethod,
+    /// The percentage of the table to sample, between 0 and 100
+    pub fraction: f64,
+    /// The seed for a repeatable sample, if any
+    pub repeatable: Option<u64>,
 }
 
-/// The OrderBy struct is used to specify the order by clause in a query.
-/// It is used in the Query struct.
-/// It is used to specify the columns, and optionally, whether they are ascending or descending.
-/// Each column can be ascending or descending
-#[derive(Clone)]
-pub struct OrderBy<'a> {
-    /// List of columns with their sort order
-    pub columns: Vec<OrderedColumn<'a>>,
+impl PartialEq for TableSample {
+    fn eq(&self, other: &Self) -> bool {
+        self.method == other.method
+            && self.fraction.to_bits() == other.fraction.to_bits()
+            && self.repeatable == other.repeatable
+    }
 }
 
-impl<'a> Sql for OrderBy<'a> {
+impl Eq for TableSample {}
+
+impl Hash for TableSample {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.method.hash(state);
+        self.fraction.to_bits().hash(state);
+        self.repeatable.hash(state);
+    }
+}
+
+impl Sql for TableSample {
     fn sql(&self) -> String {
-        let mut result = "ORDER BY ".to_string();
-        let mut first = true;
-        for c in &self.columns {
-            if !first {
-                result.push_str(", ");
-            }
-            first = false;
-            match c {
-                OrderedColumn::Asc(s) => result.push_str(&format!("{} ASC", s)),
-                OrderedColumn::Desc(s) => result.push_str(&format!("{} DESC", s)),
-            }
+        // Display gives the shortest form (10, 0.5); NaN and the infinities need float()'s
+        // quoted literals to stay valid SQL.
+        let fraction = if self.fraction.is_finite() {
+            self.fraction.to_string()
+        } else {
+            float(self.fraction)
+        };
+        let mut result = format!("TABLESAMPLE {} ({})", self.method.sql(), fraction);
+        if let Some(seed) = self.repeatable {
+            result.push_str(&format!(" REPEATABLE ({})", seed));
         }
         result
     }
@@ -824,92 +4148,432 @@ impl<'a> Sql for OrderBy<'a> {
 ///     where_clause: None,
 ///     group_by: None,
 ///     having: None,
+///     window_defs: vec![],
 ///     order_by: None,
 ///     limit: None,
 ///     offset: None,
-///     for_update: false,
+///     fetch_first: None,
+///     locks: vec![],
+///     comment: None,
+///     params: Params::default(),
 /// };
 /// let from = FromSource::Subquery(Box::new(subquery), "u");
 /// assert_eq!(from.sql(), "(SELECT * FROM users) AS u");
 /// ```
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum FromSource<'a> {
     /// A simple table name
     Table(&'a str),
     /// A subquery with an alias
     Subquery(Box<Query<'a>>, &'a str),
+    /// A subquery with an alias that also names its output columns, `(...) AS t (a, b)`, so the
+    /// outer query can reference `t.a` even when the subquery selects `*`
+    AliasedSubquery {
+        /// The subquery
+        query: Box<Query<'a>>,
+        /// The table alias
+        alias: &'a str,
+        /// Names for the output columns, in select order
+        column_aliases: Vec<&'a str>,
+    },
+    /// A table read through a TABLESAMPLE clause
+    Sampled(&'a str, TableSample),
+    /// `ONLY table`: excludes rows from inheriting tables and partitions
+    Only(&'a str),
+    /// `table *`: explicitly includes rows from inheriting tables and partitions
+    WithDescendants(&'a str),
+    /// A schema-qualified table, `schema.table`, each part quoted with quote_ident() as needed
+    Qualified(&'a str, &'a str),
+    /// A set-returning function such as unnest() or generate_series(), rendered as
+    /// `func [WITH ORDINALITY] AS alias [(columns)]`
+    FunctionCall {
+        /// The function call
+        func: Term<'a>,
+        /// The table alias
+        alias: &'a str,
+        /// Names for the output columns; with ordinality, the last names the ordinal column
+        column_aliases: Vec<&'a str>,
+        /// Append a 1-based ordinal column numbering the rows
+        with_ordinality: bool,
+    },
 }
 
 impl<'a> Sql for FromSource<'a> {
     fn sql(&self) -> String {
         match self {
             FromSource::Table(table) => table.to_string(),
+            FromSource::Sampled(table, sample) => format!("{} {}", table, sample.sql()),
+            FromSource::Only(table) => format!("ONLY {}", table),
+            FromSource::Qualified(schema, table) => {
+                format!("{}.{}", quote_ident(schema), quote_ident(table))
+            }
+            FromSource::WithDescendants(table) => format!("{} *", table),
             FromSource::Subquery(query, alias) => format!("({}) AS {}", query.sql(), alias),
+            FromSource::AliasedSubquery {
+                query,
+                alias,
+                column_aliases,
+            } => {
+                let mut result = format!("({}) AS {}", query.sql(), alias);
+                if !column_aliases.is_empty() {
+                    result.push_str(&format!(" ({})", column_aliases.join(", ")));
+                }
+                result
+            }
+            FromSource::FunctionCall {
+                func,
+                alias,
+                column_aliases,
+                with_ordinality,
+            } => {
+                let mut result = func.sql();
+                if *with_ordinality {
+                    result.push_str(" WITH ORDINALITY");
+                }
+                result.push_str(&format!(" AS {}", alias));
+                if !column_aliases.is_empty() {
+                    result.push_str(&format!(" ({})", column_aliases.join(", ")));
+                }
+                result
+            }
+        }
+    }
+}
+
+/// The TargetTable struct is the table a DELETE or UPDATE modifies, optionally
+/// schema-qualified and aliased. A plain `&str` converts into it unchanged.
+///
+/// The parts are rendered as given, like a plain table string, so adding a schema does not
+/// change how the table name is case-folded. Quote them with quote_ident() where needed.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let target = TargetTable { schema: Some("public"), name: "orders", alias: Some("o") };
+/// assert_eq!(target.sql(), "public.orders AS o");
+/// assert_eq!(TargetTable::from("orders").sql(), "orders");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct TargetTable<'a> {
+    /// The schema, rendered as `schema.name`
+    pub schema: Option<&'a str>,
+    /// The table name
+    pub name: &'a str,
+    /// The alias the rest of the statement refers to the target by
+    pub alias: Option<&'a str>,
+}
+
+impl<'a> From<&'a str> for TargetTable<'a> {
+    fn from(name: &'a str) -> Self {
+        TargetTable {
+            schema: None,
+            name,
+            alias: None,
+        }
+    }
+}
+
+impl<'a> Sql for TargetTable<'a> {
+    fn sql(&self) -> String {
+        let mut result = match self.schema {
+            Some(schema) => format!("{}.{}", schema, self.name),
+            None => self.name.to_string(),
+        };
+        if let Some(alias) = self.alias {
+            result.push_str(&format!(" AS {}", alias));
+        }
+        result
+    }
+}
+
+/// Join type for SQL JOIN clauses
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum JoinType {
+    /// INNER JOIN
+    Inner,
+    /// LEFT JOIN (LEFT OUTER JOIN)
+    Left,
+    /// RIGHT JOIN (RIGHT OUTER JOIN)
+    Right,
+    /// FULL JOIN (FULL OUTER JOIN)
+    Full,
+    /// CROSS JOIN
+    Cross,
+}
+
+impl Sql for JoinType {
+    fn sql(&self) -> String {
+        match self {
+            JoinType::Inner => "INNER JOIN",
+            JoinType::Left => "LEFT JOIN",
+            JoinType::Right => "RIGHT JOIN",
+            JoinType::Full => "FULL JOIN",
+            JoinType::Cross => "CROSS JOIN",
+        }
+        .to_string()
+    }
+}
+
+/// Represents a JOIN clause in a SQL query
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Join<'a> {
+    /// The type of join (INNER, LEFT, RIGHT, FULL, CROSS)
+    pub join_type: JoinType,
+    /// The table or subquery to join
+    pub source: FromSource<'a>,
+    /// The join condition (ON clause), None for CROSS JOIN
+    pub on: Option<Term<'a>>,
+}
+
+impl<'a> Sql for Join<'a> {
+    fn sql(&self) -> String {
+        let mut result = format!("{} {}", self.join_type.sql(), self.source.sql());
+        if let Some(condition) = &self.on {
+            result.push_str(&format!(" ON {}", condition.sql()));
+        }
+        result
+    }
+}
+
+/// The FetchFirst struct represents the SQL-standard `FETCH FIRST n ROWS` clause.
+/// Unlike LIMIT, it can keep rows that tie with the last one according to the ORDER BY.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let fetch = FetchFirst { count: 5, with_ties: true };
+/// assert_eq!(fetch.sql(), "FETCH FIRST 5 ROWS WITH TIES");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FetchFirst {
+    /// The number of rows to fetch
+    pub count: u64,
+    /// Whether to also return rows tied with the last one (WITH TIES) instead of ONLY
+    pub with_ties: bool,
+}
+
+impl Sql for FetchFirst {
+    fn sql(&self) -> String {
+        let mode = if self.with_ties { "WITH TIES" } else { "ONLY" };
+        format!("FETCH FIRST {} ROWS {}", self.count, mode)
+    }
+}
+
+/// The row-lock strength of a locking clause, strongest first
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockStrength {
+    /// FOR UPDATE
+    Update,
+    /// FOR NO KEY UPDATE
+    NoKeyUpdate,
+    /// FOR SHARE
+    Share,
+    /// FOR KEY SHARE
+    KeyShare,
+}
+
+impl Sql for LockStrength {
+    fn sql(&self) -> String {
+        match self {
+            LockStrength::Update => "FOR UPDATE",
+            LockStrength::NoKeyUpdate => "FOR NO KEY UPDATE",
+            LockStrength::Share => "FOR SHARE",
+            LockStrength::KeyShare => "FOR KEY SHARE",
+        }
+        .to_string()
+    }
+}
+
+/// What a locking clause does when a row is already locked by another transaction
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LockWait {
+    /// Wait for the other transaction (the default, renders nothing)
+    #[default]
+    Wait,
+    /// Fail immediately with NOWAIT
+    NoWait,
+    /// Skip locked rows with SKIP LOCKED
+    SkipLocked,
+}
+
+/// A single locking clause. A query may carry several, e.g. to lock different joined
+/// tables with different strengths.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let lock = LockClause {
+///     strength: LockStrength::Share,
+///     of: vec!["users"],
+///     wait: LockWait::SkipLocked,
+/// };
+/// assert_eq!(lock.sql(), "FOR SHARE OF users SKIP LOCKED");
+/// assert_eq!(LockClause::new(LockStrength::Update).sql(), "FOR UPDATE");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct LockClause<'a> {
+    /// The lock strength
+    pub strength: LockStrength,
+    /// The tables to lock (OF ...); empty locks every table in the FROM clause
+    pub of: Vec<&'a str>,
+    /// The behaviour when a row is already locked
+    pub wait: LockWait,
+}
+
+impl<'a> LockClause<'a> {
+    /// Creates a locking clause of the given strength covering all tables, waiting on
+    /// conflicting locks
+    pub fn new(strength: LockStrength) -> LockClause<'a> {
+        LockClause {
+            strength,
+            of: vec![],
+            wait: LockWait::Wait,
+        }
+    }
+}
+
+impl<'a> Sql for LockClause<'a> {
+    fn sql(&self) -> String {
+        let mut result = self.strength.sql();
+        if !self.of.is_empty() {
+            result.push_str(&format!(" OF {}", self.of.join(", ")));
+        }
+        match self.wait {
+            LockWait::Wait => {}
+            LockWait::NoWait => result.push_str(" NOWAIT"),
+            LockWait::SkipLocked => result.push_str(" SKIP LOCKED"),
+        }
+        result
+    }
+}
+
+/// The statement that defines a CTE. Besides a SELECT, PostgreSQL allows data-modifying
+/// statements, whose RETURNING rows become the CTE's contents.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum CteBody<'a> {
+    /// A SELECT query
+    Select(Box<Query<'a>>),
+    /// An INSERT statement, usually with RETURNING
+    Insert(Box<Insert<'a>>),
+    /// An UPDATE statement, usually with RETURNING
+    Update(Box<Update<'a>>),
+    /// A DELETE statement, usually with RETURNING
+    Delete(Box<Delete<'a>>),
+    /// A `TABLE name` statement
+    Table(TableStmt<'a>),
+}
+
+impl<'a> Sql for CteBody<'a> {
+    fn sql(&self) -> String {
+        match self {
+            CteBody::Select(query) => query.sql(),
+            CteBody::Insert(insert) => insert.sql(),
+            CteBody::Update(update) => update.sql(),
+            CteBody::Delete(delete) => delete.sql(),
+            CteBody::Table(table) => table.sql(),
+        }
+    }
+}
+
+impl<'a> CteBody<'a> {
+    /// Returns the rendered FROM and JOIN sources of the statement, for detecting which
+    /// relations it reads.
+    fn source_sql(&self) -> Vec<String> {
+        match self {
+            CteBody::Select(query) => query_source_sql(query),
+            CteBody::Insert(insert) => match &insert.source {
+                InsertSource::Select(query) => query_source_sql(query),
+                _ => vec![],
+            },
+            CteBody::Update(update) => update.from.iter().map(|f| f.sql()).collect(),
+            CteBody::Delete(delete) => delete.using.iter().map(|f| f.sql()).collect(),
+            CteBody::Table(table) => vec![table.table.to_string()],
         }
     }
 }
 
-/// Join type for SQL JOIN clauses
-#[derive(Clone)]
-pub enum JoinType {
-    /// INNER JOIN
-    Inner,
-    /// LEFT JOIN (LEFT OUTER JOIN)
-    Left,
-    /// RIGHT JOIN (RIGHT OUTER JOIN)
-    Right,
-    /// FULL JOIN (FULL OUTER JOIN)
-    Full,
-    /// CROSS JOIN
-    Cross,
+/// Returns the rendered FROM and JOIN sources of a query.
+fn query_source_sql(query: &Query) -> Vec<String> {
+    query
+        .from
+        .iter()
+        .map(|f| f.sql())
+        .chain(query.joins.iter().map(|j| j.source.sql()))
+        .collect()
+}
+
+/// Whether `name` appears in `sql` as a whole identifier rather than as part of a longer one.
+fn mentions_identifier(sql: &str, name: &str) -> bool {
+    sql.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .any(|word| word == name)
+}
+
+impl<'a> From<Query<'a>> for CteBody<'a> {
+    fn from(query: Query<'a>) -> Self {
+        CteBody::Select(Box::new(query))
+    }
+}
+
+impl<'a> From<Insert<'a>> for CteBody<'a> {
+    fn from(insert: Insert<'a>) -> Self {
+        CteBody::Insert(Box::new(insert))
+    }
 }
 
-impl Sql for JoinType {
-    fn sql(&self) -> String {
-        match self {
-            JoinType::Inner => "INNER JOIN",
-            JoinType::Left => "LEFT JOIN",
-            JoinType::Right => "RIGHT JOIN",
-            JoinType::Full => "FULL JOIN",
-            JoinType::Cross => "CROSS JOIN",
-        }
-        .to_string()
+impl<'a> From<Update<'a>> for CteBody<'a> {
+    fn from(update: Update<'a>) -> Self {
+        CteBody::Update(Box::new(update))
     }
 }
 
-/// Represents a JOIN clause in a SQL query
-#[derive(Clone)]
-pub struct Join<'a> {
-    /// The type of join (INNER, LEFT, RIGHT, FULL, CROSS)
-    pub join_type: JoinType,
-    /// The table or subquery to join
-    pub source: FromSource<'a>,
-    /// The join condition (ON clause), None for CROSS JOIN
-    pub on: Option<Term<'a>>,
+impl<'a> From<Delete<'a>> for CteBody<'a> {
+    fn from(delete: Delete<'a>) -> Self {
+        CteBody::Delete(Box::new(delete))
+    }
 }
 
-impl<'a> Sql for Join<'a> {
-    fn sql(&self) -> String {
-        let mut result = format!("{} {}", self.join_type.sql(), self.source.sql());
-        if let Some(condition) = &self.on {
-            result.push_str(&format!(" ON {}", condition.sql()));
+impl<'a> From<TableStmt<'a>> for CteBody<'a> {
+    fn from(table: TableStmt<'a>) -> Self {
+        CteBody::Table(table)
+    }
+}
+
+/// Appends a `WITH ... ` clause, followed by a space, if there are any CTEs.
+pub(crate) fn push_with_clause(result: &mut String, with_clause: &Option<Vec<Cte>>) {
+    if let Some(ctes) = with_clause {
+        result.push_str("WITH ");
+        let mut first = true;
+        for cte in ctes {
+            if !first {
+                result.push_str(", ");
+            }
+            first = false;
+            result.push_str(&cte.sql());
         }
-        result
+        result.push(' ');
     }
 }
 
 /// Represents a Common Table Expression (CTE) in a WITH clause
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Cte<'a> {
     /// The name of the CTE
     pub name: &'a str,
-    /// The query that defines the CTE
-    pub query: Box<Query<'a>>,
+    /// The statement that defines the CTE
+    pub body: CteBody<'a>,
+    /// `Some(true)` renders `AS MATERIALIZED`, `Some(false)` renders `AS NOT MATERIALIZED`,
+    /// and `None` leaves the inlining decision to the planner.
+    pub materialized: Option<bool>,
 }
 
 impl<'a> Sql for Cte<'a> {
     fn sql(&self) -> String {
-        format!("{} AS ({})", self.name, self.query.sql())
+        let hint = match self.materialized {
+            Some(true) => "MATERIALIZED ",
+            Some(false) => "NOT MATERIALIZED ",
+            None => "",
+        };
+        format!("{} AS {}({})", self.name, hint, self.body.sql())
     }
 }
 
@@ -917,7 +4581,7 @@ impl<'a> Sql for Cte<'a> {
 /// The user is expected to construct the Query object and then call the sql() method to get the
 /// SQL string.
 ///
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Query<'a> {
     /// WITH clause (Common Table Expressions)
     pub with_clause: Option<Vec<Cte<'a>>>,
@@ -929,18 +4593,28 @@ pub struct Query<'a> {
     pub joins: Vec<Join<'a>>,
     /// The conditions for the where clause, if it exists.
     pub where_clause: Option<Term<'a>>,
-    /// The columns to group by, if any.
-    pub group_by: Option<Vec<&'a str>>,
+    /// The columns or expressions to group by, if any.
+    pub group_by: Option<Vec<Term<'a>>>,
     /// The having clause conditions, if any.
     pub having: Option<Having<'a>>,
+    /// Named window definitions for the WINDOW clause.
+    pub window_defs: Vec<(&'a str, WindowSpec<'a>)>,
     /// The order by clause, if any.
     pub order_by: Option<OrderBy<'a>>,
     /// The maximum number of rows to return.
     pub limit: Option<u64>,
     /// The number of rows to skip.
     pub offset: Option<u64>,
-    /// Whether to lock rows with FOR UPDATE.
-    pub for_update: bool,
+    /// The FETCH FIRST clause, an alternative to LIMIT. If both are set, FETCH FIRST wins and
+    /// LIMIT is not rendered; OFFSET is then rendered in its `OFFSET n ROWS` form.
+    pub fetch_first: Option<FetchFirst>,
+    /// The row-locking clauses (FOR UPDATE, FOR SHARE, ...), rendered in order.
+    pub locks: Vec<LockClause<'a>>,
+    /// A comment prepended to the statement as `/* ... */`, e.g. for query tagging.
+    /// Comment delimiters inside it are neutralized on render.
+    pub comment: Option<&'a str>,
+    /// The placeholders handed out by the builder, including those of nested statements.
+    pub params: Params,
 }
 
 /// The QueryBuilder struct is a fluent interface for building a Query.
@@ -957,18 +4631,24 @@ pub struct QueryBuilder<'a> {
     pub joins: Vec<Join<'a>>,
     /// The WHERE clause conditions
     pub where_clause: Option<Term<'a>>,
-    /// The columns to GROUP BY
-    pub group_by: Option<Vec<&'a str>>,
+    /// The columns or expressions to GROUP BY
+    pub group_by: Option<Vec<Term<'a>>>,
     /// The HAVING clause conditions
     pub having: Option<Having<'a>>,
+    /// Named window definitions for the WINDOW clause
+    pub window_defs: Vec<(&'a str, WindowSpec<'a>)>,
     /// The ORDER BY clause
     pub order_by: Option<OrderBy<'a>>,
     /// The LIMIT value
     pub limit: Option<u64>,
     /// The OFFSET value
     pub offset: Option<u64>,
-    /// Whether to use FOR UPDATE
-    pub for_update: bool,
+    /// The FETCH FIRST clause
+    pub fetch_first: Option<FetchFirst>,
+    /// The row-locking clauses
+    pub locks: Vec<LockClause<'a>>,
+    /// The leading comment
+    pub comment: Option<&'a str>,
     /// PostgreSQL parameter counter
     pub params: PgParams,
 }
@@ -986,10 +4666,13 @@ pub fn Q<'a>() -> QueryBuilder<'a> {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
         params: PgParams::new(),
     }
 }
@@ -1005,7 +4688,7 @@ impl<'a> QueryBuilder<'a> {
     /// assert_eq!(query.sql(), "SELECT * FROM users");
     /// ```
     pub fn build(&self) -> Query<'a> {
-        Query {
+        let mut query = Query {
             with_clause: self.with_clause.clone(),
             select: self.select.clone(),
             from: self.from.clone(),
@@ -1013,10 +4696,132 @@ impl<'a> QueryBuilder<'a> {
             where_clause: self.where_clause.clone(),
             group_by: self.group_by.clone(),
             having: self.having.clone(),
+            window_defs: self.window_defs.clone(),
             order_by: self.order_by.clone(),
             limit: self.limit,
             offset: self.offset,
-            for_update: self.for_update,
+            fetch_first: self.fetch_first.clone(),
+            locks: self.locks.clone(),
+            comment: self.comment,
+            params: self.params.params(),
+        };
+        query.adopt_nested_params();
+        query
+    }
+
+    /// Builds the final Query after running the builder's validations: validate_cte_order(),
+    /// validate_distinct_on(), validate_distinct_order_by(), validate_having() and
+    /// validate_fetch_first().
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let result = qb
+    ///     .select(vec!["dept", "COUNT(*)"])
+    ///     .from("employees")
+    ///     .group_by(vec!["dept"])
+    ///     .having(gt("salary", "1000"))
+    ///     .try_build();
+    /// assert!(matches!(result, Err(BuildError::InvalidHaving { .. })));
+    /// ```
+    pub fn try_build(&self) -> Result<Query<'a>, BuildError> {
+        self.validate_cte_order()?;
+        self.validate_distinct_on()?;
+        self.validate_distinct_order_by()?;
+        self.validate_having()?;
+        self.validate_fetch_first()?;
+        Ok(self.build())
+    }
+
+    /// Checks that a SELECT DISTINCT only orders by expressions in its select list. An ORDER
+    /// BY column is found when it matches a select item or its alias, or is covered by `*` or
+    /// `table.*`. Rendering is not affected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let builder = qb
+    ///     .select(vec!["city"])
+    ///     .from("users")
+    ///     .distinct()
+    ///     .order_by(vec![OrderedColumn::Asc("signup_date")]);
+    /// assert_eq!(
+    ///     builder.validate_distinct_order_by(),
+    ///     Err(BuildError::DistinctOrderByNotSelected { columns: vec!["signup_date".to_string()] })
+    /// );
+    /// ```
+    pub fn validate_distinct_order_by(&self) -> Result<(), BuildError> {
+        let Some(select) = &self.select else {
+            return Ok(());
+        };
+        let (Some(Distinct::All), Some(order_by)) = (&select.distinct, &self.order_by) else {
+            return Ok(());
+        };
+        let missing: Vec<String> = order_by
+            .columns
+            .iter()
+            .map(|c| c.column())
+            .filter(|col| !is_selected(col, &select.cols))
+            .map(|col| col.to_string())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(BuildError::DistinctOrderByNotSelected { columns: missing })
+        }
+    }
+
+    /// Checks that the HAVING condition only references columns inside aggregate calls or
+    /// columns listed in GROUP BY. Column references are recognized in typed terms and plain
+    /// atoms; raw SQL fragments and subqueries are not inspected. Rendering is not affected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let builder = qb
+    ///     .select(vec!["dept", "COUNT(*)"])
+    ///     .from("employees")
+    ///     .group_by(vec!["dept"])
+    ///     .having(and(gt("COUNT(*)", "5"), ne("dept", "'hr'")));
+    /// assert_eq!(builder.validate_having(), Ok(()));
+    /// ```
+    pub fn validate_having(&self) -> Result<(), BuildError> {
+        let Some(having) = &self.having else {
+            return Ok(());
+        };
+        let grouped: Vec<String> = self.group_by.iter().flatten().map(|t| t.sql()).collect();
+        let mut columns = Vec::new();
+        ungrouped_columns(&having.term, &mut columns);
+        match columns.into_iter().find(|c| !is_grouped(c, &grouped)) {
+            Some(column) => Err(BuildError::InvalidHaving {
+                column: column.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that FETCH FIRST ... WITH TIES comes with an ORDER BY, which Postgres needs to
+    /// tell which rows tie with the last one. Rendering is not affected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let builder = qb.select(vec!["*"]).from("scores").fetch_first(5, true);
+    /// assert_eq!(builder.validate_fetch_first(), Err(BuildError::TiesWithoutOrderBy));
+    /// ```
+    pub fn validate_fetch_first(&self) -> Result<(), BuildError> {
+        match (&self.fetch_first, &self.order_by) {
+            (
+                Some(FetchFirst {
+                    with_ties: true, ..
+                }),
+                None,
+            ) => Err(BuildError::TiesWithoutOrderBy),
+            _ => Ok(()),
         }
     }
 
@@ -1033,10 +4838,14 @@ impl<'a> QueryBuilder<'a> {
     ///     where_clause: Some(eq("active", "true")),
     ///     group_by: None,
     ///     having: None,
+    ///     window_defs: vec![],
     ///     order_by: None,
     ///     limit: None,
     ///     offset: None,
-    ///     for_update: false,
+    ///     fetch_first: None,
+    ///     locks: vec![],
+    ///     comment: None,
+    ///     params: Params::default(),
     /// };
     /// let mut qb = Q();
     /// let query = qb.with("active_users", cte_query)
@@ -1045,11 +4854,44 @@ impl<'a> QueryBuilder<'a> {
     ///     .build();
     /// assert_eq!(query.sql(), "WITH active_users AS (SELECT id, name FROM users WHERE active = true) SELECT * FROM active_users");
     /// ```
-    pub fn with(&'a mut self, name: &'a str, query: Query<'a>) -> &'a mut QueryBuilder<'a> {
-        let cte = Cte {
+    ///
+    /// The CTE may also be a data-modifying statement with a RETURNING clause:
+    /// ```
+    /// use squeal::*;
+    /// let expired = D("sessions").where_(lt("expires_at", "NOW()")).returning(Columns::Star).build();
+    /// let mut qb = Q();
+    /// let query = qb.with("removed", expired).select(vec!["count(*)"]).from("removed").build();
+    /// assert_eq!(query.sql(), "WITH removed AS (DELETE FROM sessions WHERE expires_at < NOW() RETURNING *) SELECT count(*) FROM removed");
+    /// ```
+    pub fn with(&mut self, name: &'a str, body: impl Into<CteBody<'a>>) -> &mut QueryBuilder<'a> {
+        self.with_cte(Cte {
             name,
-            query: Box::new(query),
-        };
+            body: body.into(),
+            materialized: None,
+        })
+    }
+
+    /// Adds an already-formed CTE to the WITH clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let ctes = vec![
+    ///     Q().select(vec!["id"]).from("users").build().as_cte("u"),
+    ///     Q().select(vec!["user_id"]).from("orders").build().as_cte("o"),
+    /// ];
+    /// let mut qb = Q();
+    /// for cte in ctes {
+    ///     qb.with_cte(cte);
+    /// }
+    /// let query = qb.select(vec!["*"]).from("u").inner_join("o", eq("u.id", "o.user_id")).build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "WITH u AS (SELECT id FROM users), o AS (SELECT user_id FROM orders) \
+    ///      SELECT * FROM u INNER JOIN o ON u.id = o.user_id"
+    /// );
+    /// ```
+    pub fn with_cte(&mut self, cte: Cte<'a>) -> &mut QueryBuilder<'a> {
         match &mut self.with_clause {
             None => self.with_clause = Some(vec![cte]),
             Some(ctes) => ctes.push(cte),
@@ -1057,6 +4899,33 @@ impl<'a> QueryBuilder<'a> {
         self
     }
 
+    /// Adds a WITH clause with an explicit MATERIALIZED or NOT MATERIALIZED hint (PostgreSQL 12+)
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut inner = Q();
+    /// let cte_query = inner.select(vec!["id"]).from("users").build();
+    /// let mut qb = Q();
+    /// let query = qb.with_materialized("u", cte_query, false)
+    ///     .select(vec!["*"])
+    ///     .from("u")
+    ///     .build();
+    /// assert_eq!(query.sql(), "WITH u AS NOT MATERIALIZED (SELECT id FROM users) SELECT * FROM u");
+    /// ```
+    pub fn with_materialized(
+        &mut self,
+        name: &'a str,
+        body: impl Into<CteBody<'a>>,
+        materialized: bool,
+    ) -> &mut QueryBuilder<'a> {
+        self.with_cte(Cte {
+            name,
+            body: body.into(),
+            materialized: Some(materialized),
+        })
+    }
+
     /// Sets the columns to SELECT
     ///
     /// # Example
@@ -1066,8 +4935,8 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["id", "name"]).from("users").build();
     /// assert_eq!(query.sql(), "SELECT id, name FROM users");
     /// ```
-    pub fn select(&'a mut self, cols: Vec<&'a str>) -> &'a mut QueryBuilder<'a> {
-        self.select = Some(Select::new(Columns::Selected(cols), None));
+    pub fn select(&mut self, cols: Vec<&'a str>) -> &mut QueryBuilder<'a> {
+        self.set_columns(Columns::Selected(cols));
         self
     }
 
@@ -1084,10 +4953,14 @@ impl<'a> QueryBuilder<'a> {
     ///     where_clause: None,
     ///     group_by: None,
     ///     having: None,
+    ///     window_defs: vec![],
     ///     order_by: None,
     ///     limit: None,
     ///     offset: None,
-    ///     for_update: false,
+    ///     fetch_first: None,
+    ///     locks: vec![],
+    ///     comment: None,
+    ///     params: Params::default(),
     /// };
     /// let mut qb = Q();
     /// let query = qb.select_expressions(vec![
@@ -1097,14 +4970,21 @@ impl<'a> QueryBuilder<'a> {
     /// assert_eq!(query.sql(), "SELECT id, (SELECT COUNT(*) FROM orders) AS order_count FROM users");
     /// ```
     pub fn select_expressions(
-        &'a mut self,
+        &mut self,
         exprs: Vec<SelectExpression<'a>>,
-    ) -> &'a mut QueryBuilder<'a> {
-        self.select = Some(Select::new(Columns::Expressions(exprs), None));
+    ) -> &mut QueryBuilder<'a> {
+        self.set_columns(Columns::Expressions(exprs));
         self
     }
+
+    /// Replaces the select list, keeping a DISTINCT set before it
+    fn set_columns(&mut self, cols: Columns<'a>) {
+        let distinct = self.select.take().and_then(|s| s.distinct);
+        self.select = Some(Select::new(cols, distinct));
+    }
+
     /// Sets the SELECT clause to be DISTINCT
-    pub fn distinct(&'a mut self) -> &'a mut QueryBuilder<'a> {
+    pub fn distinct(&mut self) -> &mut QueryBuilder<'a> {
         if let Some(s) = &mut self.select {
             s.distinct = Some(Distinct::All);
         }
@@ -1112,12 +4992,133 @@ impl<'a> QueryBuilder<'a> {
     }
 
     /// Sets the SELECT clause to be DISTINCT ON the given columns
-    pub fn distinct_on(&'a mut self, cols: Vec<&'a str>) -> &'a mut QueryBuilder<'a> {
+    pub fn distinct_on(&mut self, cols: Vec<&'a str>) -> &mut QueryBuilder<'a> {
         if let Some(s) = &mut self.select {
             s.distinct = Some(Distinct::On(cols));
         }
         self
     }
+
+    /// Keeps one row per group, the one with the latest (or, when `desc` is false, the
+    /// earliest) `order_col`: sets `DISTINCT ON (group_cols)` and an ORDER BY that starts with
+    /// the same columns, as Postgres requires. NULLs in `order_col` sort last, so a row with a
+    /// value wins over one without. It may be called before or after the select list is set.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let query = Q()
+    ///     .select(vec!["user_id", "status", "changed_at"])
+    ///     .from("status_changes")
+    ///     .distinct_on_latest(vec!["user_id"], "changed_at", true)
+    ///     .build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT DISTINCT ON (user_id) user_id, status, changed_at FROM status_changes \
+    ///      ORDER BY user_id, changed_at DESC NULLS LAST"
+    /// );
+    /// ```
+    pub fn distinct_on_latest(
+        &mut self,
+        group_cols: Vec<&'a str>,
+        order_col: &'a str,
+        desc: bool,
+    ) -> &mut QueryBuilder<'a> {
+        let mut columns: Vec<OrderedColumn<'a>> = group_cols
+            .iter()
+            .copied()
+            .map(OrderedColumn::Column)
+            .collect();
+        columns.push(if desc {
+            OrderedColumn::Desc(order_col).nulls_last()
+        } else {
+            OrderedColumn::Asc(order_col).nulls_last()
+        });
+        self.order_by = Some(OrderBy { columns });
+        // Start a `SELECT *` list if none is set yet; a later select() keeps the DISTINCT ON.
+        self.select
+            .get_or_insert_with(|| Select::new(Columns::Star, None))
+            .distinct = Some(Distinct::On(group_cols));
+        self
+    }
+
+    /// Checks that no CTE reads from a CTE defined after it in the WITH clause, which Postgres
+    /// rejects for a non-recursive WITH. References are detected by name in each CTE's FROM and
+    /// JOIN sources; a CTE naming itself is left alone. Rendering is not affected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut totals = Q();
+    /// let totals = totals.select(vec!["user_id", "SUM(amount) AS total"]).from("paid").group_by(vec!["user_id"]).build();
+    /// let mut paid = Q();
+    /// let paid = paid.select(vec!["*"]).from("payments").where_(eq("status", "'paid'")).build();
+    /// let mut qb = Q();
+    /// let builder = qb.with("totals", totals).with("paid", paid).select(vec!["*"]).from("totals");
+    /// assert_eq!(
+    ///     builder.validate_cte_order(),
+    ///     Err(BuildError::CteOrder { cte: "totals".to_string(), references: "paid".to_string() })
+    /// );
+    /// ```
+    pub fn validate_cte_order(&self) -> Result<(), BuildError> {
+        let Some(ctes) = &self.with_clause else {
+            return Ok(());
+        };
+        for (i, cte) in ctes.iter().enumerate() {
+            let sources = cte.body.source_sql();
+            for later in &ctes[i + 1..] {
+                if later.name != cte.name
+                    && sources.iter().any(|s| mentions_identifier(s, later.name))
+                {
+                    return Err(BuildError::CteOrder {
+                        cte: cte.name.to_string(),
+                        references: later.name.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that a DISTINCT ON query's ORDER BY starts with the DISTINCT ON expressions, as
+    /// Postgres requires. The leading ORDER BY columns may list them in any order, so
+    /// `DISTINCT ON (a, b) ... ORDER BY b, a, c` passes. Queries without DISTINCT ON or without
+    /// an ORDER BY always pass. Rendering is not affected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let builder = qb
+    ///     .select(vec!["user_id", "created_at"])
+    ///     .from("events")
+    ///     .distinct_on(vec!["user_id"])
+    ///     .order_by(vec![OrderedColumn::Desc("created_at")]);
+    /// assert!(matches!(
+    ///     builder.validate_distinct_on(),
+    ///     Err(BuildError::DistinctOnOrderMismatch { .. })
+    /// ));
+    /// ```
+    pub fn validate_distinct_on(&self) -> Result<(), BuildError> {
+        let Some(Distinct::On(cols)) = self.select.as_ref().and_then(|s| s.distinct.as_ref())
+        else {
+            return Ok(());
+        };
+        let Some(order_by) = &self.order_by else {
+            return Ok(());
+        };
+        let ordered: Vec<&str> = order_by.columns.iter().map(|c| c.column()).collect();
+        let leading: BTreeSet<&str> = ordered.iter().take(cols.len()).copied().collect();
+        let distinct: BTreeSet<&str> = cols.iter().copied().collect();
+        if ordered.len() >= cols.len() && leading == distinct {
+            Ok(())
+        } else {
+            Err(BuildError::DistinctOnOrderMismatch {
+                distinct_on: cols.iter().map(|c| c.to_string()).collect(),
+                order_by: ordered.iter().map(|c| c.to_string()).collect(),
+            })
+        }
+    }
     /// Sets the table to SELECT FROM
     ///
     /// # Example
@@ -1127,11 +5128,95 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["*"]).from("products").build();
     /// assert_eq!(query.sql(), "SELECT * FROM products");
     /// ```
-    pub fn from(&'a mut self, table: &'a str) -> &'a mut QueryBuilder<'a> {
+    pub fn from(&mut self, table: &'a str) -> &mut QueryBuilder<'a> {
         self.from = Some(FromSource::Table(table));
         self
     }
 
+    /// Sets the table to SELECT FROM, excluding its inheriting tables and partitions
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["*"]).from_only("measurements").build();
+    /// assert_eq!(query.sql(), "SELECT * FROM ONLY measurements");
+    /// ```
+    pub fn from_only(&mut self, table: &'a str) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::Only(table));
+        self
+    }
+
+    /// Sets the table to SELECT FROM, explicitly including its inheriting tables and partitions
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["*"]).from_with_descendants("measurements").build();
+    /// assert_eq!(query.sql(), "SELECT * FROM measurements *");
+    /// ```
+    pub fn from_with_descendants(&mut self, table: &'a str) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::WithDescendants(table));
+        self
+    }
+
+    /// Sets a schema-qualified table to SELECT FROM, quoting each part as needed
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["*"]).from_qualified("Analytics", "events").build();
+    /// assert_eq!(query.sql(), "SELECT * FROM \"Analytics\".events");
+    /// ```
+    pub fn from_qualified(&mut self, schema: &'a str, table: &'a str) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::Qualified(schema, table));
+        self
+    }
+
+    /// Selects from a set-returning function
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["*"])
+    ///     .from_function(unnest(Term::Atom("ARRAY[1, 2, 3]")), "t", vec!["val", "idx"], true)
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT * FROM unnest(ARRAY[1, 2, 3]) WITH ORDINALITY AS t (val, idx)");
+    /// ```
+    pub fn from_function(
+        &mut self,
+        func: Term<'a>,
+        alias: &'a str,
+        column_aliases: Vec<&'a str>,
+        with_ordinality: bool,
+    ) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::FunctionCall {
+            func,
+            alias,
+            column_aliases,
+            with_ordinality,
+        });
+        self
+    }
+
+    /// Sets the table to SELECT FROM, read through a TABLESAMPLE clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let sample = TableSample { method: SampleMethod::Bernoulli, fraction: 10.0, repeatable: None };
+    /// let query = qb.select(vec!["count(*)"]).from_sampled("big_table", sample).build();
+    /// assert_eq!(query.sql(), "SELECT count(*) FROM big_table TABLESAMPLE BERNOULLI (10)");
+    /// ```
+    pub fn from_sampled(&mut self, table: &'a str, sample: TableSample) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::Sampled(table, sample));
+        self
+    }
+
     /// Sets a subquery as the FROM source
     ///
     /// # Example
@@ -1145,24 +5230,76 @@ impl<'a> QueryBuilder<'a> {
     ///     where_clause: None,
     ///     group_by: None,
     ///     having: None,
+    ///     window_defs: vec![],
     ///     order_by: None,
     ///     limit: None,
     ///     offset: None,
-    ///     for_update: false,
+    ///     fetch_first: None,
+    ///     locks: vec![],
+    ///     comment: None,
+    ///     params: Params::default(),
     /// };
     /// let mut qb = Q();
     /// let query = qb.select(vec!["*"]).from_subquery(subquery, "u").build();
     /// assert_eq!(query.sql(), "SELECT * FROM (SELECT * FROM users) AS u");
     /// ```
-    pub fn from_subquery(
-        &'a mut self,
+    pub fn from_subquery(&mut self, subquery: Query<'a>, alias: &'a str) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::Subquery(Box::new(subquery), alias));
+        self
+    }
+
+    /// Sets a subquery as the FROM source, renaming its output columns with `AS alias (cols)`
+    ///
+    /// The names apply positionally, which gives predictable column names for the outer query
+    /// even when the subquery selects `*`.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let subquery = Q().select(vec!["*"]).from("users").build();
+    /// let query = Q()
+    ///     .select(vec!["u.user_id", "u.user_name"])
+    ///     .from_subquery_as(subquery, "u", vec!["user_id", "user_name"])
+    ///     .build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT u.user_id, u.user_name FROM (SELECT * FROM users) AS u (user_id, user_name)"
+    /// );
+    /// ```
+    pub fn from_subquery_as(
+        &mut self,
         subquery: Query<'a>,
         alias: &'a str,
-    ) -> &'a mut QueryBuilder<'a> {
-        self.from = Some(FromSource::Subquery(Box::new(subquery), alias));
+        columns: Vec<&'a str>,
+    ) -> &mut QueryBuilder<'a> {
+        self.from = Some(FromSource::AliasedSubquery {
+            query: Box::new(subquery),
+            alias,
+            column_aliases: columns,
+        });
         self
     }
 
+    /// Sets the FROM clause to a subquery built inline by the given closure
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb
+    ///     .select(vec!["*"])
+    ///     .from_subquery_with("active", |q| q.select(vec!["id"]).from("users").where_(eq("active", "TRUE")))
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT * FROM (SELECT id FROM users WHERE active = TRUE) AS active");
+    /// ```
+    pub fn from_subquery_with(
+        &mut self,
+        alias: &'a str,
+        build: impl for<'b> FnOnce(&'b mut QueryBuilder<'a>) -> &'b mut QueryBuilder<'a>,
+    ) -> &mut QueryBuilder<'a> {
+        self.from_subquery(subquery(build), alias)
+    }
+
     /// Adds an INNER JOIN clause
     ///
     /// # Example
@@ -1175,7 +5312,7 @@ impl<'a> QueryBuilder<'a> {
     ///     .build();
     /// assert_eq!(query.sql(), "SELECT users.name, orders.total FROM users INNER JOIN orders ON users.id = orders.user_id");
     /// ```
-    pub fn inner_join(&'a mut self, table: &'a str, on: Term<'a>) -> &'a mut QueryBuilder<'a> {
+    pub fn inner_join(&mut self, table: &'a str, on: Term<'a>) -> &mut QueryBuilder<'a> {
         self.joins.push(Join {
             join_type: JoinType::Inner,
             source: FromSource::Table(table),
@@ -1184,6 +5321,26 @@ impl<'a> QueryBuilder<'a> {
         self
     }
 
+    /// Adds an INNER JOIN clause whose condition is the equality of two columns
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let query = Q().select(vec!["users.name", "orders.total"])
+    ///     .from("users")
+    ///     .inner_join_on_eq("orders", "users.id", "orders.user_id")
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT users.name, orders.total FROM users INNER JOIN orders ON users.id = orders.user_id");
+    /// ```
+    pub fn inner_join_on_eq(
+        &mut self,
+        table: &'a str,
+        left_col: &'a str,
+        right_col: &'a str,
+    ) -> &mut QueryBuilder<'a> {
+        self.inner_join(table, eq(left_col, right_col))
+    }
+
     /// Adds a LEFT JOIN clause
     ///
     /// # Example
@@ -1196,7 +5353,7 @@ impl<'a> QueryBuilder<'a> {
     ///     .build();
     /// assert_eq!(query.sql(), "SELECT users.name, orders.total FROM users LEFT JOIN orders ON users.id = orders.user_id");
     /// ```
-    pub fn left_join(&'a mut self, table: &'a str, on: Term<'a>) -> &'a mut QueryBuilder<'a> {
+    pub fn left_join(&mut self, table: &'a str, on: Term<'a>) -> &mut QueryBuilder<'a> {
         self.joins.push(Join {
             join_type: JoinType::Left,
             source: FromSource::Table(table),
@@ -1217,7 +5374,7 @@ impl<'a> QueryBuilder<'a> {
     ///     .build();
     /// assert_eq!(query.sql(), "SELECT users.name, orders.total FROM users RIGHT JOIN orders ON users.id = orders.user_id");
     /// ```
-    pub fn right_join(&'a mut self, table: &'a str, on: Term<'a>) -> &'a mut QueryBuilder<'a> {
+    pub fn right_join(&mut self, table: &'a str, on: Term<'a>) -> &mut QueryBuilder<'a> {
         self.joins.push(Join {
             join_type: JoinType::Right,
             source: FromSource::Table(table),
@@ -1238,7 +5395,7 @@ impl<'a> QueryBuilder<'a> {
     ///     .build();
     /// assert_eq!(query.sql(), "SELECT users.name, orders.total FROM users FULL JOIN orders ON users.id = orders.user_id");
     /// ```
-    pub fn full_join(&'a mut self, table: &'a str, on: Term<'a>) -> &'a mut QueryBuilder<'a> {
+    pub fn full_join(&mut self, table: &'a str, on: Term<'a>) -> &mut QueryBuilder<'a> {
         self.joins.push(Join {
             join_type: JoinType::Full,
             source: FromSource::Table(table),
@@ -1259,7 +5416,7 @@ impl<'a> QueryBuilder<'a> {
     ///     .build();
     /// assert_eq!(query.sql(), "SELECT users.name, colors.name FROM users CROSS JOIN colors");
     /// ```
-    pub fn cross_join(&'a mut self, table: &'a str) -> &'a mut QueryBuilder<'a> {
+    pub fn cross_join(&mut self, table: &'a str) -> &mut QueryBuilder<'a> {
         self.joins.push(Join {
             join_type: JoinType::Cross,
             source: FromSource::Table(table),
@@ -1279,33 +5436,61 @@ impl<'a> QueryBuilder<'a> {
     ///     from: Some(FromSource::Table("orders")),
     ///     joins: vec![],
     ///     where_clause: None,
-    ///     group_by: Some(vec!["user_id"]),
+    ///     group_by: Some(vec![Term::Atom("user_id")]),
     ///     having: None,
+    ///     window_defs: vec![],
     ///     order_by: None,
     ///     limit: None,
     ///     offset: None,
-    ///     for_update: false,
+    ///     fetch_first: None,
+    ///     locks: vec![],
+    ///     comment: None,
+    ///     params: Params::default(),
     /// };
     /// let mut qb = Q();
     /// let query = qb.select(vec!["users.name", "oc.order_count"])
     ///     .from("users")
-    ///     .join_subquery(JoinType::Left, subquery, "oc", eq("users.id", "oc.user_id"))
+    ///     .join_subquery(JoinType::Left, subquery, "oc", eq("users.id", "oc.user_id"))
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT users.name, oc.order_count FROM users LEFT JOIN (SELECT user_id, COUNT(*) as order_count FROM orders GROUP BY user_id) AS oc ON users.id = oc.user_id");
+    /// ```
+    pub fn join_subquery(
+        &mut self,
+        join_type: JoinType,
+        subquery: Query<'a>,
+        alias: &'a str,
+        on: Term<'a>,
+    ) -> &mut QueryBuilder<'a> {
+        self.joins.push(Join {
+            join_type,
+            source: FromSource::Subquery(Box::new(subquery), alias),
+            on: Some(on),
+        });
+        self
+    }
+
+    /// Adds a JOIN against a subquery built inline by the given closure
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["users.name", "oc.n"])
+    ///     .from("users")
+    ///     .join_subquery_with(JoinType::Left, "oc", eq("users.id", "oc.user_id"), |q| {
+    ///         q.select(vec!["user_id", "COUNT(*) AS n"]).from("orders").group_by(vec!["user_id"])
+    ///     })
     ///     .build();
-    /// assert_eq!(query.sql(), "SELECT users.name, oc.order_count FROM users LEFT JOIN (SELECT user_id, COUNT(*) as order_count FROM orders GROUP BY user_id) AS oc ON users.id = oc.user_id");
+    /// assert_eq!(query.sql(), "SELECT users.name, oc.n FROM users LEFT JOIN (SELECT user_id, COUNT(*) AS n FROM orders GROUP BY user_id) AS oc ON users.id = oc.user_id");
     /// ```
-    pub fn join_subquery(
-        &'a mut self,
+    pub fn join_subquery_with(
+        &mut self,
         join_type: JoinType,
-        subquery: Query<'a>,
         alias: &'a str,
         on: Term<'a>,
-    ) -> &'a mut QueryBuilder<'a> {
-        self.joins.push(Join {
-            join_type,
-            source: FromSource::Subquery(Box::new(subquery), alias),
-            on: Some(on),
-        });
-        self
+        build: impl for<'b> FnOnce(&'b mut QueryBuilder<'a>) -> &'b mut QueryBuilder<'a>,
+    ) -> &mut QueryBuilder<'a> {
+        self.join_subquery(join_type, subquery(build), alias, on)
     }
 
     /// Sets the WHERE clause
@@ -1317,14 +5502,14 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["*"]).from("users").where_(eq("id", "1")).build();
     /// assert_eq!(query.sql(), "SELECT * FROM users WHERE id = 1");
     /// ```
-    pub fn where_(&'a mut self, term: Term<'a>) -> &'a mut QueryBuilder<'a> {
+    pub fn where_(&mut self, term: Term<'a>) -> &mut QueryBuilder<'a> {
         self.where_clause = Some(term);
         self
     }
 
     /// Sets WHERE clause only if the Option contains Some value
     /// Useful for conditional/dynamic query building
-    pub fn where_opt(&'a mut self, term: Option<Term<'a>>) -> &'a mut QueryBuilder<'a> {
+    pub fn where_opt(&mut self, term: Option<Term<'a>>) -> &mut QueryBuilder<'a> {
         if let Some(t) = term {
             self.where_clause = Some(t);
         }
@@ -1334,20 +5519,43 @@ impl<'a> QueryBuilder<'a> {
     /// Adds a condition to the WHERE clause with AND
     /// If no WHERE clause exists yet, this becomes the first condition
     /// Otherwise, it ANDs the new condition with the existing one
-    pub fn and_where(&'a mut self, term: Term<'a>) -> &'a mut QueryBuilder<'a> {
-        match &self.where_clause {
-            None => self.where_clause = Some(term),
-            Some(existing) => {
-                self.where_clause = Some(Term::Condition(
-                    Box::new(existing.clone()),
-                    Op::And,
-                    Box::new(term),
-                ));
-            }
-        }
+    pub fn and_where(&mut self, term: Term<'a>) -> &mut QueryBuilder<'a> {
+        self.where_clause = Some(match self.where_clause.take() {
+            None => term,
+            Some(existing) => and(existing, term),
+        });
         self
     }
 
+    /// Binds `value` as a parameter and ANDs `col op $n` into the WHERE clause, for mapping
+    /// dynamic filters such as REST query strings onto SQL
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// qb.select(vec!["*"]).from("users");
+    /// for (col, op, value) in [("status", "=", "active"), ("age", ">=", "18")] {
+    ///     qb.filter(col, parse_op(op), value);
+    /// }
+    /// let (sql, values) = qb.build().render().unwrap();
+    /// assert_eq!(sql, "SELECT * FROM users WHERE status = $1 AND age >= $2");
+    /// assert_eq!(values, vec![Value::Text("active".to_string()), Value::Text("18".to_string())]);
+    /// ```
+    pub fn filter(
+        &mut self,
+        col: &'a str,
+        op: Op<'a>,
+        value: impl Into<Value>,
+    ) -> &mut QueryBuilder<'a> {
+        let placeholder = self.bind(value);
+        self.and_where(Term::Condition(
+            Box::new(Term::Atom(col)),
+            op,
+            Box::new(Term::Raw(Cow::Owned(placeholder))),
+        ))
+    }
+
     /// Sets the GROUP BY clause
     ///
     /// # Example
@@ -1357,8 +5565,32 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["category", "count(*)"]).from("products").group_by(vec!["category"]).build();
     /// assert_eq!(query.sql(), "SELECT category, count(*) FROM products GROUP BY category");
     /// ```
-    pub fn group_by(&'a mut self, cols: Vec<&'a str>) -> &'a mut QueryBuilder<'a> {
-        self.group_by = Some(cols);
+    pub fn group_by(&mut self, cols: Vec<&'a str>) -> &mut QueryBuilder<'a> {
+        self.group_by = Some(cols.into_iter().map(Term::Atom).collect());
+        self
+    }
+    /// Sets the GROUP BY clause to expressions, so a bucketing expression can be grouped by
+    /// exactly as it is selected
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let day = func("DATE_TRUNC", vec![Term::Atom("'day'"), Term::Atom("ts")]);
+    /// let query = Q()
+    ///     .select_expressions(vec![
+    ///         SelectExpression::Expr(day.clone(), Some("day")),
+    ///         SelectExpression::Column("count(*)"),
+    ///     ])
+    ///     .from("events")
+    ///     .group_by_exprs(vec![day])
+    ///     .build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT DATE_TRUNC('day', ts) AS day, count(*) FROM events GROUP BY DATE_TRUNC('day', ts)"
+    /// );
+    /// ```
+    pub fn group_by_exprs(&mut self, exprs: Vec<Term<'a>>) -> &mut QueryBuilder<'a> {
+        self.group_by = Some(exprs);
         self
     }
     /// Sets the HAVING clause
@@ -1370,10 +5602,59 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["category", "count(*)"]).from("products").group_by(vec!["category"]).having(gt("count(*)", "5")).build();
     /// assert_eq!(query.sql(), "SELECT category, count(*) FROM products GROUP BY category HAVING count(*) > 5");
     /// ```
-    pub fn having(&'a mut self, term: Term<'a>) -> &'a mut QueryBuilder<'a> {
+    pub fn having(&mut self, term: Term<'a>) -> &mut QueryBuilder<'a> {
         self.having = Some(Having::new(term));
         self
     }
+    /// Sets the HAVING clause from a borrowed term, cloning it, so a predicate kept in a
+    /// variable can also be passed to where_() or reused in another query
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let positive = gt("amount", "0");
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["amount", "count(*)"])
+    ///     .from("payments")
+    ///     .where_(positive.clone())
+    ///     .group_by(vec!["amount"])
+    ///     .having_term(&positive)
+    ///     .build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT amount, count(*) FROM payments WHERE amount > 0 GROUP BY amount HAVING amount > 0"
+    /// );
+    /// ```
+    pub fn having_term(&mut self, term: &Term<'a>) -> &mut QueryBuilder<'a> {
+        self.having = Some(Having::new(term.clone()));
+        self
+    }
+    /// Adds a named window definition to the WINDOW clause, which window functions can then
+    /// reference with over_named().
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let spec = WindowSpec {
+    ///     partition_by: vec!["dept"],
+    ///     order_by: Some(OrderBy { columns: vec![OrderedColumn::Desc("salary")] }),
+    ///     frame: None,
+    /// };
+    /// let mut qb = Q();
+    /// let query = qb.select_expressions(vec![
+    ///         SelectExpression::Column("name"),
+    ///         SelectExpression::Expr(over_named(Term::Atom("rank()"), "w"), Some("r")),
+    ///         SelectExpression::Expr(over_named(Term::Atom("sum(salary)"), "w"), None),
+    ///     ])
+    ///     .from("employees")
+    ///     .window("w", spec)
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT name, rank() OVER w AS r, sum(salary) OVER w FROM employees WINDOW w AS (PARTITION BY dept ORDER BY salary DESC)");
+    /// ```
+    pub fn window(&mut self, name: &'a str, spec: WindowSpec<'a>) -> &mut QueryBuilder<'a> {
+        self.window_defs.push((name, spec));
+        self
+    }
     /// Sets the ORDER BY clause
     ///
     /// # Example
@@ -1383,10 +5664,77 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["*"]).from("users").order_by(vec![OrderedColumn::Desc("created_at")]).build();
     /// assert_eq!(query.sql(), "SELECT * FROM users ORDER BY created_at DESC");
     /// ```
-    pub fn order_by(&'a mut self, cols: Vec<OrderedColumn<'a>>) -> &'a mut QueryBuilder<'a> {
+    pub fn order_by(&mut self, cols: Vec<OrderedColumn<'a>>) -> &mut QueryBuilder<'a> {
         self.order_by = Some(OrderBy { columns: cols });
         self
     }
+    /// Pages through the results by key (keyset or "cursor" pagination) rather than OFFSET.
+    ///
+    /// Orders by `keys` and limits the result to `page_size` rows. For every page but the first,
+    /// pass the keys of the last row seen as `after`: they are bound as parameters and a
+    /// condition selecting only the rows that sort after them is ANDed onto the WHERE clause.
+    ///
+    /// A single key renders `key > $n` (`<` for a descending key). Composite keys that all sort
+    /// the same way use a row comparison, `(a, b) > ($1, $2)`; keys with mixed directions are
+    /// expanded to `(a > $1 OR (a = $2 AND b < $3))`, binding a value again for each place it is
+    /// compared, so the placeholders stay valid in either placeholder style.
+    ///
+    /// Returns [`BuildError::EmptyKeyset`] if `keys` is empty, and [`BuildError::KeysetArity`] if
+    /// `after` does not hold exactly one value per key.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let (sql, values) = qb.select(vec!["*"])
+    ///     .from("users")
+    ///     .keyset_paginate(vec![OrderedColumn::Asc("id")], Some(vec![Value::Int(100)]), 20)?
+    ///     .build()
+    ///     .render()?;
+    /// assert_eq!(sql, "SELECT * FROM users WHERE id > $1 ORDER BY id ASC LIMIT 20");
+    /// assert_eq!(values, vec![Value::Int(100)]);
+    /// # Ok::<(), BuildError>(())
+    /// ```
+    pub fn keyset_paginate(
+        &mut self,
+        keys: Vec<OrderedColumn<'a>>,
+        after: Option<Vec<Value>>,
+        page_size: u64,
+    ) -> Result<&mut QueryBuilder<'a>, BuildError> {
+        if keys.is_empty() {
+            return Err(BuildError::EmptyKeyset);
+        }
+        if let Some(values) = after {
+            if keys.len() != values.len() {
+                return Err(BuildError::KeysetArity {
+                    keys: keys.len(),
+                    values: values.len(),
+                });
+            }
+            let bound: Vec<(&'a str, Op<'a>, Value)> = keys
+                .iter()
+                .zip(values)
+                .map(|(key, value)| {
+                    if key.is_desc() {
+                        (key.column(), Op::LessThan, value)
+                    } else {
+                        (key.column(), Op::GreaterThan, value)
+                    }
+                })
+                .collect();
+            if let Some(condition) = keyset_condition(bound, |value| self.bind(value)) {
+                self.where_clause = Some(match self.where_clause.take() {
+                    None => condition,
+                    Some(existing) => {
+                        Term::Condition(Box::new(existing), Op::And, Box::new(condition))
+                    }
+                });
+            }
+        }
+        self.order_by = Some(OrderBy { columns: keys });
+        self.limit = Some(page_size);
+        Ok(self)
+    }
     /// Sets the LIMIT clause
     ///
     /// # Example
@@ -1396,7 +5744,7 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["*"]).from("users").limit(10).build();
     /// assert_eq!(query.sql(), "SELECT * FROM users LIMIT 10");
     /// ```
-    pub fn limit(&'a mut self, limit: u64) -> &'a mut QueryBuilder<'a> {
+    pub fn limit(&mut self, limit: u64) -> &mut QueryBuilder<'a> {
         self.limit = Some(limit);
         self
     }
@@ -1409,10 +5757,29 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["*"]).from("users").offset(20).build();
     /// assert_eq!(query.sql(), "SELECT * FROM users OFFSET 20");
     /// ```
-    pub fn offset(&'a mut self, offset: u64) -> &'a mut QueryBuilder<'a> {
+    pub fn offset(&mut self, offset: u64) -> &mut QueryBuilder<'a> {
         self.offset = Some(offset);
         self
     }
+    /// Sets the FETCH FIRST clause, optionally keeping rows tied with the last one.
+    /// Takes precedence over limit() if both are set.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.select(vec!["*"])
+    ///     .from("scores")
+    ///     .order_by(vec![OrderedColumn::Desc("points")])
+    ///     .offset(10)
+    ///     .fetch_first(5, true)
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT * FROM scores ORDER BY points DESC OFFSET 10 ROWS FETCH FIRST 5 ROWS WITH TIES");
+    /// ```
+    pub fn fetch_first(&mut self, count: u64, with_ties: bool) -> &mut QueryBuilder<'a> {
+        self.fetch_first = Some(FetchFirst { count, with_ties });
+        self
+    }
     /// Adds FOR UPDATE to lock selected rows
     ///
     /// # Example
@@ -1422,15 +5789,180 @@ impl<'a> QueryBuilder<'a> {
     /// let query = qb.select(vec!["*"]).from("users").for_update().build();
     /// assert_eq!(query.sql(), "SELECT * FROM users FOR UPDATE");
     /// ```
-    pub fn for_update(&'a mut self) -> &'a mut QueryBuilder<'a> {
-        self.for_update = true;
+    pub fn for_update(&mut self) -> &mut QueryBuilder<'a> {
+        self.locks.push(LockClause::new(LockStrength::Update));
+        self
+    }
+    /// Adds a locking clause. Clauses render in the order they are added, so different
+    /// joined tables can be locked with different strengths.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb
+    ///     .select(vec!["*"])
+    ///     .from("orders")
+    ///     .inner_join("users", eq("orders.user_id", "users.id"))
+    ///     .lock(LockClause { strength: LockStrength::Update, of: vec!["orders"], wait: LockWait::Wait })
+    ///     .lock(LockClause { strength: LockStrength::Share, of: vec!["users"], wait: LockWait::SkipLocked })
+    ///     .build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT * FROM orders INNER JOIN users ON orders.user_id = users.id FOR UPDATE OF orders FOR SHARE OF users SKIP LOCKED"
+    /// );
+    /// ```
+    pub fn lock(&mut self, clause: LockClause<'a>) -> &mut QueryBuilder<'a> {
+        self.locks.push(clause);
+        self
+    }
+    /// Adds FOR UPDATE OF the given tables, locking only rows from those tables
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb
+    ///     .select(vec!["*"])
+    ///     .from("orders")
+    ///     .inner_join("users", eq("orders.user_id", "users.id"))
+    ///     .for_update_of(vec!["orders"])
+    ///     .nowait()
+    ///     .build();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT * FROM orders INNER JOIN users ON orders.user_id = users.id FOR UPDATE OF orders NOWAIT"
+    /// );
+    /// ```
+    pub fn for_update_of(&mut self, tables: Vec<&'a str>) -> &mut QueryBuilder<'a> {
+        let mut clause = LockClause::new(LockStrength::Update);
+        clause.of = tables;
+        self.lock(clause)
+    }
+    /// Adds FOR NO KEY UPDATE, which does not block inserts referencing the locked rows
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb
+    ///     .select(vec!["*"])
+    ///     .from("users")
+    ///     .for_no_key_update()
+    ///     .lock_of(vec!["users"])
+    ///     .skip_locked()
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT * FROM users FOR NO KEY UPDATE OF users SKIP LOCKED");
+    /// ```
+    pub fn for_no_key_update(&mut self) -> &mut QueryBuilder<'a> {
+        self.lock(LockClause::new(LockStrength::NoKeyUpdate))
+    }
+    /// Adds FOR SHARE
+    pub fn for_share(&mut self) -> &mut QueryBuilder<'a> {
+        self.lock(LockClause::new(LockStrength::Share))
+    }
+    /// Adds FOR KEY SHARE
+    pub fn for_key_share(&mut self) -> &mut QueryBuilder<'a> {
+        self.lock(LockClause::new(LockStrength::KeyShare))
+    }
+    /// Restricts the most recently added locking clause to the given tables (`OF ...`).
+    /// Does nothing when no locking clause has been added.
+    pub fn lock_of(&mut self, tables: Vec<&'a str>) -> &mut QueryBuilder<'a> {
+        if let Some(clause) = self.locks.last_mut() {
+            clause.of = tables;
+        }
+        self
+    }
+    /// Makes the most recently added locking clause skip rows that are already locked.
+    /// Does nothing when no locking clause has been added.
+    pub fn skip_locked(&mut self) -> &mut QueryBuilder<'a> {
+        if let Some(clause) = self.locks.last_mut() {
+            clause.wait = LockWait::SkipLocked;
+        }
+        self
+    }
+    /// Makes the most recently added locking clause fail instead of waiting for locked rows.
+    /// Does nothing when no locking clause has been added.
+    pub fn nowait(&mut self) -> &mut QueryBuilder<'a> {
+        if let Some(clause) = self.locks.last_mut() {
+            clause.wait = LockWait::NoWait;
+        }
+        self
+    }
+}
+
+impl<'a> QueryBuilder<'a> {
+    /// Tags the statement with a leading `/* ... */` comment, e.g. for attributing queries in
+    /// pg_stat_statements. Any comment delimiters in the text are broken up on render.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let query = qb.comment("service=billing,route=/invoices")
+    ///     .select(vec!["*"])
+    ///     .from("invoices")
+    ///     .build();
+    /// assert_eq!(query.sql(), "/* service=billing,route=/invoices */ SELECT * FROM invoices");
+    /// ```
+    pub fn comment(&mut self, comment: &'a str) -> &mut QueryBuilder<'a> {
+        self.comment = Some(comment);
+        self
+    }
+
+    /// Sets the style placeholders are rendered in. Placeholders are numbered internally and
+    /// only converted when the statement is rendered, so it can be called at any point, and
+    /// render() orders the values by where the `?` placeholders appear.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// qb.param_style(ParamStyle::Question);
+    /// let id = qb.param();
+    /// let query = qb.select(vec!["*"]).from("users").where_(eq("id", &id)).build();
+    /// assert_eq!(query.sql(), "SELECT * FROM users WHERE id = ?");
+    /// ```
+    pub fn param_style(&mut self, style: ParamStyle) -> &mut QueryBuilder<'a> {
+        self.params.set_style(style);
         self
     }
+
+    /// Returns the next placeholder and records `value` as the value bound to it.
+    /// The recorded values are returned in placeholder order by the built query's render().
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let id = qb.bind(7);
+    /// let query = qb.select(vec!["*"]).from("users").where_(eq("id", &id)).build();
+    /// assert_eq!(query.render(), Ok(("SELECT * FROM users WHERE id = $1".to_string(), vec![Value::Int(7)])));
+    /// ```
+    pub fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.params.slot(Some(value.into()))
+    }
+
+    /// Like bind(), but returns the placeholder cast to `type_name`, as in `$1::uuid`, for
+    /// parameters whose type PostgreSQL cannot infer from context.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let id = qb.bind_as("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f", "uuid");
+    /// let (sql, values) = qb.select(vec!["*"]).from("users").where_(eq("id", &id)).build().render().unwrap();
+    /// assert_eq!(sql, "SELECT * FROM users WHERE id = $1::uuid");
+    /// assert_eq!(values.len(), 1);
+    /// ```
+    pub fn bind_as(&mut self, value: impl Into<Value>, type_name: &str) -> String {
+        format!("{}::{}", self.bind(value), type_name)
+    }
 }
 
 impl<'a> Parameterized for QueryBuilder<'a> {
     fn param(&mut self) -> String {
-        self.params.seq()
+        self.params.slot(None)
     }
 }
 
@@ -1438,19 +5970,12 @@ impl<'a> Sql for Query<'a> {
     fn sql(&self) -> String {
         let mut result = String::new();
 
-        if let Some(ctes) = &self.with_clause {
-            result.push_str("WITH ");
-            let mut first = true;
-            for cte in ctes {
-                if !first {
-                    result.push_str(", ");
-                }
-                first = false;
-                result.push_str(&cte.sql());
-            }
-            result.push(' ');
+        if let Some(comment) = self.comment {
+            result.push_str(&format!("/* {} */ ", sanitize_comment(comment)));
         }
 
+        push_with_clause(&mut result, &self.with_clause);
+
         if let Some(select) = &self.select {
             result.push_str(&format!("SELECT {}", select.sql()));
         }
@@ -1464,23 +5989,454 @@ impl<'a> Sql for Query<'a> {
             result.push_str(&format!(" WHERE {}", conditions.sql()));
         }
         if let Some(group_by) = &self.group_by {
-            result.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+            let exprs: Vec<String> = group_by.iter().map(|t| t.sql()).collect();
+            result.push_str(&format!(" GROUP BY {}", exprs.join(", ")));
         }
         if let Some(having) = &self.having {
             result.push_str(&format!(" HAVING {}", having.sql()));
         }
+        if !self.window_defs.is_empty() {
+            let defs: Vec<String> = self
+                .window_defs
+                .iter()
+                .map(|(name, spec)| format!("{} AS ({})", name, spec.sql()))
+                .collect();
+            result.push_str(&format!(" WINDOW {}", defs.join(", ")));
+        }
         if let Some(order_by) = &self.order_by {
-            result.push_str(&format!(" {}", order_by.sql()));
+            result.push_str(&format!(" ORDER BY {}", order_by.columns_sql()));
+        }
+        if let Some(fetch) = &self.fetch_first {
+            if let Some(offset) = &self.offset {
+                result.push_str(&format!(" OFFSET {} ROWS", offset));
+            }
+            result.push_str(&format!(" {}", fetch.sql()));
+        } else {
+            if let Some(limit) = &self.limit {
+                result.push_str(&format!(" LIMIT {}", limit));
+            }
+            if let Some(offset) = &self.offset {
+                result.push_str(&format!(" OFFSET {}", offset));
+            }
+        }
+        for lock in &self.locks {
+            result.push_str(&format!(" {}", lock.sql()));
         }
-        if let Some(limit) = &self.limit {
-            result.push_str(&format!(" LIMIT {}", limit));
+        self.params.finish(result)
+    }
+}
+
+impl<'a> Query<'a> {
+    /// Turns this query into a named CTE, for QueryBuilder::with_cte()
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let cte = Q().select(vec!["id"]).from("users").build().as_cte("u");
+    /// assert_eq!(cte.sql(), "u AS (SELECT id FROM users)");
+    /// ```
+    pub fn as_cte(self, name: &'a str) -> Cte<'a> {
+        Cte {
+            name,
+            body: CteBody::Select(Box::new(self)),
+            materialized: None,
         }
-        if let Some(offset) = &self.offset {
-            result.push_str(&format!(" OFFSET {}", offset));
+    }
+
+    /// Checks that the `$n` placeholders in the rendered SQL form a contiguous `$1..=$max`
+    /// sequence with no gaps or repeats. Mixing `p(n)` with a builder's `param()` calls can
+    /// easily produce `$1, $3` with no `$2`, which PostgreSQL rejects at execution time.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let p1 = qb.param();
+    /// let p3 = p(3);
+    /// let query = qb.select(vec!["*"])
+    ///     .from("users")
+    ///     .where_(and(eq("id", &p1), eq("status", &p3)))
+    ///     .build();
+    /// assert_eq!(
+    ///     query.validate_params(),
+    ///     Err(BuildError::InvalidParams { missing: vec![2], duplicates: vec![] })
+    /// );
+    /// ```
+    pub fn validate_params(&self) -> Result<(), BuildError> {
+        validate_placeholders(&self.sql())
+    }
+
+    /// Renders the SQL and returns it together with the values bound with bind().
+    ///
+    /// The values are ordered by placeholder number, ready to hand to the driver, and include
+    /// those bound in subqueries and CTE bodies. Placeholders written by hand with `p(n)` carry
+    /// no value; a query mixing param() and bind() is rejected, since its values would not
+    /// line up with the placeholders.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let id = qb.bind(7);
+    /// let status = qb.bind("active");
+    /// let (sql, values) = qb.select(vec!["*"])
+    ///     .from("users")
+    ///     .where_(and(eq("id", &id), eq("status", &status)))
+    ///     .build()
+    ///     .render()?;
+    /// assert_eq!(sql, "SELECT * FROM users WHERE id = $1 AND status = $2");
+    /// assert_eq!(values, vec![Value::Int(7), Value::Text("active".to_string())]);
+    /// # Ok::<(), BuildError>(())
+    /// ```
+    pub fn render(mut self) -> Result<(String, Vec<Value>), BuildError> {
+        let params = std::mem::take(&mut self.params);
+        params.render(self.sql())
+    }
+
+    /// Hands the params of every nested statement to this query, in the order they are
+    /// rendered, so their placeholders follow this query's own.
+    fn adopt_nested_params(&mut self) {
+        let mut params = std::mem::take(&mut self.params);
+        for cte in self.with_clause.iter_mut().flatten() {
+            params.adopt_cte(&mut cte.body);
         }
-        if self.for_update {
-            result.push_str(" FOR UPDATE");
+        if let Some(select) = &mut self.select {
+            params.adopt_columns(&mut select.cols);
+        }
+        if let Some(from) = &mut self.from {
+            params.adopt_source(from);
+        }
+        for join in &mut self.joins {
+            params.adopt_source(&mut join.source);
+            if let Some(on) = &mut join.on {
+                params.adopt_term(on);
+            }
+        }
+        if let Some(where_clause) = &mut self.where_clause {
+            params.adopt_term(where_clause);
+        }
+        for term in self.group_by.iter_mut().flatten() {
+            params.adopt_term(term);
+        }
+        if let Some(having) = &mut self.having {
+            params.adopt_term(&mut having.term);
+        }
+        self.params = params;
+    }
+
+    /// Prefixes bare column references in the SELECT list, WHERE, GROUP BY and HAVING clauses
+    /// with `default_table`, so the query stays unambiguous once it is joined against other
+    /// tables.
+    ///
+    /// This is a heuristic: only plain identifiers are touched. Anything already qualified,
+    /// function calls, literals, placeholders, keywords such as `NULL` and subqueries are left
+    /// alone, so running it twice is the same as running it once. A GROUP BY name that is a
+    /// select-list alias is kept as is. ORDER BY and JOIN conditions are not rewritten: ORDER BY
+    /// names resolve against the output columns first, so qualifying them would break ordering
+    /// by an alias, and a JOIN condition usually refers to more than one table.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let mut query = qb
+    ///     .select(vec!["id", "u.name", "count(*)"])
+    ///     .from("users u")
+    ///     .where_(and(eq("status", "'active'"), gt("age", "18")))
+    ///     .group_by(vec!["id", "u.name"])
+    ///     .having(gt("max(score)", "10"))
+    ///     .build();
+    /// query.qualify_columns("u");
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "SELECT u.id, u.name, count(*) FROM users u WHERE u.status = 'active' AND u.age > 18 \
+    ///      GROUP BY u.id, u.name HAVING max(score) > 10"
+    /// );
+    /// ```
+    pub fn qualify_columns(&mut self, default_table: &str) {
+        let mut aliases: Vec<&str> = Vec::new();
+        if let Some(select) = &mut self.select {
+            if let Columns::Expressions(exprs) = &select.cols {
+                aliases.extend(exprs.iter().filter_map(|e| match e {
+                    SelectExpression::Expr(_, alias) | SelectExpression::Subquery(_, alias) => {
+                        *alias
+                    }
+                    _ => None,
+                }));
+            }
+            qualify_select_columns(&mut select.cols, default_table);
+        }
+        if let Some(term) = &mut self.where_clause {
+            qualify_term(term, default_table);
+        }
+        for term in self.group_by.iter_mut().flatten() {
+            if !matches!(term, Term::Atom(name) if aliases.contains(name)) {
+                qualify_term(term, default_table);
+            }
+        }
+        if let Some(having) = &mut self.having {
+            qualify_term(&mut having.term, default_table);
+        }
+    }
+}
+
+/// Builds the "sorts after the last-seen row" condition for keyset_paginate() from
+/// (key, comparison, last-seen value) triples, or None when there are no keys. `bind` is called
+/// once per comparison, in render order, and returns the placeholder to use.
+fn keyset_condition<'a>(
+    keys: Vec<(&'a str, Op<'a>, Value)>,
+    mut bind: impl FnMut(Value) -> String,
+) -> Option<Term<'a>> {
+    let mut placeholder = |value: &Value| Term::Raw(Cow::Owned(bind(value.clone())));
+    match keys.as_slice() {
+        [] => return None,
+        [(key, op, value)] => {
+            return Some(Term::Condition(
+                Box::new(Term::Atom(key)),
+                op.clone(),
+                Box::new(placeholder(value)),
+            ));
+        }
+        [(_, op, _), rest @ ..] if rest.iter().all(|(_, o, _)| o == op) => {
+            let (columns, placeholders): (Vec<_>, Vec<_>) = keys
+                .iter()
+                .map(|(k, _, v)| (Term::Atom(k), placeholder(v)))
+                .unzip();
+            return Some(Term::Condition(
+                Box::new(Term::Row(columns)),
+                op.clone(),
+                Box::new(Term::Row(placeholders)),
+            ));
+        }
+        _ => {}
+    }
+    // Mixed directions: the row sorts later on its first differing key.
+    let mut alternatives: Vec<Term<'a>> = vec![];
+    for i in 0..keys.len() {
+        let branch = keys
+            .iter()
+            .take(i + 1)
+            .enumerate()
+            .map(|(j, (key, op, value))| {
+                let op = if j < i { Op::Equals } else { op.clone() };
+                Term::Condition(Box::new(Term::Atom(key)), op, Box::new(placeholder(value)))
+            })
+            .reduce(|b, cmp| Term::Condition(Box::new(b), Op::And, Box::new(cmp)))?;
+        alternatives.push(if i > 0 {
+            Term::Parens(Box::new(branch))
+        } else {
+            branch
+        });
+    }
+    alternatives
+        .into_iter()
+        .reduce(|a, branch| Term::Condition(Box::new(a), Op::Or, Box::new(branch)))
+        .map(|alternatives| Term::Parens(Box::new(alternatives)))
+}
+
+/// Breaks up comment delimiters so the text cannot end the comment it is placed in. Both are
+/// handled because PostgreSQL block comments nest.
+fn sanitize_comment(comment: &str) -> String {
+    comment.replace("*/", "* /").replace("/*", "/ *")
+}
+
+/// Whether the ORDER BY expression `col` is produced by the select list `cols`.
+fn is_selected(col: &str, cols: &Columns) -> bool {
+    let matches_item = |item: &str| {
+        if item == col {
+            return true;
+        }
+        let lower = item.to_ascii_lowercase();
+        lower
+            .rfind(" as ")
+            .is_some_and(|i| item[i + 4..].trim() == col)
+    };
+    match cols {
+        Columns::Star => true,
+        Columns::Selected(items) => items.iter().any(|item| matches_item(item)),
+        Columns::Expressions(exprs) => exprs.iter().any(|e| match e {
+            SelectExpression::Star => true,
+            SelectExpression::TableStar(table) => {
+                col.split_once('.').is_none_or(|(t, _)| t == *table)
+            }
+            SelectExpression::Column(item) => matches_item(item),
+            SelectExpression::Expr(_, Some(alias)) | SelectExpression::Subquery(_, Some(alias)) => {
+                *alias == col
+            }
+            SelectExpression::Expr(term, None) => term.sql() == col,
+            SelectExpression::Subquery(_, None) => false,
+        }),
+    }
+}
+
+/// Aggregate functions recognized by validate_having(): PostgreSQL's general-purpose,
+/// statistical, ordered-set and hypothetical-set aggregates.
+const AGGREGATES: &[&str] = &[
+    "any_value",
+    "array_agg",
+    "avg",
+    "bit_and",
+    "bit_or",
+    "bit_xor",
+    "bool_and",
+    "bool_or",
+    "count",
+    "every",
+    "json_agg",
+    "json_agg_strict",
+    "json_arrayagg",
+    "json_objectagg",
+    "json_object_agg",
+    "json_object_agg_strict",
+    "json_object_agg_unique",
+    "json_object_agg_unique_strict",
+    "jsonb_agg",
+    "jsonb_agg_strict",
+    "jsonb_object_agg",
+    "jsonb_object_agg_strict",
+    "jsonb_object_agg_unique",
+    "jsonb_object_agg_unique_strict",
+    "max",
+    "min",
+    "range_agg",
+    "range_intersect_agg",
+    "string_agg",
+    "sum",
+    "xmlagg",
+    "corr",
+    "covar_pop",
+    "covar_samp",
+    "regr_avgx",
+    "regr_avgy",
+    "regr_count",
+    "regr_intercept",
+    "regr_r2",
+    "regr_slope",
+    "regr_sxx",
+    "regr_sxy",
+    "regr_syy",
+    "stddev",
+    "stddev_pop",
+    "stddev_samp",
+    "variance",
+    "var_pop",
+    "var_samp",
+    "mode",
+    "percentile_cont",
+    "percentile_disc",
+    "rank",
+    "dense_rank",
+    "percent_rank",
+    "cume_dist",
+];
+
+/// Whether `s` is a column reference, bare or qualified as `table.column`.
+fn is_column_reference(s: &str) -> bool {
+    s.split('.').all(is_bare_column)
+}
+
+/// Whether the column reference `column` matches an entry of GROUP BY. An unqualified name
+/// on either side matches the other's column part.
+fn is_grouped(column: &str, grouped: &[String]) -> bool {
+    let last = |s: &str| s.rsplit('.').next().unwrap_or(s).to_string();
+    grouped.iter().any(|g| {
+        *g == column || ((!g.contains('.') || !column.contains('.')) && last(g) == last(column))
+    })
+}
+
+/// Collects the column references in `term` that are not inside an aggregate call.
+fn ungrouped_columns<'t>(term: &'t Term, out: &mut Vec<&'t str>) {
+    walk_terms(term, |t| match t {
+        Term::Atom(s) => {
+            if is_column_reference(s) {
+                out.push(s);
+            }
+            false
+        }
+        Term::Aggregate(..) | Term::Over(..) | Term::WithinGroup(..) => false,
+        Term::Function(name, _) => !AGGREGATES.iter().any(|a| a.eq_ignore_ascii_case(name)),
+        _ => true,
+    });
+}
+
+/// Returns true for a plain, unqualified identifier that is not a value keyword.
+fn is_bare_column(s: &str) -> bool {
+    const KEYWORDS: [&str; 15] = [
+        "null",
+        "true",
+        "false",
+        "default",
+        "current_date",
+        "current_time",
+        "current_timestamp",
+        "localtime",
+        "localtimestamp",
+        "current_user",
+        "current_role",
+        "current_schema",
+        "current_catalog",
+        "session_user",
+        "user",
+    ];
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(s))
+}
+
+/// Returns the qualified form of `s`, or None when it should be left as is.
+fn qualified(s: &str, table: &str) -> Option<String> {
+    if is_bare_column(s) {
+        Some(format!("{}.{}", table, s))
+    } else {
+        None
+    }
+}
+
+fn qualify_select_columns(cols: &mut Columns, table: &str) {
+    match cols {
+        Columns::Star => {}
+        Columns::Selected(names) => {
+            if names.iter().any(|n| is_bare_column(n)) {
+                let exprs = names
+                    .iter()
+                    .map(|n| match qualified(n, table) {
+                        Some(q) => SelectExpression::Expr(Term::Raw(Cow::Owned(q)), None),
+                        None => SelectExpression::Column(n),
+                    })
+                    .collect();
+                *cols = Columns::Expressions(exprs);
+            }
+        }
+        Columns::Expressions(exprs) => {
+            for e in exprs.iter_mut() {
+                match e {
+                    SelectExpression::Column(n) => {
+                        if let Some(q) = qualified(n, table) {
+                            *e = SelectExpression::Expr(Term::Raw(Cow::Owned(q)), None);
+                        }
+                    }
+                    SelectExpression::Expr(term, _) => qualify_term(term, table),
+                    SelectExpression::Subquery(..)
+                    | SelectExpression::Star
+                    | SelectExpression::TableStar(_) => {}
+                }
+            }
         }
-        result
     }
 }
+
+/// Qualifies the bare column atoms in a term. Subqueries have their own scope and are skipped.
+fn qualify_term(term: &mut Term, table: &str) {
+    walk_terms_mut(term, |t| {
+        if let Term::Atom(s) = t
+            && let Some(q) = qualified(s, table)
+        {
+            *t = Term::Raw(Cow::Owned(q));
+        }
+        true
+    });
+}
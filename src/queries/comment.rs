@@ -0,0 +1,63 @@
+use crate::{Sql, text};
+
+/// The object a COMMENT ON statement documents
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum CommentTarget<'a> {
+    /// `TABLE name`
+    Table(&'a str),
+    /// `COLUMN table.column`
+    Column(&'a str, &'a str),
+    /// `INDEX name`
+    Index(&'a str),
+    /// `VIEW name`
+    View(&'a str),
+    /// `SCHEMA name`
+    Schema(&'a str),
+    /// `CONSTRAINT name ON table`
+    Constraint(&'a str, &'a str),
+}
+
+impl<'a> Sql for CommentTarget<'a> {
+    fn sql(&self) -> String {
+        match self {
+            CommentTarget::Table(name) => format!("TABLE {}", name),
+            CommentTarget::Column(table, column) => format!("COLUMN {}.{}", table, column),
+            CommentTarget::Index(name) => format!("INDEX {}", name),
+            CommentTarget::View(name) => format!("VIEW {}", name),
+            CommentTarget::Schema(name) => format!("SCHEMA {}", name),
+            CommentTarget::Constraint(name, table) => {
+                format!("CONSTRAINT {} ON {}", name, table)
+            }
+        }
+    }
+}
+
+/// Comment is a COMMENT ON statement, which stores documentation for a database object.
+/// The text is quoted with text(); `None` renders `IS NULL`, which removes the comment.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let comment = Comment {
+///     target: CommentTarget::Column("users", "email"),
+///     text: Some("primary contact"),
+/// };
+/// assert_eq!(comment.sql(), "COMMENT ON COLUMN users.email IS 'primary contact'");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Comment<'a> {
+    /// The object being documented
+    pub target: CommentTarget<'a>,
+    /// The comment, or None to remove it
+    pub text: Option<&'a str>,
+}
+
+impl<'a> Sql for Comment<'a> {
+    fn sql(&self) -> String {
+        let text = match self.text {
+            Some(t) => text(t),
+            None => "NULL".to_string(),
+        };
+        format!("COMMENT ON {} IS {}", self.target.sql(), text)
+    }
+}
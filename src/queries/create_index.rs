@@ -0,0 +1,92 @@
+use crate::{OrderedColumn, Sql};
+
+/// CreateIndex is a CREATE INDEX statement.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let index = CreateIndex::new("users", vec!["email"])
+///     .name("users_email_key")
+///     .unique()
+///     .nulls_not_distinct();
+/// assert_eq!(
+///     index.sql(),
+///     "CREATE UNIQUE INDEX users_email_key ON users (email) NULLS NOT DISTINCT"
+/// );
+/// ```
+///
+/// Each column can carry its own sort direction and NULL placement, to back queries that order
+/// the same way:
+/// ```
+/// use squeal::*;
+/// let index = CreateIndex::new(
+///     "events",
+///     vec![OrderedColumn::Desc("created_at").nulls_last(), OrderedColumn::Asc("id")],
+/// );
+/// assert_eq!(index.sql(), "CREATE INDEX ON events (created_at DESC NULLS LAST, id ASC)");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CreateIndex<'a> {
+    /// The index name; PostgreSQL generates one when None
+    pub name: Option<&'a str>,
+    /// The indexed table
+    pub table: &'a str,
+    /// The indexed columns or expressions, each with an optional sort order
+    pub columns: Vec<OrderedColumn<'a>>,
+    /// CREATE UNIQUE INDEX
+    pub unique: bool,
+    /// NULLS NOT DISTINCT (PostgreSQL 15+), so a unique index allows only one NULL key
+    pub nulls_not_distinct: bool,
+}
+
+impl<'a> CreateIndex<'a> {
+    /// Creates an unnamed, non-unique index on the given columns; plain names keep the default
+    /// sort order
+    pub fn new(table: &'a str, columns: Vec<impl Into<OrderedColumn<'a>>>) -> CreateIndex<'a> {
+        CreateIndex {
+            name: None,
+            table,
+            columns: columns.into_iter().map(Into::into).collect(),
+            unique: false,
+            nulls_not_distinct: false,
+        }
+    }
+
+    /// Names the index
+    pub fn name(mut self, name: &'a str) -> CreateIndex<'a> {
+        self.name = Some(name);
+        self
+    }
+
+    /// Makes this a UNIQUE index
+    pub fn unique(mut self) -> CreateIndex<'a> {
+        self.unique = true;
+        self
+    }
+
+    /// Adds NULLS NOT DISTINCT, so NULL keys conflict with each other in a unique index
+    pub fn nulls_not_distinct(mut self) -> CreateIndex<'a> {
+        self.nulls_not_distinct = true;
+        self
+    }
+}
+
+impl<'a> Sql for CreateIndex<'a> {
+    fn sql(&self) -> String {
+        let mut result = "CREATE ".to_string();
+        if self.unique {
+            result.push_str("UNIQUE ");
+        }
+        result.push_str("INDEX ");
+        if let Some(name) = self.name {
+            result.push_str(name);
+            result.push(' ');
+        }
+        let columns: Vec<String> = self.columns.iter().map(|c| c.sql()).collect();
+        result.push_str(&format!("ON {} ({})", self.table, columns.join(", ")));
+        if self.nulls_not_distinct {
+            result.push_str(" NULLS NOT DISTINCT");
+        }
+        result
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{DropTable, Sql};
+use crate::{DropTable, Sql, Term};
 
 /// CreateTable is used to specify a create table query.
 pub struct CreateTable<'a> {
@@ -6,6 +6,8 @@ pub struct CreateTable<'a> {
     pub table: &'a str,
     /// The columns to create. Note that they must be syntactically correct.
     pub columns: Vec<String>,
+    /// Raw SQL appended after the column list, unescaped
+    pub suffix: Option<&'a str>,
 }
 
 impl<'a> Sql for CreateTable<'a> {
@@ -20,10 +22,240 @@ impl<'a> Sql for CreateTable<'a> {
             result.push_str(&c.to_string());
         }
         result.push(')');
+        if let Some(suffix) = self.suffix {
+            result.push(' ');
+            result.push_str(suffix);
+        }
         result
     }
 }
 
+/// The action taken on referencing rows when a referenced row is deleted or updated
+#[derive(Clone)]
+pub enum ForeignKeyAction {
+    /// Delete or update the referencing rows too
+    Cascade,
+    /// Reject the change, checked immediately
+    Restrict,
+    /// Set the referencing columns to NULL
+    SetNull,
+    /// Set the referencing columns to their defaults
+    SetDefault,
+    /// Reject the change, checked at the end of the statement (the PostgreSQL default)
+    NoAction,
+}
+
+impl Sql for ForeignKeyAction {
+    fn sql(&self) -> String {
+        match self {
+            ForeignKeyAction::Cascade => "CASCADE",
+            ForeignKeyAction::Restrict => "RESTRICT",
+            ForeignKeyAction::SetNull => "SET NULL",
+            ForeignKeyAction::SetDefault => "SET DEFAULT",
+            ForeignKeyAction::NoAction => "NO ACTION",
+        }
+        .to_string()
+    }
+}
+
+/// ForeignKey is the `REFERENCES` part of a foreign key, with optional referential actions.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let fk = ForeignKey {
+///     table: "users",
+///     columns: vec!["id"],
+///     on_delete: Some(ForeignKeyAction::Cascade),
+///     on_update: Some(ForeignKeyAction::SetNull),
+/// };
+/// assert_eq!(fk.sql(), "REFERENCES users(id) ON DELETE CASCADE ON UPDATE SET NULL");
+/// ```
+#[derive(Clone)]
+pub struct ForeignKey<'a> {
+    /// The referenced table
+    pub table: &'a str,
+    /// The referenced columns; empty to reference the primary key
+    pub columns: Vec<&'a str>,
+    /// The action taken when a referenced row is deleted
+    pub on_delete: Option<ForeignKeyAction>,
+    /// The action taken when a referenced row is updated
+    pub on_update: Option<ForeignKeyAction>,
+}
+
+impl<'a> Sql for ForeignKey<'a> {
+    fn sql(&self) -> String {
+        let mut result = format!("REFERENCES {}", self.table);
+        if !self.columns.is_empty() {
+            result.push_str(&format!("({})", self.columns.join(", ")));
+        }
+        if let Some(action) = &self.on_delete {
+            result.push_str(&format!(" ON DELETE {}", action.sql()));
+        }
+        if let Some(action) = &self.on_update {
+            result.push_str(&format!(" ON UPDATE {}", action.sql()));
+        }
+        result
+    }
+}
+
+/// A constraint attached to a single column in a ColumnDef
+#[derive(Clone)]
+pub enum ColumnConstraint<'a> {
+    /// NOT NULL
+    NotNull,
+    /// PRIMARY KEY
+    PrimaryKey,
+    /// UNIQUE
+    Unique,
+    /// UNIQUE NULLS NOT DISTINCT (PostgreSQL 15+): at most one row may hold NULL
+    UniqueNullsNotDistinct,
+    /// DEFAULT expr
+    Default(&'a str),
+    /// REFERENCES table(cols) with optional referential actions
+    References(ForeignKey<'a>),
+    /// GENERATED ALWAYS AS (expr) STORED
+    GeneratedStored(Term<'a>),
+    /// CHECK (expr)
+    Check(Term<'a>),
+}
+
+impl<'a> Sql for ColumnConstraint<'a> {
+    fn sql(&self) -> String {
+        match self {
+            ColumnConstraint::NotNull => "NOT NULL".to_string(),
+            ColumnConstraint::PrimaryKey => "PRIMARY KEY".to_string(),
+            ColumnConstraint::Unique => "UNIQUE".to_string(),
+            ColumnConstraint::UniqueNullsNotDistinct => "UNIQUE NULLS NOT DISTINCT".to_string(),
+            ColumnConstraint::Default(expr) => format!("DEFAULT {}", expr),
+            ColumnConstraint::References(fk) => fk.sql(),
+            ColumnConstraint::GeneratedStored(expr) => {
+                format!("GENERATED ALWAYS AS ({}) STORED", expr.sql())
+            }
+            ColumnConstraint::Check(expr) => format!("CHECK ({})", expr.sql()),
+        }
+    }
+}
+
+/// ColumnDef is a typed column definition for CREATE TABLE.
+/// Constraints render in the order given.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let col = ColumnDef {
+///     name: "user_id",
+///     data_type: "integer",
+///     constraints: vec![
+///         ColumnConstraint::NotNull,
+///         ColumnConstraint::References(ForeignKey {
+///             table: "users",
+///             columns: vec!["id"],
+///             on_delete: Some(ForeignKeyAction::Cascade),
+///             on_update: None,
+///         }),
+///     ],
+/// };
+/// assert_eq!(col.sql(), "user_id integer NOT NULL REFERENCES users(id) ON DELETE CASCADE");
+/// ```
+#[derive(Clone)]
+pub struct ColumnDef<'a> {
+    /// The column name
+    pub name: &'a str,
+    /// The column data type
+    pub data_type: &'a str,
+    /// The column constraints
+    pub constraints: Vec<ColumnConstraint<'a>>,
+}
+
+impl<'a> ColumnDef<'a> {
+    /// Creates a column definition without constraints
+    pub fn new(name: &'a str, data_type: &'a str) -> ColumnDef<'a> {
+        ColumnDef {
+            name,
+            data_type,
+            constraints: vec![],
+        }
+    }
+
+    /// Makes this a stored generated column computed from `expr`
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let price = Term::Condition(
+    ///     Box::new(Term::Atom("price")),
+    ///     Op::O("*"),
+    ///     Box::new(Term::Atom("1.2")),
+    /// );
+    /// let col = ColumnDef::new("price_with_tax", "numeric").generated_stored(price);
+    /// assert_eq!(col.sql(), "price_with_tax numeric GENERATED ALWAYS AS (price * 1.2) STORED");
+    /// ```
+    pub fn generated_stored(mut self, expr: Term<'a>) -> ColumnDef<'a> {
+        self.constraints
+            .push(ColumnConstraint::GeneratedStored(expr));
+        self
+    }
+
+    /// Adds a CHECK constraint on this column
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let col = ColumnDef::new("price", "numeric").check(gt("price", "0"));
+    /// assert_eq!(col.sql(), "price numeric CHECK (price > 0)");
+    /// ```
+    pub fn check(mut self, expr: Term<'a>) -> ColumnDef<'a> {
+        self.constraints.push(ColumnConstraint::Check(expr));
+        self
+    }
+}
+
+impl<'a> Sql for ColumnDef<'a> {
+    fn sql(&self) -> String {
+        let mut result = format!("{} {}", self.name, self.data_type);
+        for c in &self.constraints {
+            result.push(' ');
+            result.push_str(&c.sql());
+        }
+        result
+    }
+}
+
+/// A constraint declared at table level, spanning any number of columns
+#[derive(Clone)]
+pub enum TableConstraint<'a> {
+    /// CHECK (expr)
+    Check(Term<'a>),
+    /// UNIQUE [NULLS NOT DISTINCT] (columns). By default rows whose key contains a NULL never
+    /// conflict; with `nulls_not_distinct` (PostgreSQL 15+) NULLs compare equal.
+    Unique {
+        /// The columns that must be unique together
+        columns: Vec<&'a str>,
+        /// Treat NULLs as equal to each other
+        nulls_not_distinct: bool,
+    },
+}
+
+impl<'a> Sql for TableConstraint<'a> {
+    fn sql(&self) -> String {
+        match self {
+            TableConstraint::Check(expr) => format!("CHECK ({})", expr.sql()),
+            TableConstraint::Unique {
+                columns,
+                nulls_not_distinct,
+            } => {
+                let nulls = if *nulls_not_distinct {
+                    "NULLS NOT DISTINCT "
+                } else {
+                    ""
+                };
+                format!("UNIQUE {}({})", nulls, columns.join(", "))
+            }
+        }
+    }
+}
+
 /// The TableBuilder struct is a fluent interface for building a Table.
 /// Tables can be built into DROP or CREATE forms.
 pub struct TableBuilder<'a> {
@@ -31,6 +263,8 @@ pub struct TableBuilder<'a> {
     pub table: &'a str,
     /// Column definitions (each inner Vec represents one column definition)
     pub columns: Vec<Vec<String>>,
+    /// Raw SQL appended after the generated statement, unescaped
+    pub suffix: Option<&'a str>,
 }
 
 /// Defines a fluent interface for building a Table.
@@ -39,6 +273,7 @@ pub fn T<'a>(s: &'a str) -> TableBuilder<'a> {
     TableBuilder {
         table: s,
         columns: Vec::new(),
+        suffix: None,
     }
 }
 
@@ -60,6 +295,7 @@ impl<'a> TableBuilder<'a> {
         CreateTable {
             table: self.table,
             columns: table_cols,
+            suffix: self.suffix,
         }
     }
     /// Builds a DROP TABLE statement
@@ -110,4 +346,104 @@ impl<'a> TableBuilder<'a> {
         self.columns.push(str_cols);
         self
     }
+    /// Adds a typed column definition
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut tb = T("orders");
+    /// let create = tb.column("id", "serial", vec!["PRIMARY KEY"])
+    ///     .column_def(ColumnDef {
+    ///         name: "user_id",
+    ///         data_type: "integer",
+    ///         constraints: vec![ColumnConstraint::References(ForeignKey {
+    ///             table: "users",
+    ///             columns: vec!["id"],
+    ///             on_delete: Some(ForeignKeyAction::Cascade),
+    ///             on_update: Some(ForeignKeyAction::Restrict),
+    ///         })],
+    ///     })
+    ///     .build_create_table();
+    /// assert_eq!(create.sql(), "CREATE TABLE orders (id serial PRIMARY KEY, user_id integer REFERENCES users(id) ON DELETE CASCADE ON UPDATE RESTRICT)");
+    /// ```
+    pub fn column_def(&mut self, def: ColumnDef<'_>) -> &mut TableBuilder<'a> {
+        self.columns.push(vec![def.sql()]);
+        self
+    }
+    /// Adds a table constraint, rendered after the columns added before it
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut tb = T("events");
+    /// let create = tb.column("starts_at", "timestamptz", vec![])
+    ///     .column("ends_at", "timestamptz", vec![])
+    ///     .constraint(TableConstraint::Check(lt("starts_at", "ends_at")))
+    ///     .build_create_table();
+    /// assert_eq!(create.sql(), "CREATE TABLE events (starts_at timestamptz, ends_at timestamptz, CHECK (starts_at < ends_at))");
+    /// ```
+    pub fn constraint(&mut self, constraint: TableConstraint<'_>) -> &mut TableBuilder<'a> {
+        self.columns.push(vec![constraint.sql()]);
+        self
+    }
+    /// Adds a table-level CHECK constraint
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut tb = T("products");
+    /// let create = tb.column("price", "numeric", vec![])
+    ///     .check(gt("price", "0"))
+    ///     .build_create_table();
+    /// assert_eq!(create.sql(), "CREATE TABLE products (price numeric, CHECK (price > 0))");
+    /// ```
+    pub fn check(&mut self, expr: Term<'_>) -> &mut TableBuilder<'a> {
+        self.constraint(TableConstraint::Check(expr))
+    }
+    /// Adds a table-level UNIQUE constraint, optionally treating NULLs as equal
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut tb = T("accounts");
+    /// let create = tb.column("email", "text", vec![])
+    ///     .column("deleted_at", "timestamptz", vec![])
+    ///     .unique(vec!["email", "deleted_at"], true)
+    ///     .build_create_table();
+    /// assert_eq!(
+    ///     create.sql(),
+    ///     "CREATE TABLE accounts (email text, deleted_at timestamptz, UNIQUE NULLS NOT DISTINCT (email, deleted_at))"
+    /// );
+    /// ```
+    pub fn unique(
+        &mut self,
+        columns: Vec<&str>,
+        nulls_not_distinct: bool,
+    ) -> &mut TableBuilder<'a> {
+        self.constraint(TableConstraint::Unique {
+            columns,
+            nulls_not_distinct,
+        })
+    }
+    /// Appends raw SQL after the generated CREATE TABLE body, for options the builder does not
+    /// model, such as `TABLESPACE` or storage parameters.
+    ///
+    /// The text is NOT escaped or validated: never pass user input here.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut tb = T("events");
+    /// let create = tb.column("id", "bigint", vec![])
+    ///     .raw_suffix("WITH (fillfactor = 70) TABLESPACE fast_ssd")
+    ///     .build_create_table();
+    /// assert_eq!(
+    ///     create.sql(),
+    ///     "CREATE TABLE events (id bigint) WITH (fillfactor = 70) TABLESPACE fast_ssd"
+    /// );
+    /// ```
+    pub fn raw_suffix(&mut self, suffix: &'a str) -> &mut TableBuilder<'a> {
+        self.suffix = Some(suffix);
+        self
+    }
 }
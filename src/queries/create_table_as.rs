@@ -0,0 +1,48 @@
+use crate::{Query, Sql};
+
+/// CreateTableAs creates a table from the result of a query: `CREATE TABLE t AS SELECT ...`.
+///
+/// The columns and their types come from the query. With `with_data` false only the structure
+/// is created and no rows are copied.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let mut qb = Q();
+/// let query = qb.select(vec!["id", "total"]).from("orders").where_(eq("status", "'paid'")).build();
+/// let create = CreateTableAs {
+///     table: "paid_orders",
+///     query,
+///     temporary: false,
+///     with_data: false,
+/// };
+/// assert_eq!(
+///     create.sql(),
+///     "CREATE TABLE paid_orders AS SELECT id, total FROM orders WHERE status = 'paid' WITH NO DATA"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CreateTableAs<'a> {
+    /// The name of the table to create
+    pub table: &'a str,
+    /// The query whose result fills the table
+    pub query: Query<'a>,
+    /// Create a TEMPORARY table, dropped at the end of the session
+    pub temporary: bool,
+    /// Copy the query's rows into the table; when false, `WITH NO DATA` is rendered
+    pub with_data: bool,
+}
+
+impl<'a> Sql for CreateTableAs<'a> {
+    fn sql(&self) -> String {
+        let mut result = "CREATE ".to_string();
+        if self.temporary {
+            result.push_str("TEMPORARY ");
+        }
+        result.push_str(&format!("TABLE {} AS {}", self.table, self.query.sql()));
+        if !self.with_data {
+            result.push_str(" WITH NO DATA");
+        }
+        result
+    }
+}
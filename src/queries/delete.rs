@@ -1,4 +1,7 @@
-use crate::{Columns, Parameterized, PgParams, Sql, Term};
+use crate::{
+    BuildError, Columns, Cte, CteBody, FromSource, ParamStyle, Parameterized, Params, PgParams,
+    Sql, TargetTable, Term, Value, and, push_with_clause,
+};
 
 /// The Delete struct represents a DELETE statement
 ///
@@ -6,32 +9,67 @@ use crate::{Columns, Parameterized, PgParams, Sql, Term};
 /// ```
 /// use squeal::*;
 /// let delete = Delete {
-///     table: "users",
+///     with_clause: None,
+///     table: "users".into(),
+///     using: vec![],
 ///     where_clause: Some(eq("id", "123")),
 ///     returning: None,
+///     params: Params::default(),
 /// };
 /// assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 123");
 /// ```
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Delete<'a> {
-    /// The table name for the delete clause.
-    pub table: &'a str,
+    /// WITH clause (Common Table Expressions)
+    pub with_clause: Option<Vec<Cte<'a>>>,
+    /// The table to delete from.
+    pub table: TargetTable<'a>,
+    /// Tables whose columns may appear in the WHERE condition, rendered comma-separated.
+    pub using: Vec<FromSource<'a>>,
     /// The conditions for the where clause, if it exists.
     pub where_clause: Option<Term<'a>>,
     /// The columns to return, if any
     pub returning: Option<Columns<'a>>,
+    /// The placeholders handed out by the builder, including those of nested statements.
+    pub params: Params,
 }
 
 impl<'a> Sql for Delete<'a> {
     fn sql(&self) -> String {
-        let mut result = format!("DELETE FROM {}", self.table);
+        let mut result = String::new();
+        push_with_clause(&mut result, &self.with_clause);
+        result.push_str(&format!("DELETE FROM {}", self.table.sql()));
+        if !self.using.is_empty() {
+            let sources: Vec<String> = self.using.iter().map(|f| f.sql()).collect();
+            result.push_str(&format!(" USING {}", sources.join(", ")));
+        }
         if let Some(conditions) = &self.where_clause {
             result.push_str(&format!(" WHERE {}", conditions.sql()));
         }
         if let Some(returning) = &self.returning {
             result.push_str(&format!(" RETURNING {}", returning.sql()));
         }
-        result
+        self.params.finish(result)
+    }
+}
+
+impl<'a> Delete<'a> {
+    /// Renders the SQL and returns it together with the values bound with bind(), ordered by
+    /// placeholder number. Placeholders written by hand with `p(n)` carry no value, and mixing
+    /// param() with bind() is rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut db = D("users");
+    /// let id = db.bind(10);
+    /// let (sql, values) = db.where_(eq("id", &id)).build().render().unwrap();
+    /// assert_eq!(sql, "DELETE FROM users WHERE id = $1");
+    /// assert_eq!(values, vec![Value::Int(10)]);
+    /// ```
+    pub fn render(mut self) -> Result<(String, Vec<Value>), BuildError> {
+        let params = std::mem::take(&mut self.params);
+        params.render(self.sql())
     }
 }
 
@@ -39,9 +77,12 @@ impl<'a> Sql for Delete<'a> {
 /// It is not intended to be used directly, but rather through the D() function.
 /// See the integration_test.rs for an example of usage.
 pub struct DeleteBuilder<'a> {
-    table: &'a str,
+    with_clause: Option<Vec<Cte<'a>>>,
+    table: TargetTable<'a>,
+    using: Vec<FromSource<'a>>,
     where_clause: Option<Term<'a>>,
     returning: Option<Columns<'a>>,
+    all: bool,
     params: PgParams,
 }
 impl<'a> DeleteBuilder<'a> {
@@ -50,49 +91,249 @@ impl<'a> DeleteBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut db = D("users");
-    /// let delete = db.where_(eq("id", "10")).build();
+    /// let delete = D("users").where_(eq("id", "10")).build();
     /// assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10");
     /// ```
     pub fn build(&self) -> Delete<'a> {
-        Delete {
-            table: self.table,
+        let mut delete = Delete {
+            with_clause: self.with_clause.clone(),
+            table: self.table.clone(),
+            using: self.using.clone(),
             where_clause: self.where_clause.clone(),
             returning: self.returning.clone(),
+            params: self.params.params(),
+        };
+        let mut params = std::mem::take(&mut delete.params);
+        for cte in delete.with_clause.iter_mut().flatten() {
+            params.adopt_cte(&mut cte.body);
+        }
+        for source in &mut delete.using {
+            params.adopt_source(source);
+        }
+        if let Some(where_clause) = &mut delete.where_clause {
+            params.adopt_term(where_clause);
         }
+        if let Some(returning) = &mut delete.returning {
+            params.adopt_columns(returning);
+        }
+        delete.params = params;
+        delete
     }
-    /// Sets the WHERE clause
+    /// Builds the final Delete statement, refusing to produce a DELETE without a WHERE clause
+    /// unless all() was called.
     ///
     /// # Example
     /// ```
     /// use squeal::*;
     /// let mut db = D("users");
-    /// let delete = db.where_(eq("active", "false")).build();
+    /// assert_eq!(
+    ///     db.try_build().err(),
+    ///     Some(BuildError::MissingWhere { table: "users".to_string() })
+    /// );
+    /// let delete = db.where_(eq("id", "10")).try_build().unwrap();
+    /// assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10");
+    /// ```
+    pub fn try_build(&self) -> Result<Delete<'a>, BuildError> {
+        if self.where_clause.is_none() && !self.all {
+            return Err(BuildError::MissingWhere {
+                table: self.table.name.to_string(),
+            });
+        }
+        Ok(self.build())
+    }
+    /// Explicitly opts in to deleting every row, so try_build() accepts a missing WHERE clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("staging_rows").all().try_build().unwrap();
+    /// assert_eq!(delete.sql(), "DELETE FROM staging_rows");
+    /// ```
+    pub fn all(&mut self) -> &mut DeleteBuilder<'a> {
+        self.all = true;
+        self
+    }
+    /// Adds a CTE to the WITH clause. Besides a SELECT, it may be a data-modifying statement
+    /// whose RETURNING rows feed this one.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let stale = Q().select(vec!["id"]).from("accounts").where_(lt("last_seen", "NOW() - INTERVAL '1 year'")).build();
+    /// let delete = D("sessions").with("stale", stale).using("stale").where_(eq("sessions.account_id", "stale.id")).build();
+    /// assert_eq!(
+    ///     delete.sql(),
+    ///     "WITH stale AS (SELECT id FROM accounts WHERE last_seen < NOW() - INTERVAL '1 year') \
+    ///      DELETE FROM sessions USING stale WHERE sessions.account_id = stale.id"
+    /// );
+    /// ```
+    pub fn with(&mut self, name: &'a str, body: impl Into<CteBody<'a>>) -> &mut DeleteBuilder<'a> {
+        self.with_cte(Cte {
+            name,
+            body: body.into(),
+            materialized: None,
+        })
+    }
+
+    /// Adds an already-formed CTE to the WITH clause
+    pub fn with_cte(&mut self, cte: Cte<'a>) -> &mut DeleteBuilder<'a> {
+        match &mut self.with_clause {
+            None => self.with_clause = Some(vec![cte]),
+            Some(ctes) => ctes.push(cte),
+        }
+        self
+    }
+
+    /// Qualifies the target table with a schema
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("orders").schema("archive").where_(eq("id", "1")).build();
+    /// assert_eq!(delete.sql(), "DELETE FROM archive.orders WHERE id = 1");
+    /// ```
+    pub fn schema(&mut self, schema: &'a str) -> &mut DeleteBuilder<'a> {
+        self.table.schema = Some(schema);
+        self
+    }
+    /// Aliases the target table, so USING and WHERE can refer to it unambiguously
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("orders").schema("public")
+    ///     .alias("o")
+    ///     .using("customers c")
+    ///     .where_(and(eq("o.customer_id", "c.id"), eq("c.banned", "true")))
+    ///     .build();
+    /// assert_eq!(
+    ///     delete.sql(),
+    ///     "DELETE FROM public.orders AS o USING customers c WHERE o.customer_id = c.id AND c.banned = true"
+    /// );
+    /// ```
+    pub fn alias(&mut self, alias: &'a str) -> &mut DeleteBuilder<'a> {
+        self.table.alias = Some(alias);
+        self
+    }
+    /// Adds a table to the USING clause
+    pub fn using(&mut self, table: &'a str) -> &mut DeleteBuilder<'a> {
+        self.using.push(FromSource::Table(table));
+        self
+    }
+    /// Sets the WHERE clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("users").where_(eq("active", "false")).build();
     /// assert_eq!(delete.sql(), "DELETE FROM users WHERE active = false");
     /// ```
-    pub fn where_(&'a mut self, term: Term<'a>) -> &'a mut DeleteBuilder<'a> {
+    pub fn where_(&mut self, term: Term<'a>) -> &mut DeleteBuilder<'a> {
         self.where_clause = Some(term);
         self
     }
 
+    /// Sets the WHERE clause only if the Option contains Some value
+    /// Useful for conditional/dynamic statement building
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("users").where_opt(Some(eq("id", "4"))).build();
+    /// assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 4");
+    /// ```
+    pub fn where_opt(&mut self, term: Option<Term<'a>>) -> &mut DeleteBuilder<'a> {
+        if let Some(t) = term {
+            self.where_clause = Some(t);
+        }
+        self
+    }
+
+    /// Adds a condition to the WHERE clause with AND
+    /// If no WHERE clause exists yet, this becomes the first condition
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("sessions").and_where(eq("user_id", "9")).and_where(lt("expires_at", "NOW()")).build();
+    /// assert_eq!(delete.sql(), "DELETE FROM sessions WHERE user_id = 9 AND expires_at < NOW()");
+    /// ```
+    pub fn and_where(&mut self, term: Term<'a>) -> &mut DeleteBuilder<'a> {
+        self.where_clause = Some(match self.where_clause.take() {
+            None => term,
+            Some(existing) => and(existing, term),
+        });
+        self
+    }
+
     /// Sets the RETURNING clause
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut db = D("users");
-    /// let delete = db.where_(eq("id", "10")).returning(Columns::Star).build();
+    /// let delete = D("users").where_(eq("id", "10")).returning(Columns::Star).build();
     /// assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10 RETURNING *");
     /// ```
-    pub fn returning(&'a mut self, columns: Columns<'a>) -> &'a mut DeleteBuilder<'a> {
+    pub fn returning(&mut self, columns: Columns<'a>) -> &mut DeleteBuilder<'a> {
         self.returning = Some(columns);
         self
     }
+
+    /// Sets the RETURNING clause to return every column of the deleted rows
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let delete = D("sessions").where_(lt("expires_at", "NOW()")).returning_star().build();
+    /// assert_eq!(delete.sql(), "DELETE FROM sessions WHERE expires_at < NOW() RETURNING *");
+    /// ```
+    pub fn returning_star(&mut self) -> &mut DeleteBuilder<'a> {
+        self.returning = Some(Columns::Star);
+        self
+    }
+
+    /// Sets the style placeholders are rendered in. Placeholders are numbered internally and
+    /// only converted when the statement is rendered, so it can be called at any point, and
+    /// render() orders the values by where the `?` placeholders appear.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut db = D("users");
+    /// db.param_style(ParamStyle::Question);
+    /// let id = db.param();
+    /// let delete = db.where_(eq("id", &id)).build();
+    /// assert_eq!(delete.sql(), "DELETE FROM users WHERE id = ?");
+    /// ```
+    pub fn param_style(&mut self, style: ParamStyle) -> &mut DeleteBuilder<'a> {
+        self.params.set_style(style);
+        self
+    }
+
+    /// Returns the next placeholder and records `value` as the value bound to it.
+    /// The recorded values are returned in placeholder order by the built statement's render().
+    pub fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.params.slot(Some(value.into()))
+    }
+
+    /// Like bind(), but returns the placeholder cast to `type_name`, as in `$1::uuid`.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut db = D("sessions");
+    /// let token = db.bind_as("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f", "uuid");
+    /// let delete = db.where_(eq("token", &token)).build();
+    /// assert_eq!(delete.sql(), "DELETE FROM sessions WHERE token = $1::uuid");
+    /// ```
+    pub fn bind_as(&mut self, value: impl Into<Value>, type_name: &str) -> String {
+        format!("{}::{}", self.bind(value), type_name)
+    }
 }
 
 impl<'a> Parameterized for DeleteBuilder<'a> {
     fn param(&mut self) -> String {
-        self.params.seq()
+        self.params.slot(None)
     }
 }
 
@@ -102,9 +343,12 @@ impl<'a> Parameterized for DeleteBuilder<'a> {
 #[allow(non_snake_case)]
 pub fn D<'a>(table: &'a str) -> DeleteBuilder<'a> {
     DeleteBuilder {
-        table,
+        with_clause: None,
+        table: table.into(),
+        using: Vec::new(),
         where_clause: None,
         returning: None,
+        all: false,
         params: PgParams::new(),
     }
 }
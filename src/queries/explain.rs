@@ -0,0 +1,102 @@
+use crate::Sql;
+
+/// The output format of an EXPLAIN statement
+#[derive(Clone)]
+pub enum ExplainFormat {
+    /// Plain text (the PostgreSQL default)
+    Text,
+    /// XML
+    Xml,
+    /// JSON
+    Json,
+    /// YAML
+    Yaml,
+}
+
+impl Sql for ExplainFormat {
+    fn sql(&self) -> String {
+        match self {
+            ExplainFormat::Text => "text",
+            ExplainFormat::Xml => "xml",
+            ExplainFormat::Json => "json",
+            ExplainFormat::Yaml => "yaml",
+        }
+        .to_string()
+    }
+}
+
+/// The ExplainOptions struct holds the options rendered in the EXPLAIN parenthesized option list.
+/// Options left at their default are omitted.
+#[derive(Clone, Default)]
+pub struct ExplainOptions {
+    /// Execute the statement and report actual run times (ANALYZE)
+    pub analyze: bool,
+    /// Include additional plan details (VERBOSE)
+    pub verbose: bool,
+    /// Include buffer usage (BUFFERS)
+    pub buffers: bool,
+    /// The output format, if not the default
+    pub format: Option<ExplainFormat>,
+}
+
+impl Sql for ExplainOptions {
+    fn sql(&self) -> String {
+        let mut options = Vec::new();
+        if self.analyze {
+            options.push("ANALYZE true".to_string());
+        }
+        if self.verbose {
+            options.push("VERBOSE true".to_string());
+        }
+        if self.buffers {
+            options.push("BUFFERS true".to_string());
+        }
+        if let Some(format) = &self.format {
+            options.push(format!("FORMAT {}", format.sql()));
+        }
+        options.join(", ")
+    }
+}
+
+/// The Explain struct wraps any statement in an EXPLAIN, for query tuning.
+///
+/// Note that with `analyze` set, PostgreSQL actually executes the statement, including any
+/// INSERT, UPDATE or DELETE side effects.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let mut qb = Q();
+/// let query = qb.select(vec!["*"]).from("users").where_(eq("id", "1")).build();
+/// let explain = Explain::new(query, ExplainOptions {
+///     analyze: true,
+///     format: Some(ExplainFormat::Json),
+///     ..Default::default()
+/// });
+/// assert_eq!(explain.sql(), "EXPLAIN (ANALYZE true, FORMAT json) SELECT * FROM users WHERE id = 1");
+/// ```
+#[derive(Clone)]
+pub struct Explain<S: Sql> {
+    /// The statement to explain
+    pub statement: S,
+    /// The EXPLAIN options
+    pub options: ExplainOptions,
+}
+
+impl<S: Sql> Explain<S> {
+    /// Creates a new Explain wrapping the given statement
+    pub fn new(statement: S, options: ExplainOptions) -> Explain<S> {
+        Explain { statement, options }
+    }
+}
+
+impl<S: Sql> Sql for Explain<S> {
+    fn sql(&self) -> String {
+        let options = self.options.sql();
+        if options.is_empty() {
+            format!("EXPLAIN {}", self.statement.sql())
+        } else {
+            format!("EXPLAIN ({}) {}", options, self.statement.sql())
+        }
+    }
+}
@@ -1,27 +1,76 @@
-use crate::{Columns, Parameterized, PgParams, Query, Sql};
+use crate::{
+    BuildError, Columns, Cte, CteBody, Op, ParamStyle, Parameterized, Params, PgParams, Query,
+    SelectExpression, Sql, Term, Value, excluded, push_with_clause,
+};
 
 /// Represents the source of data for an INSERT statement
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum InsertSource<'a> {
     /// Insert from literal values: VALUES (val1, val2, ...), (val3, val4, ...), ...
     /// Each inner Vec represents one row of values
     Values(Vec<Vec<&'a str>>),
+    /// Insert from rows of placeholders generated by bind_rows(): VALUES ($1, $2), ($3, $4), ...
+    BoundRows(Vec<Vec<String>>),
     /// Insert from a SELECT query: SELECT ...
     Select(Box<Query<'a>>),
 }
 
 /// Represents the ON CONFLICT clause for INSERT statements (PostgreSQL UPSERT)
-#[derive(Clone)]
+///
+/// The DO UPDATE assignments are rendered in the order given. Use sorted() to order them by
+/// column name when they come from an unordered source such as a HashMap, so the SQL text stays
+/// stable across runs.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum OnConflict<'a> {
-    /// ON CONFLICT (columns) DO NOTHING
+    /// ON CONFLICT (columns) DO NOTHING. With no columns the target is omitted,
+    /// `ON CONFLICT DO NOTHING`, which ignores a violation of any unique constraint.
     DoNothing(Vec<&'a str>),
     /// ON CONFLICT (columns) DO UPDATE SET col1 = val1, col2 = val2, ...
     DoUpdate(Vec<&'a str>, Vec<(&'a str, &'a str)>),
+    /// ON CONFLICT (columns) DO UPDATE SET col1 = expr1, ... with typed right-hand sides
+    DoUpdateTerms(Vec<&'a str>, Vec<(&'a str, Term<'a>)>),
+    /// ON CONFLICT (columns) DO UPDATE SET (a, b) = (EXCLUDED.a, EXCLUDED.b), the row-assignment
+    /// form taking every listed column from the proposed row
+    DoUpdateExcludedRow(Vec<&'a str>, Vec<&'a str>),
+}
+
+impl<'a> OnConflict<'a> {
+    /// Sorts the DO UPDATE assignments by column name. DO NOTHING is returned unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let on_conflict = OnConflict::DoUpdate(vec!["id"], vec![("name", "EXCLUDED.name"), ("email", "EXCLUDED.email")]);
+    /// assert_eq!(
+    ///     on_conflict.sorted().sql(),
+    ///     "ON CONFLICT (id) DO UPDATE SET email = EXCLUDED.email, name = EXCLUDED.name"
+    /// );
+    /// ```
+    pub fn sorted(self) -> OnConflict<'a> {
+        match self {
+            OnConflict::DoNothing(columns) => OnConflict::DoNothing(columns),
+            OnConflict::DoUpdate(columns, mut updates) => {
+                updates.sort_by(|a, b| a.0.cmp(b.0));
+                OnConflict::DoUpdate(columns, updates)
+            }
+            OnConflict::DoUpdateTerms(columns, mut updates) => {
+                updates.sort_by(|a, b| a.0.cmp(b.0));
+                OnConflict::DoUpdateTerms(columns, updates)
+            }
+            OnConflict::DoUpdateExcludedRow(columns, mut set_columns) => {
+                set_columns.sort();
+                OnConflict::DoUpdateExcludedRow(columns, set_columns)
+            }
+        }
+    }
 }
 
 impl<'a> Sql for OnConflict<'a> {
     fn sql(&self) -> String {
         match self {
+            OnConflict::DoNothing(columns) if columns.is_empty() => {
+                "ON CONFLICT DO NOTHING".to_string()
+            }
             OnConflict::DoNothing(columns) => {
                 format!("ON CONFLICT ({}) DO NOTHING", columns.join(", "))
             }
@@ -37,6 +86,30 @@ impl<'a> Sql for OnConflict<'a> {
                 }
                 result
             }
+            OnConflict::DoUpdateTerms(columns, updates) => {
+                let assignments: Vec<String> = updates
+                    .iter()
+                    .map(|(col, val)| format!("{} = {}", col, val.sql()))
+                    .collect();
+                format!(
+                    "ON CONFLICT ({}) DO UPDATE SET {}",
+                    columns.join(", "),
+                    assignments.join(", ")
+                )
+            }
+            OnConflict::DoUpdateExcludedRow(columns, set_columns) => {
+                let excluded: Vec<String> = set_columns
+                    .iter()
+                    .map(|c| format!("EXCLUDED.{}", c))
+                    .collect();
+                // A one-column row assignment would need ROW(...), so use the plain form.
+                let set = if set_columns.len() == 1 {
+                    format!("{} = {}", set_columns[0], excluded[0])
+                } else {
+                    format!("({}) = ({})", set_columns.join(", "), excluded.join(", "))
+                };
+                format!("ON CONFLICT ({}) DO UPDATE SET {}", columns.join(", "), set)
+            }
         }
     }
 }
@@ -50,11 +123,13 @@ impl<'a> Sql for OnConflict<'a> {
 /// use squeal::*;
 /// // Single row insert
 /// let result = Insert {
+///    with_clause: None,
 ///    table: "table",
 ///    columns: vec!["a", "b"],
 ///    source: InsertSource::Values(vec![vec!["1", "2"]]),
 ///    on_conflict: None,
 ///    returning: None,
+///    params: Params::default(),
 /// }.sql();
 /// assert_eq!(result, "INSERT INTO table (a, b) VALUES (1, 2)");
 /// ```
@@ -63,19 +138,38 @@ impl<'a> Sql for OnConflict<'a> {
 /// use squeal::*;
 /// // Multiple row insert
 /// let result = Insert {
+///    with_clause: None,
 ///    table: "table",
 ///    columns: vec!["a", "b"],
 ///    source: InsertSource::Values(vec![vec!["1", "2"], vec!["3", "4"]]),
 ///    on_conflict: None,
 ///    returning: None,
+///    params: Params::default(),
 /// }.sql();
 /// assert_eq!(result, "INSERT INTO table (a, b) VALUES (1, 2), (3, 4)");
 /// ```
+///
+/// ```
+/// use squeal::*;
+/// // Positional insert into every column, without a column list
+/// let result = Insert {
+///    with_clause: None,
+///    table: "table",
+///    columns: vec![],
+///    source: InsertSource::Values(vec![vec!["1", "'a'"]]),
+///    on_conflict: None,
+///    returning: None,
+///    params: Params::default(),
+/// }.sql();
+/// assert_eq!(result, "INSERT INTO table VALUES (1, 'a')");
+/// ```
 /// Note that the values are not escaped, so you must do that yourself.
 /// If using a prepared statement, you will have to specify the Placeholder and pass in the values to
 /// the execution call at the callsite rather than the preparation site.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Insert<'a> {
+    /// WITH clause (Common Table Expressions)
+    pub with_clause: Option<Vec<Cte<'a>>>,
     /// The table name for the insert clause.
     pub table: &'a str,
     /// The columns to insert.
@@ -86,43 +180,24 @@ pub struct Insert<'a> {
     pub on_conflict: Option<OnConflict<'a>>,
     /// Optional RETURNING clause columns
     pub returning: Option<Columns<'a>>,
+    /// The placeholders handed out by the builder, including those of nested statements.
+    pub params: Params,
 }
 
 impl<'a> Sql for Insert<'a> {
     fn sql(&self) -> String {
-        let mut result = format!("INSERT INTO {} (", self.table);
-        let mut first = true;
-        for c in &self.columns {
-            if !first {
-                result.push_str(", ");
-            }
-            first = false;
-            result.push_str(c.as_ref());
+        let mut result = String::new();
+        push_with_clause(&mut result, &self.with_clause);
+        result.push_str(&format!("INSERT INTO {} ", self.table));
+        // Without a column list, values are assigned to the table's columns positionally
+        if !self.columns.is_empty() {
+            result.push_str(&format!("({}) ", self.columns.join(", ")));
         }
-        result.push_str(") ");
 
         // Handle source (VALUES or SELECT)
         match &self.source {
-            InsertSource::Values(rows) => {
-                result.push_str("VALUES ");
-                let mut first_row = true;
-                for row in rows {
-                    if !first_row {
-                        result.push_str(", ");
-                    }
-                    first_row = false;
-                    result.push('(');
-                    let mut first_val = true;
-                    for v in row {
-                        if !first_val {
-                            result.push_str(", ");
-                        }
-                        first_val = false;
-                        result.push_str(v.as_ref());
-                    }
-                    result.push(')');
-                }
-            }
+            InsertSource::Values(rows) => push_values(&mut result, rows),
+            InsertSource::BoundRows(rows) => push_values(&mut result, rows),
             InsertSource::Select(query) => {
                 result.push_str(&query.sql());
             }
@@ -132,19 +207,60 @@ impl<'a> Sql for Insert<'a> {
             result.push_str(&format!(" {}", on_conflict.sql()));
         }
 
-        if self.returning.is_some() {
-            result.push_str(&format!(
-                " RETURNING {}",
-                self.returning.as_ref().unwrap().sql()
-            ));
+        if let Some(returning) = &self.returning {
+            result.push_str(&format!(" RETURNING {}", returning.sql()));
         }
 
-        result
+        self.params.finish(result)
+    }
+}
+
+/// Appends a VALUES list, one parenthesized group per row.
+fn push_values<S: AsRef<str>>(result: &mut String, rows: &[Vec<S>]) {
+    result.push_str("VALUES ");
+    let mut first_row = true;
+    for row in rows {
+        if !first_row {
+            result.push_str(", ");
+        }
+        first_row = false;
+        result.push('(');
+        let mut first_val = true;
+        for v in row {
+            if !first_val {
+                result.push_str(", ");
+            }
+            first_val = false;
+            result.push_str(v.as_ref());
+        }
+        result.push(')');
+    }
+}
+
+impl<'a> Insert<'a> {
+    /// Renders the SQL and returns it together with the values bound with bind(), ordered by
+    /// placeholder number. Placeholders written by hand with `p(n)` carry no value, and mixing
+    /// param() with bind() is rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ib = I("users");
+    /// let name = ib.bind("Alice");
+    /// let age = ib.bind(30);
+    /// let (sql, values) = ib.columns(vec!["name", "age"]).values(vec![&name, &age]).build().render().unwrap();
+    /// assert_eq!(sql, "INSERT INTO users (name, age) VALUES ($1, $2)");
+    /// assert_eq!(values, vec![Value::Text("Alice".to_string()), Value::Int(30)]);
+    /// ```
+    pub fn render(mut self) -> Result<(String, Vec<Value>), BuildError> {
+        let params = std::mem::take(&mut self.params);
+        params.render(self.sql())
     }
 }
 
 /// Builder for constructing INSERT statements with a fluent interface
 pub struct InsertBuilder<'a> {
+    with_clause: Option<Vec<Cte<'a>>>,
     table: &'a str,
     columns: Vec<&'a str>,
     source: Option<InsertSource<'a>>,
@@ -171,6 +287,7 @@ pub struct InsertBuilder<'a> {
 #[allow(non_snake_case)]
 pub fn I<'a>(table: &'a str) -> InsertBuilder<'a> {
     InsertBuilder {
+        with_clause: None,
         table,
         columns: Vec::new(),
         source: None,
@@ -183,35 +300,130 @@ pub fn I<'a>(table: &'a str) -> InsertBuilder<'a> {
 impl<'a> InsertBuilder<'a> {
     /// Builds the final Insert statement
     ///
+    /// When inserting from a SELECT, the query's placeholders are renumbered to continue after
+    /// the builder's own, and its bound values follow the builder's in the result's params.
+    /// The same goes for subqueries in ON CONFLICT DO UPDATE and RETURNING. With
+    /// `ParamStyle::Question`, render() instead orders the values by where the placeholders
+    /// appear, so values bound in the SELECT come before those in ON CONFLICT.
+    ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["name"]).values(vec!["'Alice'"]).build();
+    /// let insert = I("users").columns(vec!["name"]).values(vec!["'Alice'"]).build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES ('Alice')");
     /// ```
     pub fn build(&self) -> Insert<'a> {
+        let mut params = self.params.params();
+        let mut with_clause = self.with_clause.clone();
+        for cte in with_clause.iter_mut().flatten() {
+            params.adopt_cte(&mut cte.body);
+        }
+        let mut source = self
+            .source
+            .clone()
+            .unwrap_or(InsertSource::Values(vec![Vec::new()]));
+        if let InsertSource::Select(query) = &mut source {
+            params.adopt(&mut query.params);
+        }
+        let mut on_conflict = self.on_conflict.clone();
+        if let Some(OnConflict::DoUpdateTerms(_, updates)) = &mut on_conflict {
+            for (_, term) in updates {
+                params.adopt_term(term);
+            }
+        }
+        let mut returning = self.returning.clone();
+        if let Some(returning) = &mut returning {
+            params.adopt_columns(returning);
+        }
         Insert {
+            with_clause,
             table: self.table,
             columns: self.columns.clone(),
-            source: self
-                .source
-                .clone()
-                .unwrap_or(InsertSource::Values(vec![Vec::new()])),
-            on_conflict: self.on_conflict.clone(),
-            returning: self.returning.clone(),
+            source,
+            on_conflict,
+            returning,
+            params,
+        }
+    }
+    /// Builds the final Insert statement, checking that every row of values has exactly one
+    /// value per column. Statements without a column list or inserting from a SELECT are not
+    /// checked.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let result = I("users").columns(vec!["name", "age"])
+    ///     .rows(vec![vec!["'Alice'", "30"], vec!["'Bob'"]])
+    ///     .try_build();
+    /// assert_eq!(
+    ///     result.err(),
+    ///     Some(BuildError::RowArityMismatch { row_index: 1, expected: 2, got: 1 })
+    /// );
+    /// ```
+    pub fn try_build(&self) -> Result<Insert<'a>, BuildError> {
+        let insert = self.build();
+        let expected = insert.columns.len();
+        if expected > 0 {
+            let lengths: Vec<usize> = match &insert.source {
+                InsertSource::Values(rows) => rows.iter().map(|r| r.len()).collect(),
+                InsertSource::BoundRows(rows) => rows.iter().map(|r| r.len()).collect(),
+                InsertSource::Select(_) => vec![],
+            };
+            if let Some((row_index, &got)) = lengths
+                .iter()
+                .enumerate()
+                .find(|(_, len)| **len != expected)
+            {
+                return Err(BuildError::RowArityMismatch {
+                    row_index,
+                    expected,
+                    got,
+                });
+            }
         }
+        Ok(insert)
+    }
+    /// Adds a CTE to the WITH clause. Besides a SELECT, it may be a data-modifying statement
+    /// whose RETURNING rows feed this one.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let moved = D("orders").where_(lt("placed_at", "'2020-01-01'")).returning(Columns::Star).build();
+    /// let insert = I("orders_archive")
+    ///     .with("moved", moved)
+    ///     .select(Q().select(vec!["*"]).from("moved").build())
+    ///     .build();
+    /// assert_eq!(
+    ///     insert.sql(),
+    ///     "WITH moved AS (DELETE FROM orders WHERE placed_at < '2020-01-01' RETURNING *) \
+    ///      INSERT INTO orders_archive SELECT * FROM moved"
+    /// );
+    /// ```
+    pub fn with(&mut self, name: &'a str, body: impl Into<CteBody<'a>>) -> &mut InsertBuilder<'a> {
+        self.with_cte(Cte {
+            name,
+            body: body.into(),
+            materialized: None,
+        })
+    }
+    /// Adds an already-formed CTE to the WITH clause
+    pub fn with_cte(&mut self, cte: Cte<'a>) -> &mut InsertBuilder<'a> {
+        match &mut self.with_clause {
+            None => self.with_clause = Some(vec![cte]),
+            Some(ctes) => ctes.push(cte),
+        }
+        self
     }
     /// Sets the columns to insert into
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["name", "email"]).values(vec!["'Alice'", "'alice@example.com'"]).build();
+    /// let insert = I("users").columns(vec!["name", "email"]).values(vec!["'Alice'", "'alice@example.com'"]).build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')");
     /// ```
-    pub fn columns(&'a mut self, columns: Vec<&'a str>) -> &'a mut InsertBuilder<'a> {
+    pub fn columns(&mut self, columns: Vec<&'a str>) -> &mut InsertBuilder<'a> {
         for c in columns {
             self.columns.push(c);
         }
@@ -222,11 +434,26 @@ impl<'a> InsertBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["name"]).values(vec!["'Bob'"]).build();
+    /// let insert = I("users").columns(vec!["name"]).values(vec!["'Bob'"]).build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES ('Bob')");
     /// ```
-    pub fn values(&'a mut self, values: Vec<&'a str>) -> &'a mut InsertBuilder<'a> {
+    pub fn values(&mut self, values: Vec<&'a str>) -> &mut InsertBuilder<'a> {
+        self.source = Some(InsertSource::Values(vec![values]));
+        self
+    }
+
+    /// Sets the columns and a single row of values from column-value pairs, so the two cannot
+    /// get out of step. Replaces any columns and values set before.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let insert = I("users").set_columns(vec![("email", "'bob@example.com'"), ("name", "'Bob'")]).build();
+    /// assert_eq!(insert.sql(), "INSERT INTO users (email, name) VALUES ('bob@example.com', 'Bob')");
+    /// ```
+    pub fn set_columns(&mut self, pairs: Vec<(&'a str, &'a str)>) -> &mut InsertBuilder<'a> {
+        let (columns, values): (Vec<&'a str>, Vec<&'a str>) = pairs.into_iter().unzip();
+        self.columns = columns;
         self.source = Some(InsertSource::Values(vec![values]));
         self
     }
@@ -236,8 +463,7 @@ impl<'a> InsertBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["name", "age"])
+    /// let insert = I("users").columns(vec!["name", "age"])
     ///     .rows(vec![
     ///         vec!["'Alice'", "30"],
     ///         vec!["'Bob'", "25"],
@@ -246,11 +472,39 @@ impl<'a> InsertBuilder<'a> {
     ///     .build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (name, age) VALUES ('Alice', 30), ('Bob', 25), ('Charlie', 35)");
     /// ```
-    pub fn rows(&'a mut self, rows: Vec<Vec<&'a str>>) -> &'a mut InsertBuilder<'a> {
+    pub fn rows(&mut self, rows: Vec<Vec<&'a str>>) -> &mut InsertBuilder<'a> {
         self.source = Some(InsertSource::Values(rows));
         self
     }
 
+    /// Binds every value in `rows` and uses the generated placeholders as the rows to insert.
+    ///
+    /// Placeholders are numbered in row-major order, continuing from any already issued by
+    /// param() or bind(), and the values are recorded for render().
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ib = I("users");
+    /// let (sql, values) = ib.columns(vec!["name", "age"])
+    ///     .bind_rows(vec![
+    ///         vec![Value::from("Alice"), Value::from(30)],
+    ///         vec![Value::from("Bob"), Value::from(25)],
+    ///     ])
+    ///     .build()
+    ///     .render().unwrap();
+    /// assert_eq!(sql, "INSERT INTO users (name, age) VALUES ($1, $2), ($3, $4)");
+    /// assert_eq!(values.len(), 4);
+    /// ```
+    pub fn bind_rows(&mut self, rows: Vec<Vec<Value>>) -> &mut InsertBuilder<'a> {
+        let placeholders = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| self.bind(v)).collect())
+            .collect();
+        self.source = Some(InsertSource::BoundRows(placeholders));
+        self
+    }
+
     /// Sets a SELECT query as the data source
     ///
     /// # Example
@@ -264,16 +518,19 @@ impl<'a> InsertBuilder<'a> {
     ///     where_clause: None,
     ///     group_by: None,
     ///     having: None,
+    ///     window_defs: vec![],
     ///     order_by: None,
     ///     limit: None,
     ///     offset: None,
-    ///     for_update: false,
+    ///     fetch_first: None,
+    ///     locks: vec![],
+    ///     comment: None,
+    ///     params: Params::default(),
     /// };
-    /// let mut ib = I("archived_users");
-    /// let insert = ib.columns(vec!["name", "email"]).select(subquery).build();
+    /// let insert = I("archived_users").columns(vec!["name", "email"]).select(subquery).build();
     /// assert_eq!(insert.sql(), "INSERT INTO archived_users (name, email) SELECT name, email FROM active_users");
     /// ```
-    pub fn select(&'a mut self, query: Query<'a>) -> &'a mut InsertBuilder<'a> {
+    pub fn select(&mut self, query: Query<'a>) -> &mut InsertBuilder<'a> {
         self.source = Some(InsertSource::Select(Box::new(query)));
         self
     }
@@ -283,59 +540,218 @@ impl<'a> InsertBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["name"]).values(vec!["'Charlie'"]).returning(Columns::Star).build();
+    /// let insert = I("users").columns(vec!["name"]).values(vec!["'Charlie'"]).returning(Columns::Star).build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES ('Charlie') RETURNING *");
     /// ```
-    pub fn returning(&'a mut self, columns: Columns<'a>) -> &'a mut InsertBuilder<'a> {
+    pub fn returning(&mut self, columns: Columns<'a>) -> &mut InsertBuilder<'a> {
         self.returning = Some(columns);
         self
     }
 
+    /// Sets the RETURNING clause to the given columns plus an `inserted` flag that tells a
+    /// freshly inserted row (true) from one updated by ON CONFLICT DO UPDATE (false).
+    ///
+    /// This uses the PostgreSQL `xmax = 0` idiom: a newly inserted row version has no
+    /// deleting or locking transaction recorded yet.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let insert = I("users").columns(vec!["email", "name"])
+    ///     .values(vec!["'a@example.com'", "'Alice'"])
+    ///     .on_conflict_do_update(vec!["email"], vec![("name", "EXCLUDED.name")])
+    ///     .returning_with_inserted_flag(vec!["id"])
+    ///     .build();
+    /// assert_eq!(insert.sql(), "INSERT INTO users (email, name) VALUES ('a@example.com', 'Alice') ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name RETURNING id, (xmax = 0) AS inserted");
+    /// ```
+    pub fn returning_with_inserted_flag(
+        &mut self,
+        columns: Vec<&'a str>,
+    ) -> &mut InsertBuilder<'a> {
+        let mut exprs: Vec<SelectExpression<'a>> =
+            columns.into_iter().map(SelectExpression::Column).collect();
+        exprs.push(SelectExpression::Expr(
+            Term::Parens(Box::new(Term::Condition(
+                Box::new(Term::Atom("xmax")),
+                Op::Equals,
+                Box::new(Term::Atom("0")),
+            ))),
+            Some("inserted"),
+        ));
+        self.returning = Some(Columns::Expressions(exprs));
+        self
+    }
+
     /// Sets the ON CONFLICT DO NOTHING clause
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["email", "name"])
+    /// let insert = I("users").columns(vec!["email", "name"])
     ///     .values(vec!["'alice@example.com'", "'Alice'"])
     ///     .on_conflict_do_nothing(vec!["email"])
     ///     .build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (email, name) VALUES ('alice@example.com', 'Alice') ON CONFLICT (email) DO NOTHING");
     /// ```
-    pub fn on_conflict_do_nothing(
-        &'a mut self,
-        columns: Vec<&'a str>,
-    ) -> &'a mut InsertBuilder<'a> {
+    pub fn on_conflict_do_nothing(&mut self, columns: Vec<&'a str>) -> &mut InsertBuilder<'a> {
         self.on_conflict = Some(OnConflict::DoNothing(columns));
         self
     }
 
+    /// Sets a target-less ON CONFLICT DO NOTHING clause, which skips rows that would violate
+    /// any unique or exclusion constraint
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let insert = I("tags").columns(vec!["name"])
+    ///     .values(vec!["'rust'"])
+    ///     .on_conflict_do_nothing_any()
+    ///     .build();
+    /// assert_eq!(insert.sql(), "INSERT INTO tags (name) VALUES ('rust') ON CONFLICT DO NOTHING");
+    /// ```
+    pub fn on_conflict_do_nothing_any(&mut self) -> &mut InsertBuilder<'a> {
+        self.on_conflict = Some(OnConflict::DoNothing(vec![]));
+        self
+    }
+
     /// Sets the ON CONFLICT DO UPDATE clause
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ib = I("users");
-    /// let insert = ib.columns(vec!["email", "name"])
+    /// let insert = I("users").columns(vec!["email", "name"])
     ///     .values(vec!["'alice@example.com'", "'Alice'"])
     ///     .on_conflict_do_update(vec!["email"], vec![("name", "'Alice Updated'")])
     ///     .build();
     /// assert_eq!(insert.sql(), "INSERT INTO users (email, name) VALUES ('alice@example.com', 'Alice') ON CONFLICT (email) DO UPDATE SET name = 'Alice Updated'");
     /// ```
     pub fn on_conflict_do_update(
-        &'a mut self,
+        &mut self,
         conflict_columns: Vec<&'a str>,
         updates: Vec<(&'a str, &'a str)>,
-    ) -> &'a mut InsertBuilder<'a> {
+    ) -> &mut InsertBuilder<'a> {
         self.on_conflict = Some(OnConflict::DoUpdate(conflict_columns, updates));
         self
     }
+
+    /// Sets the ON CONFLICT DO UPDATE clause with typed expressions on the right-hand side
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let insert = I("users").columns(vec!["email", "login_count"])
+    ///     .values(vec!["'alice@example.com'", "1"])
+    ///     .on_conflict_do_update_terms(
+    ///         vec!["email"],
+    ///         vec![("login_count", add(Term::Atom("users.login_count"), excluded("login_count")))],
+    ///     )
+    ///     .build();
+    /// assert_eq!(insert.sql(), "INSERT INTO users (email, login_count) VALUES ('alice@example.com', 1) ON CONFLICT (email) DO UPDATE SET login_count = users.login_count + EXCLUDED.login_count");
+    /// ```
+    pub fn on_conflict_do_update_terms(
+        &mut self,
+        conflict_columns: Vec<&'a str>,
+        updates: Vec<(&'a str, Term<'a>)>,
+    ) -> &mut InsertBuilder<'a> {
+        self.on_conflict = Some(OnConflict::DoUpdateTerms(conflict_columns, updates));
+        self
+    }
+
+    /// Sets an ON CONFLICT DO UPDATE clause that overwrites each of `set_columns` with the
+    /// value from the proposed row, `col = EXCLUDED.col`
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let insert = I("users").columns(vec!["email", "name", "plan"])
+    ///     .values(vec!["'alice@example.com'", "'Alice'", "'pro'"])
+    ///     .on_conflict_do_update_excluded(vec!["email"], vec!["name", "plan"])
+    ///     .build();
+    /// assert_eq!(
+    ///     insert.sql(),
+    ///     "INSERT INTO users (email, name, plan) VALUES ('alice@example.com', 'Alice', 'pro') \
+    ///      ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name, plan = EXCLUDED.plan"
+    /// );
+    /// ```
+    pub fn on_conflict_do_update_excluded(
+        &mut self,
+        conflict_columns: Vec<&'a str>,
+        set_columns: Vec<&'a str>,
+    ) -> &mut InsertBuilder<'a> {
+        let updates = set_columns.into_iter().map(|c| (c, excluded(c))).collect();
+        self.on_conflict_do_update_terms(conflict_columns, updates)
+    }
+
+    /// Like on_conflict_do_update_excluded(), but renders the row-assignment form
+    /// `SET (a, b) = (EXCLUDED.a, EXCLUDED.b)`
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let insert = I("users").columns(vec!["email", "name", "plan"])
+    ///     .values(vec!["'alice@example.com'", "'Alice'", "'pro'"])
+    ///     .on_conflict_do_update_excluded_row(vec!["email"], vec!["name", "plan"])
+    ///     .build();
+    /// assert_eq!(
+    ///     insert.sql(),
+    ///     "INSERT INTO users (email, name, plan) VALUES ('alice@example.com', 'Alice', 'pro') \
+    ///      ON CONFLICT (email) DO UPDATE SET (name, plan) = (EXCLUDED.name, EXCLUDED.plan)"
+    /// );
+    /// ```
+    pub fn on_conflict_do_update_excluded_row(
+        &mut self,
+        conflict_columns: Vec<&'a str>,
+        set_columns: Vec<&'a str>,
+    ) -> &mut InsertBuilder<'a> {
+        self.on_conflict = Some(OnConflict::DoUpdateExcludedRow(
+            conflict_columns,
+            set_columns,
+        ));
+        self
+    }
+
+    /// Sets the style placeholders are rendered in. Placeholders are numbered internally and
+    /// only converted when the statement is rendered, so it can be called at any point, and
+    /// render() orders the values by where the `?` placeholders appear.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ib = I("users");
+    /// ib.param_style(ParamStyle::Question);
+    /// let name = ib.param();
+    /// let insert = ib.columns(vec!["name"]).values(vec![&name]).build();
+    /// assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES (?)");
+    /// ```
+    pub fn param_style(&mut self, style: ParamStyle) -> &mut InsertBuilder<'a> {
+        self.params.set_style(style);
+        self
+    }
+
+    /// Returns the next placeholder and records `value` as the value bound to it.
+    /// The recorded values are returned in placeholder order by the built statement's render().
+    pub fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.params.slot(Some(value.into()))
+    }
+
+    /// Like bind(), but returns the placeholder cast to `type_name`, as in `$1::uuid`.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ib = I("users");
+    /// let id = ib.bind_as("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f", "uuid");
+    /// let insert = ib.columns(vec!["id"]).values(vec![&id]).build();
+    /// assert_eq!(insert.sql(), "INSERT INTO users (id) VALUES ($1::uuid)");
+    /// ```
+    pub fn bind_as(&mut self, value: impl Into<Value>, type_name: &str) -> String {
+        format!("{}::{}", self.bind(value), type_name)
+    }
 }
 
 impl<'a> Parameterized for InsertBuilder<'a> {
     fn param(&mut self) -> String {
-        self.params.seq()
+        self.params.slot(None)
     }
 }
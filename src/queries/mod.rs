@@ -1,6 +1,15 @@
+pub mod comment;
+pub mod create_index;
 pub mod create_table;
+pub mod create_table_as;
 pub mod delete;
 pub mod drop_table;
+pub mod explain;
 pub mod insert;
+pub mod prepare;
+pub mod script;
 pub mod select;
+pub mod set_operation;
+pub mod stats;
+pub mod table_stmt;
 pub mod update;
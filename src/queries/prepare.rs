@@ -0,0 +1,81 @@
+use crate::{Query, Sql};
+
+/// The Prepare struct represents a server-side `PREPARE` statement.
+/// The body is an ordinary Query whose `$n` placeholders become the statement's parameters.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let mut qb = Q();
+/// let p1 = qb.param();
+/// let query = qb.select(vec!["*"]).from("users").where_(eq("id", &p1)).build();
+/// let prepare = Prepare { name: "user_by_id", arg_types: vec!["int"], stmt: query };
+/// assert_eq!(prepare.sql(), "PREPARE user_by_id (int) AS SELECT * FROM users WHERE id = $1");
+/// ```
+#[derive(Clone)]
+pub struct Prepare<'a> {
+    /// The name of the prepared statement
+    pub name: &'a str,
+    /// The data types of the parameters, in placeholder order. May be empty.
+    pub arg_types: Vec<&'a str>,
+    /// The statement to prepare
+    pub stmt: Query<'a>,
+}
+
+impl<'a> Sql for Prepare<'a> {
+    fn sql(&self) -> String {
+        let mut result = format!("PREPARE {}", self.name);
+        if !self.arg_types.is_empty() {
+            result.push_str(&format!(" ({})", self.arg_types.join(", ")));
+        }
+        result.push_str(&format!(" AS {}", self.stmt.sql()));
+        result
+    }
+}
+
+/// The Execute struct represents an `EXECUTE` of a prepared statement.
+/// Note that the arguments are not escaped, so you must do that yourself.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let execute = Execute { name: "user_by_id", args: vec!["1", "'x'"] };
+/// assert_eq!(execute.sql(), "EXECUTE user_by_id (1, 'x')");
+/// ```
+#[derive(Clone)]
+pub struct Execute<'a> {
+    /// The name of the prepared statement
+    pub name: &'a str,
+    /// The argument values, in placeholder order. May be empty.
+    pub args: Vec<&'a str>,
+}
+
+impl<'a> Sql for Execute<'a> {
+    fn sql(&self) -> String {
+        if self.args.is_empty() {
+            format!("EXECUTE {}", self.name)
+        } else {
+            format!("EXECUTE {} ({})", self.name, self.args.join(", "))
+        }
+    }
+}
+
+/// The Deallocate struct represents a `DEALLOCATE` of a prepared statement.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let deallocate = Deallocate { name: "user_by_id" };
+/// assert_eq!(deallocate.sql(), "DEALLOCATE user_by_id");
+/// ```
+#[derive(Clone)]
+pub struct Deallocate<'a> {
+    /// The name of the prepared statement
+    pub name: &'a str,
+}
+
+impl<'a> Sql for Deallocate<'a> {
+    fn sql(&self) -> String {
+        format!("DEALLOCATE {}", self.name)
+    }
+}
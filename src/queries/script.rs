@@ -0,0 +1,74 @@
+use crate::Sql;
+
+/// Script holds a sequence of heterogeneous statements, e.g. the steps of a migration.
+///
+/// It renders as one script with each statement terminated by `;` on its own line. Drivers that
+/// accept a single statement per call can use statements() instead.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let mut tb = T("users");
+/// let create = tb.column("id", "serial", vec!["PRIMARY KEY"]).build_create_table();
+/// let mut ib = I("users");
+/// let insert = ib.columns(vec!["id"]).values(vec!["DEFAULT"]).build();
+///
+/// let mut script = Script::new();
+/// script.push(create).push(insert);
+/// assert_eq!(
+///     script.sql(),
+///     "CREATE TABLE users (id serial PRIMARY KEY);\nINSERT INTO users (id) VALUES (DEFAULT);"
+/// );
+/// ```
+#[derive(Default)]
+pub struct Script<'a> {
+    statements: Vec<Box<dyn Sql + 'a>>,
+}
+
+impl<'a> Script<'a> {
+    /// Creates an empty script
+    pub fn new() -> Script<'a> {
+        Script {
+            statements: Vec::new(),
+        }
+    }
+
+    /// Appends a statement to the script
+    pub fn push(&mut self, statement: impl Sql + 'a) -> &mut Script<'a> {
+        self.statements.push(Box::new(statement));
+        self
+    }
+
+    /// Returns each statement rendered on its own, without a terminating `;`
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut script = Script::new();
+    /// script.push(DropTable { table: "a" }).push(DropTable { table: "b" });
+    /// let statements: Vec<String> = script.statements().collect();
+    /// assert_eq!(statements, vec!["DROP TABLE a", "DROP TABLE b"]);
+    /// ```
+    pub fn statements(&self) -> impl Iterator<Item = String> + '_ {
+        self.statements.iter().map(|s| s.sql())
+    }
+
+    /// Returns the number of statements in the script
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Returns true if the script has no statements
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+}
+
+impl<'a> Sql for Script<'a> {
+    fn sql(&self) -> String {
+        self.statements()
+            .map(|s| format!("{};", s))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
@@ -1,12 +1,18 @@
-use crate::{Distinct, Query, Sql};
+use crate::{Distinct, Query, Sql, Term};
 
 /// A single expression in a SELECT clause
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum SelectExpression<'a> {
     /// A simple column name or expression
     Column(&'a str),
     /// A subquery with an optional alias
     Subquery(Box<Query<'a>>, Option<&'a str>),
+    /// An arbitrary term (e.g. a window function call) with an optional alias
+    Expr(Term<'a>, Option<&'a str>),
+    /// All columns (`*`), for mixing with computed expressions
+    Star,
+    /// All columns of one table (`table.*`)
+    TableStar(&'a str),
 }
 
 impl<'a> Sql for SelectExpression<'a> {
@@ -20,10 +26,48 @@ impl<'a> Sql for SelectExpression<'a> {
                     format!("({})", query.sql())
                 }
             }
+            SelectExpression::Expr(term, alias) => match alias {
+                Some(a) => format!("{} AS {}", term.sql(), a),
+                None => term.sql(),
+            },
+            SelectExpression::Star => "*".to_string(),
+            SelectExpression::TableStar(table) => format!("{}.*", table),
         }
     }
 }
 
+impl<'a> SelectExpression<'a> {
+    /// Selects a placeholder from param() or bind() cast to a type, as in
+    /// `SELECT $1::text AS greeting`. PostgreSQL needs the cast to know the parameter's type.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let greeting = qb.bind("hello");
+    /// let id = qb.bind(7);
+    /// let query = qb
+    ///     .select_expressions(vec![
+    ///         SelectExpression::param(&greeting, "text", Some("greeting")),
+    ///         SelectExpression::Column("name"),
+    ///     ])
+    ///     .from("users")
+    ///     .where_(eq("id", &id))
+    ///     .build();
+    /// assert_eq!(query.sql(), "SELECT $1::text AS greeting, name FROM users WHERE id = $2");
+    /// ```
+    pub fn param(
+        placeholder: &'a str,
+        type_name: &'a str,
+        alias: Option<&'a str>,
+    ) -> SelectExpression<'a> {
+        SelectExpression::Expr(
+            Term::PgCast(Box::new(Term::Atom(placeholder)), type_name),
+            alias,
+        )
+    }
+}
+
 /// The Columns enum is used to specify which columns to select.
 ///
 /// It is used in the Select struct.
@@ -43,7 +87,21 @@ impl<'a> Sql for SelectExpression<'a> {
 /// let result = Select::new(Columns::Selected(vec!["a", "b"]), None).sql();
 /// assert_eq!(result, "a, b");
 /// ```
-#[derive(Clone)]
+///
+/// All columns plus a computed one:
+/// ```
+/// use squeal::*;
+/// let result = Select::new(
+///     Columns::Expressions(vec![
+///         SelectExpression::Star,
+///         SelectExpression::Expr(func("now", vec![]), Some("fetched_at")),
+///     ]),
+///     None,
+/// )
+/// .sql();
+/// assert_eq!(result, "*, now() AS fetched_at");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Columns<'a> {
     /// Wildcard selector (*)
     Star,
@@ -76,7 +134,7 @@ impl<'a> Sql for Columns<'a> {
 ///
 /// It does not currently support DISTINCT, functions, or other SELECT features besides simple
 /// projection.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Select<'a> {
     /// The columns to select
     pub cols: Columns<'a>,
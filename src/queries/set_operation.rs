@@ -0,0 +1,232 @@
+use crate::{BuildError, OrderBy, OrderedColumn, Params, Query, Sql, TableStmt, Value};
+
+/// The operator joining two branches of a compound query
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SetOperator {
+    /// UNION, which removes duplicate rows from the combined result
+    Union,
+    /// UNION ALL, which keeps duplicates
+    UnionAll,
+    /// INTERSECT
+    Intersect,
+    /// INTERSECT ALL
+    IntersectAll,
+    /// EXCEPT
+    Except,
+    /// EXCEPT ALL
+    ExceptAll,
+}
+
+impl Sql for SetOperator {
+    fn sql(&self) -> String {
+        match self {
+            SetOperator::Union => "UNION",
+            SetOperator::UnionAll => "UNION ALL",
+            SetOperator::Intersect => "INTERSECT",
+            SetOperator::IntersectAll => "INTERSECT ALL",
+            SetOperator::Except => "EXCEPT",
+            SetOperator::ExceptAll => "EXCEPT ALL",
+        }
+        .to_string()
+    }
+}
+
+/// One branch of a compound query: a SELECT or a `TABLE name` statement
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum SetBranch<'a> {
+    /// A SELECT query
+    Select(Box<Query<'a>>),
+    /// A `TABLE name` statement
+    Table(TableStmt<'a>),
+}
+
+impl<'a> Sql for SetBranch<'a> {
+    fn sql(&self) -> String {
+        match self {
+            SetBranch::Select(query) => query.sql(),
+            SetBranch::Table(table) => table.sql(),
+        }
+    }
+}
+
+impl<'a> SetBranch<'a> {
+    /// Returns the params of a SELECT branch; a `TABLE name` statement has none
+    fn params_mut(&mut self) -> Option<&mut Params> {
+        match self {
+            SetBranch::Select(query) => Some(&mut query.params),
+            SetBranch::Table(_) => None,
+        }
+    }
+}
+
+impl<'a> From<Query<'a>> for SetBranch<'a> {
+    fn from(query: Query<'a>) -> Self {
+        SetBranch::Select(Box::new(query))
+    }
+}
+
+impl<'a> From<TableStmt<'a>> for SetBranch<'a> {
+    fn from(table: TableStmt<'a>) -> Self {
+        SetBranch::Table(table)
+    }
+}
+
+/// The SetQuery struct is a compound query combining SELECTs with UNION, INTERSECT or EXCEPT.
+///
+/// Every branch is parenthesized, so a branch's own DISTINCT, ORDER BY or LIMIT applies to that
+/// branch only. The ORDER BY, LIMIT and OFFSET set here apply to the combined result.
+///
+/// Each branch hands its params to the compound query as it is added, so placeholders bound in
+/// later branches are renumbered to follow those of the earlier ones.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let compound = Q().select(vec!["a"]).distinct().from("x").build()
+///     .union_all(Q().select(vec!["a"]).from("y").build())
+///     .order_by(vec![OrderedColumn::Asc("a")])
+///     .limit(10);
+/// assert_eq!(
+///     compound.sql(),
+///     "(SELECT DISTINCT a FROM x) UNION ALL (SELECT a FROM y) ORDER BY a ASC LIMIT 10"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SetQuery<'a> {
+    /// The first branch
+    pub first: SetBranch<'a>,
+    /// The following branches, each with the operator that joins it to what precedes it
+    pub rest: Vec<(SetOperator, SetBranch<'a>)>,
+    /// The ORDER BY of the combined result
+    pub order_by: Option<OrderBy<'a>>,
+    /// The LIMIT of the combined result
+    pub limit: Option<u64>,
+    /// The OFFSET of the combined result
+    pub offset: Option<u64>,
+    /// The parameters adopted from the branches, in render order
+    pub params: Params,
+}
+
+impl<'a> SetQuery<'a> {
+    /// Creates a compound query with a single branch, to be extended with combine()
+    pub fn new(first: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        let mut first = first.into();
+        let mut params = Params::default();
+        if let Some(nested) = first.params_mut() {
+            params.style = nested.style;
+            params.adopt(nested);
+        }
+        SetQuery {
+            first,
+            rest: vec![],
+            order_by: None,
+            limit: None,
+            offset: None,
+            params,
+        }
+    }
+
+    /// Appends a branch joined with the given operator
+    pub fn combine(mut self, op: SetOperator, branch: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        let mut branch = branch.into();
+        if let Some(nested) = branch.params_mut() {
+            self.params.adopt(nested);
+        }
+        self.rest.push((op, branch));
+        self
+    }
+
+    /// Appends a branch with UNION
+    pub fn union(self, branch: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        self.combine(SetOperator::Union, branch)
+    }
+
+    /// Appends a branch with UNION ALL
+    pub fn union_all(self, branch: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        self.combine(SetOperator::UnionAll, branch)
+    }
+
+    /// Sets the ORDER BY of the combined result
+    pub fn order_by(mut self, columns: Vec<OrderedColumn<'a>>) -> SetQuery<'a> {
+        self.order_by = Some(OrderBy { columns });
+        self
+    }
+
+    /// Sets the LIMIT of the combined result
+    pub fn limit(mut self, limit: u64) -> SetQuery<'a> {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the OFFSET of the combined result
+    pub fn offset(mut self, offset: u64) -> SetQuery<'a> {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Renders the SQL and returns it together with the values bound in every branch, ordered
+    /// by placeholder number.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let a = qb.bind(1);
+    /// let first = qb.select(vec!["id"]).from("a").where_(eq("id", &a)).build();
+    /// let mut qb = Q();
+    /// let b = qb.bind(2);
+    /// let second = qb.select(vec!["id"]).from("b").where_(eq("id", &b)).build();
+    /// let (sql, values) = first.union(second).render()?;
+    /// assert_eq!(sql, "(SELECT id FROM a WHERE id = $1) UNION (SELECT id FROM b WHERE id = $2)");
+    /// assert_eq!(values, vec![Value::Int(1), Value::Int(2)]);
+    /// # Ok::<(), BuildError>(())
+    /// ```
+    pub fn render(mut self) -> Result<(String, Vec<Value>), BuildError> {
+        let params = std::mem::take(&mut self.params);
+        params.render(self.sql())
+    }
+}
+
+impl<'a> Sql for SetQuery<'a> {
+    fn sql(&self) -> String {
+        let mut result = format!("({})", self.first.sql());
+        for (op, branch) in &self.rest {
+            result.push_str(&format!(" {} ({})", op.sql(), branch.sql()));
+        }
+        if let Some(order_by) = &self.order_by {
+            result.push_str(&format!(" ORDER BY {}", order_by.columns_sql()));
+        }
+        if let Some(limit) = self.limit {
+            result.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            result.push_str(&format!(" OFFSET {}", offset));
+        }
+        self.params.finish(result)
+    }
+}
+
+impl<'a> Query<'a> {
+    /// Starts a compound query with this query as the first branch
+    pub fn combine(self, op: SetOperator, other: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        SetQuery::new(self).combine(op, other)
+    }
+
+    /// Combines this query with another using UNION
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let compound = Q().select(vec!["email"]).from("users").build()
+    ///     .union(Q().select(vec!["email"]).from("invites").build());
+    /// assert_eq!(compound.sql(), "(SELECT email FROM users) UNION (SELECT email FROM invites)");
+    /// ```
+    pub fn union(self, other: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        self.combine(SetOperator::Union, other)
+    }
+
+    /// Combines this query with another using UNION ALL
+    pub fn union_all(self, other: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        self.combine(SetOperator::UnionAll, other)
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    Columns, Cte, CteBody, FromSource, InsertSource, Query, SelectExpression, SetBranch, SetQuery,
+    Term, walk_terms,
+};
+
+/// The QueryStats struct summarizes the shape of a query, for rejecting overly complex
+/// generated queries before they reach the database.
+///
+/// Counts cover the whole tree: joins, CTEs and subqueries inside subqueries and CTE bodies
+/// are included.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let query = Q()
+///     .select(vec!["*"])
+///     .from("users")
+///     .inner_join("orders", eq("orders.user_id", "users.id"))
+///     .where_(exists(subquery(|q| q.select(vec!["1"]).from("bans").where_(eq("bans.user_id", "users.id")))))
+///     .build();
+/// assert_eq!(
+///     query.stats(),
+///     QueryStats { joins: 1, subqueries: 1, ctes: 0, union_branches: 0, max_depth: 1 }
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct QueryStats {
+    /// The number of JOIN clauses
+    pub joins: usize,
+    /// The number of subqueries, in FROM, JOIN, the select list or any condition
+    pub subqueries: usize,
+    /// The number of common table expressions
+    pub ctes: usize,
+    /// The number of branches of set operations such as UNION; 0 for a plain query
+    pub union_branches: usize,
+    /// The deepest nesting of subqueries and CTE bodies; 0 when there are none
+    pub max_depth: usize,
+}
+
+impl QueryStats {
+    /// Adds the counts of a query at the same nesting level, such as another set-operation
+    /// branch
+    fn merge(&mut self, other: QueryStats) {
+        self.joins += other.joins;
+        self.subqueries += other.subqueries;
+        self.ctes += other.ctes;
+        self.union_branches += other.union_branches;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+
+    /// Adds the counts of a query nested one level below this one
+    fn nest(&mut self, child: QueryStats) {
+        self.merge(QueryStats {
+            max_depth: child.max_depth + 1,
+            ..child
+        });
+    }
+
+    /// Adds a WITH clause, each CTE body as a nested level
+    fn nest_ctes(&mut self, with_clause: &Option<Vec<Cte>>) {
+        for cte in with_clause.iter().flatten() {
+            self.ctes += 1;
+            self.nest(cte_body_stats(&cte.body));
+        }
+    }
+
+    /// Adds each subquery as a nested level
+    fn nest_subqueries(&mut self, subqueries: Vec<&Query>) {
+        for query in subqueries {
+            self.subqueries += 1;
+            self.nest(query.stats());
+        }
+    }
+}
+
+impl<'a> Query<'a> {
+    /// Counts the joins, subqueries, CTEs and set-operation branches of this query and measures
+    /// how deeply it nests. See [`QueryStats`].
+    pub fn stats(&self) -> QueryStats {
+        let mut stats = QueryStats {
+            joins: self.joins.len(),
+            ..QueryStats::default()
+        };
+        stats.nest_ctes(&self.with_clause);
+
+        let mut subqueries = Vec::new();
+        if let Some(select) = &self.select {
+            select_subqueries(&select.cols, &mut subqueries);
+        }
+        if let Some(from) = &self.from {
+            source_subqueries(from, &mut subqueries);
+        }
+        for join in &self.joins {
+            source_subqueries(&join.source, &mut subqueries);
+            if let Some(on) = &join.on {
+                term_subqueries(on, &mut subqueries);
+            }
+        }
+        if let Some(where_clause) = &self.where_clause {
+            term_subqueries(where_clause, &mut subqueries);
+        }
+        for term in self.group_by.iter().flatten() {
+            term_subqueries(term, &mut subqueries);
+        }
+        if let Some(having) = &self.having {
+            term_subqueries(&having.term, &mut subqueries);
+        }
+        stats.nest_subqueries(subqueries);
+        stats
+    }
+}
+
+impl<'a> SetBranch<'a> {
+    /// Returns the stats of the branch; a TABLE statement has no joins, CTEs or subqueries.
+    pub fn stats(&self) -> QueryStats {
+        match self {
+            SetBranch::Select(query) => query.stats(),
+            SetBranch::Table(_) => QueryStats::default(),
+        }
+    }
+}
+
+impl<'a> SetQuery<'a> {
+    /// Counts the branches of this compound query together with the joins, subqueries and CTEs
+    /// inside them. The branches sit at the top level, so they do not add to the nesting depth.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let compound = table_stmt("a").union(table_stmt("b")).union_all(table_stmt("c"));
+    /// assert_eq!(compound.stats().union_branches, 3);
+    /// assert_eq!(compound.stats().max_depth, 0);
+    /// ```
+    pub fn stats(&self) -> QueryStats {
+        let mut stats = self.first.stats();
+        for (_, branch) in &self.rest {
+            stats.merge(branch.stats());
+        }
+        stats.union_branches += 1 + self.rest.len();
+        stats
+    }
+}
+
+/// Returns the stats of a CTE body, counting the CTEs and subqueries of a data-modifying
+/// statement
+fn cte_body_stats(body: &CteBody) -> QueryStats {
+    let mut stats = QueryStats::default();
+    let mut subqueries = Vec::new();
+    match body {
+        CteBody::Select(query) => return query.stats(),
+        CteBody::Table(_) => {}
+        CteBody::Insert(insert) => {
+            stats.nest_ctes(&insert.with_clause);
+            if let InsertSource::Select(query) = &insert.source {
+                stats.merge(query.stats());
+            }
+        }
+        CteBody::Update(update) => {
+            stats.nest_ctes(&update.with_clause);
+            for from in &update.from {
+                source_subqueries(from, &mut subqueries);
+            }
+            if let Some(where_clause) = &update.where_clause {
+                term_subqueries(where_clause, &mut subqueries);
+            }
+        }
+        CteBody::Delete(delete) => {
+            stats.nest_ctes(&delete.with_clause);
+            for using in &delete.using {
+                source_subqueries(using, &mut subqueries);
+            }
+            if let Some(where_clause) = &delete.where_clause {
+                term_subqueries(where_clause, &mut subqueries);
+            }
+        }
+    }
+    stats.nest_subqueries(subqueries);
+    stats
+}
+
+/// Collects the subqueries directly inside a select list.
+fn select_subqueries<'q, 'a>(cols: &'q Columns<'a>, out: &mut Vec<&'q Query<'a>>) {
+    if let Columns::Expressions(exprs) = cols {
+        for expr in exprs {
+            match expr {
+                SelectExpression::Subquery(query, _) => out.push(query),
+                SelectExpression::Expr(term, _) => term_subqueries(term, out),
+                SelectExpression::Column(_)
+                | SelectExpression::Star
+                | SelectExpression::TableStar(_) => {}
+            }
+        }
+    }
+}
+
+/// Collects the subqueries directly inside a FROM or JOIN source.
+fn source_subqueries<'q, 'a>(source: &'q FromSource<'a>, out: &mut Vec<&'q Query<'a>>) {
+    match source {
+        FromSource::Subquery(query, _) | FromSource::AliasedSubquery { query, .. } => {
+            out.push(query)
+        }
+        FromSource::FunctionCall { func, .. } => term_subqueries(func, out),
+        FromSource::Table(_)
+        | FromSource::Sampled(..)
+        | FromSource::Only(_)
+        | FromSource::WithDescendants(_)
+        | FromSource::Qualified(..) => {}
+    }
+}
+
+/// Collects the subqueries directly inside `term`, without descending into them.
+fn term_subqueries<'q, 'a>(term: &'q Term<'a>, out: &mut Vec<&'q Query<'a>>) {
+    walk_terms(term, |t| {
+        out.extend(t.subquery());
+        true
+    });
+}
@@ -0,0 +1,135 @@
+use crate::{
+    Columns, FromSource, LockClause, OrderBy, OrderedColumn, Params, Query, Select, SetBranch,
+    SetOperator, SetQuery, Sql,
+};
+
+/// The TableStmt struct is a `TABLE name` statement, PostgreSQL's shorthand for
+/// `SELECT * FROM name`.
+///
+/// Besides the table it only accepts ORDER BY, LIMIT, OFFSET and locking clauses, which is all
+/// the shorthand allows. It can be used on its own, as a CTE body or as a branch of a compound
+/// query; converting it into a Query gives the equivalent `SELECT * FROM name ...`.
+///
+/// # Example
+/// ```
+/// use squeal::*;
+/// let stmt = table_stmt("events").order_by(vec![OrderedColumn::Desc("id")]).limit(5);
+/// assert_eq!(stmt.sql(), "TABLE events ORDER BY id DESC LIMIT 5");
+/// let query: Query = stmt.into();
+/// assert_eq!(query.sql(), "SELECT * FROM events ORDER BY id DESC LIMIT 5");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct TableStmt<'a> {
+    /// The table to read
+    pub table: &'a str,
+    /// The ORDER BY clause, if any
+    pub order_by: Option<OrderBy<'a>>,
+    /// The maximum number of rows to return
+    pub limit: Option<u64>,
+    /// The number of rows to skip
+    pub offset: Option<u64>,
+    /// The row-locking clauses, rendered in order
+    pub locks: Vec<LockClause<'a>>,
+}
+
+impl<'a> TableStmt<'a> {
+    /// Creates a `TABLE name` statement reading every row of the table
+    pub fn new(table: &'a str) -> TableStmt<'a> {
+        TableStmt {
+            table,
+            order_by: None,
+            limit: None,
+            offset: None,
+            locks: vec![],
+        }
+    }
+
+    /// Sets the ORDER BY clause
+    pub fn order_by(mut self, columns: Vec<OrderedColumn<'a>>) -> TableStmt<'a> {
+        self.order_by = Some(OrderBy { columns });
+        self
+    }
+
+    /// Sets the LIMIT clause
+    pub fn limit(mut self, limit: u64) -> TableStmt<'a> {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the OFFSET clause
+    pub fn offset(mut self, offset: u64) -> TableStmt<'a> {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Adds a locking clause
+    pub fn lock(mut self, clause: LockClause<'a>) -> TableStmt<'a> {
+        self.locks.push(clause);
+        self
+    }
+
+    /// Starts a compound query with this statement as the first branch
+    pub fn combine(self, op: SetOperator, other: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        SetQuery::new(self).combine(op, other)
+    }
+
+    /// Combines this statement with another branch using UNION
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// assert_eq!(
+    ///     table_stmt("a").union(table_stmt("b")).sql(),
+    ///     "(TABLE a) UNION (TABLE b)"
+    /// );
+    /// ```
+    pub fn union(self, other: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        self.combine(SetOperator::Union, other)
+    }
+
+    /// Combines this statement with another branch using UNION ALL
+    pub fn union_all(self, other: impl Into<SetBranch<'a>>) -> SetQuery<'a> {
+        self.combine(SetOperator::UnionAll, other)
+    }
+}
+
+impl<'a> Sql for TableStmt<'a> {
+    fn sql(&self) -> String {
+        let mut result = format!("TABLE {}", self.table);
+        if let Some(order_by) = &self.order_by {
+            result.push_str(&format!(" ORDER BY {}", order_by.columns_sql()));
+        }
+        if let Some(limit) = self.limit {
+            result.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            result.push_str(&format!(" OFFSET {}", offset));
+        }
+        for lock in &self.locks {
+            result.push_str(&format!(" {}", lock.sql()));
+        }
+        result
+    }
+}
+
+impl<'a> From<TableStmt<'a>> for Query<'a> {
+    fn from(stmt: TableStmt<'a>) -> Self {
+        Query {
+            with_clause: None,
+            select: Some(Select::new(Columns::Star, None)),
+            from: Some(FromSource::Table(stmt.table)),
+            joins: vec![],
+            where_clause: None,
+            group_by: None,
+            having: None,
+            window_defs: vec![],
+            order_by: stmt.order_by,
+            limit: stmt.limit,
+            offset: stmt.offset,
+            fetch_first: None,
+            locks: stmt.locks,
+            comment: None,
+            params: Params::default(),
+        }
+    }
+}
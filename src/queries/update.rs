@@ -1,29 +1,38 @@
-use crate::{Columns, Parameterized, PgParams, Sql, Term};
+use crate::{
+    BuildError, Columns, Cte, CteBody, FromSource, ParamStyle, Parameterized, Params, PgParams,
+    Query, Sql, TargetTable, Term, Value, and, push_with_clause,
+};
 
 /// The Update struct is used to specify an update query.
 /// The user is expect to construct the Update object and then call the sql() method to
 /// get the SQL string.
 ///
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Update<'a> {
-    /// The table name for the update clause.
-    pub table: &'a str,
+    /// WITH clause (Common Table Expressions)
+    pub with_clause: Option<Vec<Cte<'a>>>,
+    /// The table to update.
+    pub table: TargetTable<'a>,
     /// The columns to update.
     pub columns: Vec<&'a str>,
     /// The values to update.
     pub values: Vec<&'a str>,
     /// A table expression allowing columns from other tables to appear in the WHERE condition and
-    /// update expressions. -- pg 16 docs.
-    pub from: Option<&'a str>,
+    /// update expressions. -- pg 16 docs. Multiple sources are rendered comma-separated.
+    pub from: Vec<FromSource<'a>>,
     /// The conditions for the where clause, if it exists.
     pub where_clause: Option<Term<'a>>,
     /// The columns to return, if any
     pub returning: Option<Columns<'a>>,
+    /// The placeholders handed out by the builder, including those of nested statements.
+    pub params: Params,
 }
 
 impl<'a> Sql for Update<'a> {
     fn sql(&self) -> String {
-        let mut result = format!("UPDATE {} SET ", self.table);
+        let mut result = String::new();
+        push_with_clause(&mut result, &self.with_clause);
+        result.push_str(&format!("UPDATE {} SET ", self.table.sql()));
         let mut first = true;
         for (c, v) in self.columns.iter().zip(self.values.iter()) {
             if !first {
@@ -32,8 +41,9 @@ impl<'a> Sql for Update<'a> {
             first = false;
             result.push_str(&format!("{} = {}", c, v));
         }
-        if let Some(from) = &self.from {
-            result.push_str(&format!(" FROM {}", from));
+        if !self.from.is_empty() {
+            let sources: Vec<String> = self.from.iter().map(|f| f.sql()).collect();
+            result.push_str(&format!(" FROM {}", sources.join(", ")));
         }
         if let Some(conditions) = &self.where_clause {
             result.push_str(&format!(" WHERE {}", conditions.sql()));
@@ -41,7 +51,28 @@ impl<'a> Sql for Update<'a> {
         if let Some(returning) = &self.returning {
             result.push_str(&format!(" RETURNING {}", returning.sql()));
         }
-        result
+        self.params.finish(result)
+    }
+}
+
+impl<'a> Update<'a> {
+    /// Renders the SQL and returns it together with the values bound with bind(), ordered by
+    /// placeholder number. Placeholders written by hand with `p(n)` carry no value, and mixing
+    /// param() with bind() is rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ub = U("users");
+    /// let name = ub.bind("Eve");
+    /// let id = ub.bind(5);
+    /// let (sql, values) = ub.set(vec![("name", &name)]).where_(eq("id", &id)).build().render().unwrap();
+    /// assert_eq!(sql, "UPDATE users SET name = $1 WHERE id = $2");
+    /// assert_eq!(values, vec![Value::Text("Eve".to_string()), Value::Int(5)]);
+    /// ```
+    pub fn render(mut self) -> Result<(String, Vec<Value>), BuildError> {
+        let params = std::mem::take(&mut self.params);
+        params.render(self.sql())
     }
 }
 
@@ -49,12 +80,14 @@ impl<'a> Sql for Update<'a> {
 /// It is not intended to be used directly, but rather through the U() function.
 /// See the integration_test.rs for an example of usage.
 pub struct UpdateBuilder<'a> {
-    table: &'a str,
+    with_clause: Option<Vec<Cte<'a>>>,
+    table: TargetTable<'a>,
     columns: Vec<&'a str>,
     values: Vec<&'a str>,
-    from: Option<&'a str>,
+    from: Vec<FromSource<'a>>,
     where_clause: Option<Term<'a>>,
     returning: Option<Columns<'a>>,
+    all: bool,
     params: PgParams,
 }
 
@@ -65,8 +98,7 @@ pub struct UpdateBuilder<'a> {
 /// # Example
 /// ```
 /// use squeal::*;
-/// let mut u = U("table");
-/// let result = u
+/// let result = U("table")
 ///   .columns(vec!["a", "b"])
 ///   .values(vec!["1", "2"])
 ///   .where_(Term::Condition(
@@ -80,12 +112,14 @@ pub struct UpdateBuilder<'a> {
 #[allow(non_snake_case)]
 pub fn U<'a>(table: &'a str) -> UpdateBuilder<'a> {
     UpdateBuilder {
-        table,
+        with_clause: None,
+        table: table.into(),
         columns: Vec::new(),
         values: Vec::new(),
-        from: None,
+        from: Vec::new(),
         where_clause: None,
         returning: None,
+        all: false,
         params: PgParams::new(),
     }
 }
@@ -93,7 +127,7 @@ impl<'a> UpdateBuilder<'a> {
     /// Sets column-value pairs for the UPDATE statement
     /// This is more ergonomic than using separate columns() and values() methods
     /// as it keeps column-value pairs together, preventing mismatches.
-    pub fn set(&'a mut self, pairs: Vec<(&'a str, &'a str)>) -> &'a mut UpdateBuilder<'a> {
+    pub fn set(&mut self, pairs: Vec<(&'a str, &'a str)>) -> &mut UpdateBuilder<'a> {
         for (col, val) in pairs {
             self.columns.push(col);
             self.values.push(val);
@@ -101,16 +135,51 @@ impl<'a> UpdateBuilder<'a> {
         self
     }
 
+    /// Adds a CTE to the WITH clause. Besides a SELECT, it may be a data-modifying statement
+    /// whose RETURNING rows feed this one.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let archived = D("orders").where_(lt("placed_at", "'2020-01-01'")).returning(Columns::Selected(vec!["customer_id"])).build();
+    /// let update = U("customers")
+    ///     .with("archived", archived)
+    ///     .set(vec![("has_archive", "true")])
+    ///     .from("archived")
+    ///     .where_(eq("customers.id", "archived.customer_id"))
+    ///     .build();
+    /// assert_eq!(
+    ///     update.sql(),
+    ///     "WITH archived AS (DELETE FROM orders WHERE placed_at < '2020-01-01' RETURNING customer_id) \
+    ///      UPDATE customers SET has_archive = true FROM archived WHERE customers.id = archived.customer_id"
+    /// );
+    /// ```
+    pub fn with(&mut self, name: &'a str, body: impl Into<CteBody<'a>>) -> &mut UpdateBuilder<'a> {
+        self.with_cte(Cte {
+            name,
+            body: body.into(),
+            materialized: None,
+        })
+    }
+
+    /// Adds an already-formed CTE to the WITH clause
+    pub fn with_cte(&mut self, cte: Cte<'a>) -> &mut UpdateBuilder<'a> {
+        match &mut self.with_clause {
+            None => self.with_clause = Some(vec![cte]),
+            Some(ctes) => ctes.push(cte),
+        }
+        self
+    }
+
     /// Sets the columns to update (use with values())
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ub = U("users");
-    /// let update = ub.columns(vec!["name"]).values(vec!["'David'"]).build();
+    /// let update = U("users").columns(vec!["name"]).values(vec!["'David'"]).build();
     /// assert_eq!(update.sql(), "UPDATE users SET name = 'David'");
     /// ```
-    pub fn columns(&'a mut self, columns: Vec<&'a str>) -> &'a mut UpdateBuilder<'a> {
+    pub fn columns(&mut self, columns: Vec<&'a str>) -> &mut UpdateBuilder<'a> {
         for c in columns {
             self.columns.push(c);
         }
@@ -121,27 +190,91 @@ impl<'a> UpdateBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ub = U("users");
-    /// let update = ub.columns(vec!["email"]).values(vec!["'new@example.com'"]).build();
+    /// let update = U("users").columns(vec!["email"]).values(vec!["'new@example.com'"]).build();
     /// assert_eq!(update.sql(), "UPDATE users SET email = 'new@example.com'");
     /// ```
-    pub fn values(&'a mut self, values: Vec<&'a str>) -> &'a mut UpdateBuilder<'a> {
+    pub fn values(&mut self, values: Vec<&'a str>) -> &mut UpdateBuilder<'a> {
         for v in values {
             self.values.push(v);
         }
         self
     }
-    /// Sets the FROM clause for PostgreSQL UPDATE...FROM syntax
+    /// Adds a table to the FROM clause for PostgreSQL UPDATE...FROM syntax
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ub = U("users");
-    /// let update = ub.set(vec![("active", "false")]).from("banned").where_(eq("users.id", "banned.user_id")).build();
+    /// let update = U("users").set(vec![("active", "false")]).from("banned").where_(eq("users.id", "banned.user_id")).build();
     /// assert_eq!(update.sql(), "UPDATE users SET active = false FROM banned WHERE users.id = banned.user_id");
     /// ```
-    pub fn from(&'a mut self, from: &'a str) -> &'a mut UpdateBuilder<'a> {
-        self.from = Some(from);
+    pub fn from(&mut self, from: &'a str) -> &mut UpdateBuilder<'a> {
+        self.from.push(FromSource::Table(from));
+        self
+    }
+    /// Adds several tables to the FROM clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let update = U("orders").set(vec![("region", "r.name")])
+    ///     .from_sources(vec!["customers c", "regions r"])
+    ///     .where_(and(eq("orders.customer_id", "c.id"), eq("c.region_id", "r.id")))
+    ///     .build();
+    /// assert_eq!(
+    ///     update.sql(),
+    ///     "UPDATE orders SET region = r.name FROM customers c, regions r WHERE orders.customer_id = c.id AND c.region_id = r.id"
+    /// );
+    /// ```
+    pub fn from_sources(&mut self, tables: Vec<&'a str>) -> &mut UpdateBuilder<'a> {
+        self.from.extend(tables.into_iter().map(FromSource::Table));
+        self
+    }
+    /// Adds an aliased subquery to the FROM clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut qb = Q();
+    /// let totals = qb.select(vec!["customer_id", "sum(amount) AS total"])
+    ///     .from("payments")
+    ///     .group_by(vec!["customer_id"])
+    ///     .build();
+    /// let update = U("customers").set(vec![("lifetime_value", "s.total")])
+    ///     .from_subquery(totals, "s")
+    ///     .where_(eq("customers.id", "s.customer_id"))
+    ///     .build();
+    /// assert_eq!(
+    ///     update.sql(),
+    ///     "UPDATE customers SET lifetime_value = s.total FROM (SELECT customer_id, sum(amount) AS total FROM payments GROUP BY customer_id) AS s WHERE customers.id = s.customer_id"
+    /// );
+    /// ```
+    pub fn from_subquery(&mut self, query: Query<'a>, alias: &'a str) -> &mut UpdateBuilder<'a> {
+        self.from.push(FromSource::Subquery(Box::new(query), alias));
+        self
+    }
+    /// Qualifies the target table with a schema
+    pub fn schema(&mut self, schema: &'a str) -> &mut UpdateBuilder<'a> {
+        self.table.schema = Some(schema);
+        self
+    }
+    /// Aliases the target table, so FROM and WHERE can refer to it unambiguously
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let update = U("orders").schema("sales")
+    ///     .alias("o")
+    ///     .set(vec![("status", "'void'")])
+    ///     .from("customers c")
+    ///     .where_(and(eq("o.customer_id", "c.id"), eq("c.banned", "true")))
+    ///     .build();
+    /// assert_eq!(
+    ///     update.sql(),
+    ///     "UPDATE sales.orders AS o SET status = 'void' FROM customers c WHERE o.customer_id = c.id AND c.banned = true"
+    /// );
+    /// ```
+    pub fn alias(&mut self, alias: &'a str) -> &mut UpdateBuilder<'a> {
+        self.table.alias = Some(alias);
         self
     }
     /// Sets the WHERE clause
@@ -149,24 +282,58 @@ impl<'a> UpdateBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ub = U("users");
-    /// let update = ub.set(vec![("active", "false")]).where_(eq("id", "5")).build();
+    /// let update = U("users").set(vec![("active", "false")]).where_(eq("id", "5")).build();
     /// assert_eq!(update.sql(), "UPDATE users SET active = false WHERE id = 5");
     /// ```
-    pub fn where_(&'a mut self, term: Term<'a>) -> &'a mut UpdateBuilder<'a> {
+    pub fn where_(&mut self, term: Term<'a>) -> &mut UpdateBuilder<'a> {
         self.where_clause = Some(term);
         self
     }
+
+    /// Sets the WHERE clause only if the Option contains Some value
+    /// Useful for conditional/dynamic statement building
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let update = U("users").set(vec![("active", "false")]).where_opt(None).build();
+    /// assert_eq!(update.sql(), "UPDATE users SET active = false");
+    /// ```
+    pub fn where_opt(&mut self, term: Option<Term<'a>>) -> &mut UpdateBuilder<'a> {
+        if let Some(t) = term {
+            self.where_clause = Some(t);
+        }
+        self
+    }
+
+    /// Adds a condition to the WHERE clause with AND
+    /// If no WHERE clause exists yet, this becomes the first condition
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let update = U("users").set(vec![("active", "false")])
+    ///     .and_where(eq("tenant_id", "3"))
+    ///     .and_where(lt("last_login", "'2020-01-01'"))
+    ///     .build();
+    /// assert_eq!(update.sql(), "UPDATE users SET active = false WHERE tenant_id = 3 AND last_login < '2020-01-01'");
+    /// ```
+    pub fn and_where(&mut self, term: Term<'a>) -> &mut UpdateBuilder<'a> {
+        self.where_clause = Some(match self.where_clause.take() {
+            None => term,
+            Some(existing) => and(existing, term),
+        });
+        self
+    }
     /// Sets the RETURNING clause
     ///
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ub = U("users");
-    /// let update = ub.set(vec![("status", "'active'")]).returning(Columns::Selected(vec!["id", "status"])).build();
+    /// let update = U("users").set(vec![("status", "'active'")]).returning(Columns::Selected(vec!["id", "status"])).build();
     /// assert_eq!(update.sql(), "UPDATE users SET status = 'active' RETURNING id, status");
     /// ```
-    pub fn returning(&'a mut self, columns: Columns<'a>) -> &'a mut UpdateBuilder<'a> {
+    pub fn returning(&mut self, columns: Columns<'a>) -> &mut UpdateBuilder<'a> {
         self.returning = Some(columns);
         self
     }
@@ -175,24 +342,113 @@ impl<'a> UpdateBuilder<'a> {
     /// # Example
     /// ```
     /// use squeal::*;
-    /// let mut ub = U("users");
-    /// let update = ub.set(vec![("name", "'Eve'")]).build();
+    /// let update = U("users").set(vec![("name", "'Eve'")]).build();
     /// assert_eq!(update.sql(), "UPDATE users SET name = 'Eve'");
     /// ```
     pub fn build(&self) -> Update<'a> {
-        Update {
-            table: self.table,
+        let mut update = Update {
+            with_clause: self.with_clause.clone(),
+            table: self.table.clone(),
             columns: self.columns.clone(),
             values: self.values.clone(),
-            from: self.from,
+            from: self.from.clone(),
             where_clause: self.where_clause.clone(),
             returning: self.returning.clone(),
+            params: self.params.params(),
+        };
+        let mut params = std::mem::take(&mut update.params);
+        for cte in update.with_clause.iter_mut().flatten() {
+            params.adopt_cte(&mut cte.body);
+        }
+        for source in &mut update.from {
+            params.adopt_source(source);
+        }
+        if let Some(where_clause) = &mut update.where_clause {
+            params.adopt_term(where_clause);
+        }
+        if let Some(returning) = &mut update.returning {
+            params.adopt_columns(returning);
         }
+        update.params = params;
+        update
+    }
+
+    /// Builds the final Update statement, refusing to produce an UPDATE without a WHERE clause
+    /// unless all() was called.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let result = U("users").set(vec![("active", "false")]).try_build();
+    /// assert_eq!(
+    ///     result.err(),
+    ///     Some(BuildError::MissingWhere { table: "users".to_string() })
+    /// );
+    /// ```
+    pub fn try_build(&self) -> Result<Update<'a>, BuildError> {
+        if self.where_clause.is_none() && !self.all {
+            return Err(BuildError::MissingWhere {
+                table: self.table.name.to_string(),
+            });
+        }
+        Ok(self.build())
+    }
+
+    /// Explicitly opts in to updating every row, so try_build() accepts a missing WHERE clause
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let update = U("users").set(vec![("active", "false")]).all().try_build().unwrap();
+    /// assert_eq!(update.sql(), "UPDATE users SET active = false");
+    /// ```
+    pub fn all(&mut self) -> &mut UpdateBuilder<'a> {
+        self.all = true;
+        self
+    }
+
+    /// Sets the style placeholders are rendered in. Placeholders are numbered internally and
+    /// only converted when the statement is rendered, so it can be called at any point, and
+    /// render() orders the values by where the `?` placeholders appear.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ub = U("users");
+    /// ub.param_style(ParamStyle::Question);
+    /// let name = ub.param();
+    /// let id = ub.param();
+    /// let update = ub.set(vec![("name", &name)]).where_(eq("id", &id)).build();
+    /// assert_eq!(update.sql(), "UPDATE users SET name = ? WHERE id = ?");
+    /// ```
+    pub fn param_style(&mut self, style: ParamStyle) -> &mut UpdateBuilder<'a> {
+        self.params.set_style(style);
+        self
+    }
+
+    /// Returns the next placeholder and records `value` as the value bound to it.
+    /// The recorded values are returned in placeholder order by the built statement's render().
+    pub fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.params.slot(Some(value.into()))
+    }
+
+    /// Like bind(), but returns the placeholder cast to `type_name`, as in `$1::uuid`.
+    ///
+    /// # Example
+    /// ```
+    /// use squeal::*;
+    /// let mut ub = U("users");
+    /// let id = ub.bind_as("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f", "uuid");
+    /// let update = ub.set(vec![("active", "false")]).where_(eq("id", &id)).build();
+    /// assert_eq!(update.sql(), "UPDATE users SET active = false WHERE id = $1::uuid");
+    /// ```
+    pub fn bind_as(&mut self, value: impl Into<Value>, type_name: &str) -> String {
+        format!("{}::{}", self.bind(value), type_name)
     }
 }
 
 impl<'a> Parameterized for UpdateBuilder<'a> {
     fn param(&mut self) -> String {
-        self.params.seq()
+        self.params.slot(None)
     }
 }
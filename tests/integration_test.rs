@@ -27,18 +27,22 @@ fn test_complicated_query_builder() {
             Op::O("<>"),
             Box::new(Term::Atom("b")),
         )),
-        group_by: Some(vec!["a", "b"]),
+        group_by: Some(vec![Term::Atom("a"), Term::Atom("b")]),
         having: Some(Having::new(Term::Condition(
             Box::new(Term::Atom("a")),
             Op::O("<>"),
             Box::new(Term::Atom("b")),
         ))),
+        window_defs: vec![],
         order_by: Some(OrderBy {
             columns: vec![OrderedColumn::Asc("a"), OrderedColumn::Desc("b")],
         }),
         limit: Some(19),
         offset: Some(10),
-        for_update: true,
+        fetch_first: None,
+        locks: vec![LockClause::new(LockStrength::Update)],
+        comment: None,
+        params: Params::default(),
     }
     .sql();
     assert_eq!(
@@ -78,8 +82,7 @@ fn test_fluent_query() {
 
 #[test]
 fn test_fluent_update() {
-    let mut u = U("table_table");
-    let result = u
+    let result = U("table_table")
         .columns(vec!["last_read", "last_write"])
         .values(vec!["now()", "now()"])
         .where_(Term::Condition(
@@ -98,8 +101,7 @@ fn test_fluent_update() {
 
 #[test]
 fn test_fluent_delete() {
-    let mut d = D("table_table");
-    let result = d
+    let result = D("table_table")
         .where_(Term::Condition(
             Box::new(Term::Atom("id")),
             Op::Equals,
@@ -270,10 +272,14 @@ fn test_subquery_in_where_with_in() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = in_subquery("id", subquery).sql();
     assert_eq!(result, "id IN (SELECT user_id FROM orders)");
@@ -289,10 +295,14 @@ fn test_subquery_in_where_direct() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = Term::Subquery(Box::new(subquery)).sql();
     assert_eq!(result, "(SELECT user_id FROM orders)");
@@ -308,10 +318,14 @@ fn test_exists_operator() {
         where_clause: Some(eq("orders.user_id", "users.id")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = exists(subquery).sql();
     assert_eq!(
@@ -330,10 +344,14 @@ fn test_not_exists_operator() {
         where_clause: Some(eq("banned_users.id", "users.id")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = not_exists(subquery).sql();
     assert_eq!(
@@ -352,10 +370,14 @@ fn test_any_operator() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = any("our_price", Op::LessThan, subquery).sql();
     assert_eq!(
@@ -374,10 +396,14 @@ fn test_all_operator() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = all("our_price", Op::LessThan, subquery).sql();
     assert_eq!(
@@ -396,10 +422,14 @@ fn test_subquery_in_from_clause() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let result = FromSource::Subquery(Box::new(subquery), "active_users").sql();
     assert_eq!(
@@ -418,10 +448,14 @@ fn test_subquery_in_from_with_builder() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let result = qb
@@ -445,10 +479,14 @@ fn test_subquery_in_select_clause() {
         where_clause: Some(eq("orders.user_id", "users.id")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let expr = SelectExpression::Subquery(Box::new(subquery), Some("order_count"));
     assert_eq!(
@@ -467,10 +505,14 @@ fn test_subquery_in_select_clause_no_alias() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let expr = SelectExpression::Subquery(Box::new(subquery), None);
     assert_eq!(expr.sql(), "(SELECT COUNT(*) FROM orders)");
@@ -486,10 +528,14 @@ fn test_full_query_with_select_subquery() {
         where_clause: Some(eq("orders.user_id", "users.id")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let result = qb
@@ -517,10 +563,14 @@ fn test_complex_nested_query() {
         where_clause: Some(eq("orders.user_id", "u.id")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let from_subquery = Query {
@@ -531,10 +581,14 @@ fn test_complex_nested_query() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let mut qb = Q();
@@ -560,10 +614,14 @@ fn test_nested_subquery_in_where() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let outer_subquery = Query {
@@ -574,10 +632,14 @@ fn test_nested_subquery_in_where() {
         where_clause: Some(in_subquery("category_id", inner_subquery)),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let result = in_subquery("id", outer_subquery).sql();
@@ -983,11 +1045,13 @@ fn test_query_builder_param() {
 #[test]
 fn test_insert_direct() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["name", "email"],
         source: InsertSource::Values(vec![vec!["'John'", "'john@example.com'"]]),
         on_conflict: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -998,11 +1062,13 @@ fn test_insert_direct() {
 #[test]
 fn test_insert_with_returning() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["name"],
         source: InsertSource::Values(vec![vec!["'Alice'"]]),
         on_conflict: None,
         returning: Some(Columns::Star),
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -1013,11 +1079,13 @@ fn test_insert_with_returning() {
 #[test]
 fn test_insert_with_returning_columns() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["name"],
         source: InsertSource::Values(vec![vec!["'Bob'"]]),
         on_conflict: None,
         returning: Some(Columns::Selected(vec!["id", "name"])),
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -1027,15 +1095,16 @@ fn test_insert_with_returning_columns() {
 
 #[test]
 fn test_insert_builder_basic() {
-    let mut ib = I("users");
-    let insert = ib.columns(vec!["name"]).values(vec!["'Charlie'"]).build();
+    let insert = I("users")
+        .columns(vec!["name"])
+        .values(vec!["'Charlie'"])
+        .build();
     assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES ('Charlie')");
 }
 
 #[test]
 fn test_insert_builder_returning() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["name"])
         .values(vec!["'David'"])
         .returning(Columns::Star)
@@ -1071,17 +1140,23 @@ fn test_insert_select_direct() {
         where_clause: Some(eq("status", "'active'")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let insert = Insert {
+        with_clause: None,
         table: "archived_users",
         columns: vec!["name", "email"],
         source: InsertSource::Select(Box::new(select_query)),
         on_conflict: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -1099,13 +1174,16 @@ fn test_insert_select_builder() {
         where_clause: Some(eq("archived", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: Some(100),
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
-    let mut ib = I("archive");
-    let insert = ib.columns(vec!["*"]).select(select_query).build();
+    let insert = I("archive").columns(vec!["*"]).select(select_query).build();
     assert_eq!(
         insert.sql(),
         "INSERT INTO archive (*) SELECT * FROM old_data WHERE archived = true LIMIT 100"
@@ -1125,13 +1203,16 @@ fn test_insert_select_with_returning() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
-    let mut ib = I("completed_transactions");
-    let insert = ib
+    let insert = I("completed_transactions")
         .columns(vec!["user_id", "amount"])
         .select(select_query)
         .returning(Columns::Selected(vec!["id", "user_id"]))
@@ -1148,6 +1229,7 @@ fn test_create_table_direct() {
     let create = CreateTable {
         table: "users",
         columns: vec!["id serial PRIMARY KEY".to_string(), "name text".to_string()],
+        suffix: None,
     };
     assert_eq!(
         create.sql(),
@@ -1193,9 +1275,12 @@ fn test_drop_table_builder() {
 #[test]
 fn test_delete_direct() {
     let delete = Delete {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
+        using: vec![],
         where_clause: Some(eq("id", "10")),
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10");
 }
@@ -1203,9 +1288,12 @@ fn test_delete_direct() {
 #[test]
 fn test_delete_no_where() {
     let delete = Delete {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
+        using: vec![],
         where_clause: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(delete.sql(), "DELETE FROM users");
 }
@@ -1213,9 +1301,12 @@ fn test_delete_no_where() {
 #[test]
 fn test_delete_with_returning() {
     let delete = Delete {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
+        using: vec![],
         where_clause: Some(eq("id", "10")),
         returning: Some(Columns::Star),
+        params: Params::default(),
     };
     assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10 RETURNING *");
 }
@@ -1223,9 +1314,12 @@ fn test_delete_with_returning() {
 #[test]
 fn test_delete_with_returning_columns() {
     let delete = Delete {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
+        using: vec![],
         where_clause: Some(eq("active", "false")),
         returning: Some(Columns::Selected(vec!["id", "name", "email"])),
+        params: Params::default(),
     };
     assert_eq!(
         delete.sql(),
@@ -1235,8 +1329,10 @@ fn test_delete_with_returning_columns() {
 
 #[test]
 fn test_delete_builder_returning() {
-    let mut db = D("users");
-    let delete = db.where_(eq("id", "10")).returning(Columns::Star).build();
+    let delete = D("users")
+        .where_(eq("id", "10"))
+        .returning(Columns::Star)
+        .build();
     assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10 RETURNING *");
 }
 
@@ -1252,12 +1348,14 @@ fn test_delete_builder_param() {
 #[test]
 fn test_update_direct() {
     let update = Update {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
         columns: vec!["name", "email"],
         values: vec!["'John'", "'john@example.com'"],
-        from: None,
+        from: vec![],
         where_clause: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         update.sql(),
@@ -1268,12 +1366,14 @@ fn test_update_direct() {
 #[test]
 fn test_update_with_from() {
     let update = Update {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
         columns: vec!["active"],
         values: vec!["false"],
-        from: Some("banned"),
+        from: vec![FromSource::Table("banned")],
         where_clause: Some(eq("users.id", "banned.user_id")),
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         update.sql(),
@@ -1284,12 +1384,14 @@ fn test_update_with_from() {
 #[test]
 fn test_update_with_returning() {
     let update = Update {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
         columns: vec!["status"],
         values: vec!["'active'"],
-        from: None,
+        from: vec![],
         where_clause: None,
         returning: Some(Columns::Selected(vec!["id", "status"])),
+        params: Params::default(),
     };
     assert_eq!(
         update.sql(),
@@ -1299,8 +1401,7 @@ fn test_update_with_returning() {
 
 #[test]
 fn test_update_builder_set() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .set(vec![("name", "'Eve'"), ("email", "'eve@example.com'")])
         .build();
     assert_eq!(
@@ -1311,8 +1412,7 @@ fn test_update_builder_set() {
 
 #[test]
 fn test_update_builder_from() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .set(vec![("active", "false")])
         .from("banned")
         .where_(eq("users.id", "banned.user_id"))
@@ -1325,8 +1425,7 @@ fn test_update_builder_from() {
 
 #[test]
 fn test_update_builder_returning() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .set(vec![("status", "'active'")])
         .returning(Columns::Star)
         .build();
@@ -1363,6 +1462,7 @@ fn test_term_null() {
 #[test]
 fn test_case_multiple_when() {
     let case_expr = CaseExpression {
+        operand: None,
         when_thens: vec![
             WhenThen {
                 when: eq("x", "1"),
@@ -1421,6 +1521,7 @@ fn test_create_table_multiple_columns() {
             "name text NOT NULL".to_string(),
             "email text UNIQUE".to_string(),
         ],
+        suffix: None,
     };
     assert_eq!(
         create.sql(),
@@ -1445,11 +1546,13 @@ fn test_create_table_builder_multiple_columns() {
 #[test]
 fn test_insert_multiple_columns_direct() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["name", "email", "age"],
         source: InsertSource::Values(vec![vec!["'John'", "'john@example.com'", "30"]]),
         on_conflict: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -1460,6 +1563,7 @@ fn test_insert_multiple_columns_direct() {
 #[test]
 fn test_insert_multiple_rows_direct() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["name", "age"],
         source: InsertSource::Values(vec![
@@ -1469,6 +1573,7 @@ fn test_insert_multiple_rows_direct() {
         ]),
         on_conflict: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -1478,8 +1583,7 @@ fn test_insert_multiple_rows_direct() {
 
 #[test]
 fn test_insert_multiple_rows_builder() {
-    let mut ib = I("products");
-    let insert = ib
+    let insert = I("products")
         .columns(vec!["name", "price"])
         .rows(vec![
             vec!["'Widget'", "9.99"],
@@ -1495,8 +1599,7 @@ fn test_insert_multiple_rows_builder() {
 
 #[test]
 fn test_update_columns_and_values() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .columns(vec!["name", "email", "age"])
         .values(vec!["'Alice'", "'alice@example.com'", "25"])
         .build();
@@ -1509,12 +1612,14 @@ fn test_update_columns_and_values() {
 #[test]
 fn test_update_multiple_columns() {
     let update = Update {
-        table: "users",
+        with_clause: None,
+        table: "users".into(),
         columns: vec!["name", "email", "status"],
         values: vec!["'Bob'", "'bob@example.com'", "'active'"],
-        from: None,
+        from: vec![],
         where_clause: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         update.sql(),
@@ -1532,10 +1637,14 @@ fn test_query_empty() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), "");
 }
@@ -1550,10 +1659,14 @@ fn test_query_select_only() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), "SELECT *");
 }
@@ -1568,10 +1681,14 @@ fn test_query_from_only() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " FROM users");
 }
@@ -1586,10 +1703,14 @@ fn test_query_where_only() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " WHERE active = true");
 }
@@ -1602,12 +1723,16 @@ fn test_query_group_by_only() {
         from: None,
         joins: vec![],
         where_clause: None,
-        group_by: Some(vec!["category", "status"]),
+        group_by: Some(vec![Term::Atom("category"), Term::Atom("status")]),
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " GROUP BY category, status");
 }
@@ -1622,10 +1747,14 @@ fn test_query_having_only() {
         where_clause: None,
         group_by: None,
         having: Some(Having::new(gt("count", "5"))),
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " HAVING count > 5");
 }
@@ -1640,12 +1769,16 @@ fn test_query_order_by_only() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: Some(OrderBy {
             columns: vec![OrderedColumn::Desc("created_at")],
         }),
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " ORDER BY created_at DESC");
 }
@@ -1660,10 +1793,14 @@ fn test_query_limit_only() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: Some(10),
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " LIMIT 10");
 }
@@ -1678,10 +1815,14 @@ fn test_query_offset_only() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: Some(20),
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " OFFSET 20");
 }
@@ -1696,10 +1837,14 @@ fn test_query_for_update_only() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: true,
+        fetch_first: None,
+        locks: vec![LockClause::new(LockStrength::Update)],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(query.sql(), " FOR UPDATE");
 }
@@ -1938,12 +2083,16 @@ fn test_join_subquery() {
         from: Some(FromSource::Table("orders")),
         joins: vec![],
         where_clause: None,
-        group_by: Some(vec!["user_id"]),
+        group_by: Some(vec![Term::Atom("user_id")]),
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -1990,10 +2139,14 @@ fn test_direct_join_struct_construction() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(
         query.sql(),
@@ -2012,10 +2165,14 @@ fn test_simple_cte() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -2039,10 +2196,14 @@ fn test_multiple_ctes() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let cte2 = Query {
         with_clause: None,
@@ -2053,12 +2214,16 @@ fn test_multiple_ctes() {
         from: Some(FromSource::Table("orders")),
         joins: vec![],
         where_clause: None,
-        group_by: Some(vec!["user_id"]),
+        group_by: Some(vec![Term::Atom("user_id")]),
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -2084,10 +2249,14 @@ fn test_cte_with_join() {
         where_clause: Some(gt("created_at", "'2023-01-01'")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -2113,12 +2282,16 @@ fn test_cte_with_where_clause() {
         from: Some(FromSource::Table("transactions")),
         joins: vec![],
         where_clause: None,
-        group_by: Some(vec!["category"]),
+        group_by: Some(vec![Term::Atom("category")]),
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -2143,14 +2316,19 @@ fn test_cte_struct_sql() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let cte = Cte {
         name: "my_cte",
-        query: Box::new(cte_query),
+        body: CteBody::Select(Box::new(cte_query)),
+        materialized: None,
     };
     assert_eq!(cte.sql(), "my_cte AS (SELECT id FROM users)");
 }
@@ -2165,12 +2343,16 @@ fn test_cte_with_order_and_limit() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: Some(OrderBy {
             columns: vec![OrderedColumn::Desc("created_at")],
         }),
         limit: Some(10),
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -2189,7 +2371,7 @@ fn test_direct_cte_construction() {
     let query = Query {
         with_clause: Some(vec![Cte {
             name: "cte1",
-            query: Box::new(Query {
+            body: CteBody::Select(Box::new(Query {
                 with_clause: None,
                 select: Some(Select::new(Columns::Selected(vec!["id"]), None)),
                 from: Some(FromSource::Table("users")),
@@ -2197,11 +2379,16 @@ fn test_direct_cte_construction() {
                 where_clause: None,
                 group_by: None,
                 having: None,
+                window_defs: vec![],
                 order_by: None,
                 limit: None,
                 offset: None,
-                for_update: false,
-            }),
+                fetch_first: None,
+                locks: vec![],
+                comment: None,
+                params: Params::default(),
+            })),
+            materialized: None,
         }]),
         select: Some(Select::new(Columns::Star, None)),
         from: Some(FromSource::Table("cte1")),
@@ -2209,10 +2396,14 @@ fn test_direct_cte_construction() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     assert_eq!(
         query.sql(),
@@ -2223,8 +2414,7 @@ fn test_direct_cte_construction() {
 // ON CONFLICT / UPSERT tests
 #[test]
 fn test_on_conflict_do_nothing() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["email", "name"])
         .values(vec!["'alice@example.com'", "'Alice'"])
         .on_conflict_do_nothing(vec!["email"])
@@ -2237,8 +2427,7 @@ fn test_on_conflict_do_nothing() {
 
 #[test]
 fn test_on_conflict_do_update() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["email", "name"])
         .values(vec!["'alice@example.com'", "'Alice'"])
         .on_conflict_do_update(vec!["email"], vec![("name", "'Alice Updated'")])
@@ -2251,8 +2440,7 @@ fn test_on_conflict_do_update() {
 
 #[test]
 fn test_on_conflict_multiple_columns() {
-    let mut ib = I("products");
-    let insert = ib
+    let insert = I("products")
         .columns(vec!["sku", "name", "price"])
         .values(vec!["'ABC123'", "'Widget'", "19.99"])
         .on_conflict_do_nothing(vec!["sku", "name"])
@@ -2265,8 +2453,7 @@ fn test_on_conflict_multiple_columns() {
 
 #[test]
 fn test_on_conflict_do_update_multiple_columns() {
-    let mut ib = I("products");
-    let insert = ib
+    let insert = I("products")
         .columns(vec!["sku", "name", "price"])
         .values(vec!["'ABC123'", "'Widget'", "19.99"])
         .on_conflict_do_update(
@@ -2282,8 +2469,7 @@ fn test_on_conflict_do_update_multiple_columns() {
 
 #[test]
 fn test_on_conflict_with_returning() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["email", "name"])
         .values(vec!["'bob@example.com'", "'Bob'"])
         .on_conflict_do_update(vec!["email"], vec![("name", "'Bob Updated'")])
@@ -2316,11 +2502,13 @@ fn test_on_conflict_enum_do_update() {
 #[test]
 fn test_direct_on_conflict_construction() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["email", "name"],
         source: InsertSource::Values(vec![vec!["'test@example.com'", "'Test'"]]),
         on_conflict: Some(OnConflict::DoNothing(vec!["email"])),
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -2330,8 +2518,7 @@ fn test_direct_on_conflict_construction() {
 
 #[test]
 fn test_on_conflict_with_multiple_rows() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["email", "name"])
         .rows(vec![
             vec!["'alice@example.com'", "'Alice'"],
@@ -2348,19 +2535,20 @@ fn test_on_conflict_with_multiple_rows() {
 #[test]
 fn test_insert_single_column() {
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["name"],
         source: InsertSource::Values(vec![vec!["'Alice'"]]),
         on_conflict: None,
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(insert.sql(), "INSERT INTO users (name) VALUES ('Alice')");
 }
 
 #[test]
 fn test_insert_builder_columns_method() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["name", "email", "age"])
         .values(vec!["'Test'", "'test@example.com'", "25"])
         .build();
@@ -2389,17 +2577,23 @@ fn test_insert_with_select_and_on_conflict() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let insert = Insert {
+        with_clause: None,
         table: "users",
         columns: vec!["id", "name"],
         source: InsertSource::Select(Box::new(select_query)),
         on_conflict: Some(OnConflict::DoNothing(vec!["id"])),
         returning: None,
+        params: Params::default(),
     };
     assert_eq!(
         insert.sql(),
@@ -2418,12 +2612,16 @@ fn test_query_with_cte_and_joins() {
         from: Some(FromSource::Table("orders")),
         joins: vec![],
         where_clause: None,
-        group_by: Some(vec!["user_id"]),
+        group_by: Some(vec![Term::Atom("user_id")]),
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
     let mut qb = Q();
     let query = qb
@@ -2447,6 +2645,7 @@ fn test_query_with_cte_and_joins() {
 #[test]
 fn test_case_expression_with_else() {
     let case_expr = Term::Case(CaseExpression {
+        operand: None,
         when_thens: vec![
             WhenThen {
                 when: eq("status", "'active'"),
@@ -2490,15 +2689,20 @@ fn test_cte_with_clause() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let cte = Cte {
         name: "active_users",
-        query: Box::new(cte_query),
+        body: CteBody::Select(Box::new(cte_query)),
+        materialized: None,
     };
 
     // This exercises the Cte::sql() method at line 909
@@ -2520,10 +2724,14 @@ fn test_query_select_expressions() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let mut qb = Q();
@@ -2552,10 +2760,14 @@ fn test_query_from_subquery() {
         where_clause: Some(eq("active", "true")),
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
     let mut qb = Q();
@@ -2612,8 +2824,7 @@ fn test_query_where_opt_none() {
 // Test INSERT rows() method - covers line 251
 #[test]
 fn test_insert_rows_method() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["name", "age"])
         .rows(vec![vec!["'Alice'", "30"], vec!["'Bob'", "25"]])
         .build();
@@ -2635,14 +2846,20 @@ fn test_insert_select_method() {
         where_clause: None,
         group_by: None,
         having: None,
+        window_defs: vec![],
         order_by: None,
         limit: None,
         offset: None,
-        for_update: false,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
     };
 
-    let mut ib = I("archived_users");
-    let insert = ib.columns(vec!["name", "email"]).select(subquery).build();
+    let insert = I("archived_users")
+        .columns(vec!["name", "email"])
+        .select(subquery)
+        .build();
 
     assert_eq!(
         insert.sql(),
@@ -2653,8 +2870,7 @@ fn test_insert_select_method() {
 // Test INSERT on_conflict_do_nothing - covers line 292
 #[test]
 fn test_insert_on_conflict_do_nothing_method() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["email", "name"])
         .values(vec!["'test@example.com'", "'Test'"])
         .on_conflict_do_nothing(vec!["email"])
@@ -2669,8 +2885,7 @@ fn test_insert_on_conflict_do_nothing_method() {
 // Test INSERT on_conflict_do_update - covers line 312
 #[test]
 fn test_insert_on_conflict_do_update_method() {
-    let mut ib = I("users");
-    let insert = ib
+    let insert = I("users")
         .columns(vec!["email", "name"])
         .values(vec!["'test@example.com'", "'Test'"])
         .on_conflict_do_update(vec!["email"], vec![("name", "EXCLUDED.name")])
@@ -2685,8 +2900,7 @@ fn test_insert_on_conflict_do_update_method() {
 // Test UPDATE columns/values separately - covers lines 117, 132
 #[test]
 fn test_update_columns_values_separate() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .columns(vec!["name", "email"])
         .values(vec!["'John'", "'john@example.com'"])
         .build();
@@ -2700,8 +2914,7 @@ fn test_update_columns_values_separate() {
 // Test UPDATE from() - covers line 145
 #[test]
 fn test_update_from_method() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .set(vec![("active", "false")])
         .from("banned")
         .where_(eq("users.id", "banned.user_id"))
@@ -2716,8 +2929,7 @@ fn test_update_from_method() {
 // Test UPDATE where_() - covers line 158
 #[test]
 fn test_update_where_method() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .set(vec![("active", "false")])
         .where_(eq("id", "5"))
         .build();
@@ -2728,8 +2940,7 @@ fn test_update_where_method() {
 // Test UPDATE returning() - covers line 171
 #[test]
 fn test_update_returning_method() {
-    let mut ub = U("users");
-    let update = ub
+    let update = U("users")
         .set(vec![("status", "'active'")])
         .returning(Columns::Selected(vec!["id", "status"]))
         .build();
@@ -2743,8 +2954,7 @@ fn test_update_returning_method() {
 // Test DELETE where_() - covers line 75
 #[test]
 fn test_delete_where_method() {
-    let mut db = D("users");
-    let delete = db.where_(eq("id", "10")).build();
+    let delete = D("users").where_(eq("id", "10")).build();
 
     assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10");
 }
@@ -2752,8 +2962,10 @@ fn test_delete_where_method() {
 // Test DELETE returning() - covers line 89
 #[test]
 fn test_delete_returning_method() {
-    let mut db = D("users");
-    let delete = db.where_(eq("id", "10")).returning(Columns::Star).build();
+    let delete = D("users")
+        .where_(eq("id", "10"))
+        .returning(Columns::Star)
+        .build();
 
     assert_eq!(delete.sql(), "DELETE FROM users WHERE id = 10 RETURNING *");
 }
@@ -2835,3 +3047,5426 @@ fn test_order_by_sql_direct() {
     let sql = order_by.sql();
     assert_eq!(sql, "ORDER BY name ASC, created_at DESC");
 }
+
+// Placeholder validation tests
+#[test]
+fn test_validate_params_contiguous() {
+    let mut qb = Q();
+    let p1 = qb.param();
+    let p2 = qb.param();
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(eq("id", &p1), eq("status", &p2)))
+        .build();
+    assert_eq!(query.validate_params(), Ok(()));
+}
+
+#[test]
+fn test_validate_params_no_params() {
+    let mut qb = Q();
+    let query = qb.select(vec!["*"]).from("users").build();
+    assert_eq!(query.validate_params(), Ok(()));
+}
+
+#[test]
+fn test_validate_params_gap() {
+    let mut qb = Q();
+    let p1 = qb.param();
+    let p4 = p(4);
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(eq("id", &p1), eq("status", &p4)))
+        .build();
+    assert_eq!(
+        query.validate_params(),
+        Err(BuildError::InvalidParams {
+            missing: vec![2, 3],
+            duplicates: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_validate_params_duplicate() {
+    let mut qb = Q();
+    let p1 = qb.param();
+    let p1_again = p(1);
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(eq("id", &p1), eq("status", &p1_again)))
+        .build();
+    let err = query.validate_params().unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::InvalidParams {
+            missing: vec![],
+            duplicates: vec![1],
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid parameter placeholders: missing [], duplicated [1]"
+    );
+}
+
+// FETCH FIRST tests
+#[test]
+fn test_fetch_first_only() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .order_by(vec![OrderedColumn::Asc("id")])
+        .fetch_first(5, false)
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM users ORDER BY id ASC FETCH FIRST 5 ROWS ONLY"
+    );
+}
+
+#[test]
+fn test_fetch_first_with_ties_and_offset() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["name", "points"])
+        .from("scores")
+        .order_by(vec![OrderedColumn::Desc("points")])
+        .offset(10)
+        .fetch_first(5, true)
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT name, points FROM scores ORDER BY points DESC OFFSET 10 ROWS FETCH FIRST 5 ROWS WITH TIES"
+    );
+}
+
+#[test]
+fn test_fetch_first_wins_over_limit() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .limit(100)
+        .fetch_first(3, false)
+        .build();
+    assert_eq!(query.sql(), "SELECT * FROM users FETCH FIRST 3 ROWS ONLY");
+}
+
+#[test]
+fn test_fetch_first_struct() {
+    let fetch = FetchFirst {
+        count: 1,
+        with_ties: false,
+    };
+    assert_eq!(fetch.sql(), "FETCH FIRST 1 ROWS ONLY");
+}
+
+#[test]
+fn test_fetch_first_with_ties_requires_order_by() {
+    let mut qb = Q();
+    let result = qb
+        .select(vec!["*"])
+        .from("scores")
+        .fetch_first(5, true)
+        .try_build();
+    assert!(matches!(result, Err(BuildError::TiesWithoutOrderBy)));
+}
+
+#[test]
+fn test_fetch_first_with_ties_and_order_by_builds() {
+    let mut qb = Q();
+    let result = qb
+        .select(vec!["*"])
+        .from("scores")
+        .order_by(vec![OrderedColumn::Desc("points")])
+        .fetch_first(5, true)
+        .try_build();
+    assert!(result.is_ok());
+    let mut qb = Q();
+    let result = qb
+        .select(vec!["*"])
+        .from("scores")
+        .fetch_first(5, false)
+        .try_build();
+    assert!(result.is_ok());
+}
+
+// Raw fragment tests
+#[test]
+fn test_raw_borrowed() {
+    let result = raw("ST_DWithin(geom, 'POINT(0 0)', 10)").sql();
+    assert_eq!(result, "ST_DWithin(geom, 'POINT(0 0)', 10)");
+}
+
+#[test]
+fn test_raw_owned() {
+    let threshold = 42;
+    let result = raw(format!("score >= {}", threshold)).sql();
+    assert_eq!(result, "score >= 42");
+}
+
+#[test]
+fn test_raw_in_where() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("docs")
+        .where_(and(
+            eq("published", "true"),
+            raw("tsv @@ plainto_tsquery('rust')"),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM docs WHERE published = true AND tsv @@ plainto_tsquery('rust')"
+    );
+}
+
+// Deeply nested condition chains must render without overflowing the stack
+#[test]
+fn test_deep_and_chain_renders() {
+    let mut term = eq("c", "0");
+    for _ in 1..10_000 {
+        term = and(term, eq("c", "1"));
+    }
+    let sql = term.sql();
+    assert!(sql.starts_with("c = 0 AND c = 1 AND c = 1"));
+    assert_eq!(sql.matches(" AND ").count(), 9_999);
+}
+
+#[test]
+fn test_deep_and_where_chain_in_query() {
+    let mut term = eq("id", "0");
+    for _ in 1..10_000 {
+        term = or(term, eq("id", "1"));
+    }
+    let query = Query {
+        with_clause: None,
+        select: Some(Select::new(Columns::Star, None)),
+        from: Some(FromSource::Table("t")),
+        joins: vec![],
+        where_clause: Some(term),
+        group_by: None,
+        having: None,
+        window_defs: vec![],
+        order_by: None,
+        limit: None,
+        offset: None,
+        fetch_first: None,
+        locks: vec![],
+        comment: None,
+        params: Params::default(),
+    };
+    let sql = query.sql();
+    assert!(sql.starts_with("SELECT * FROM t WHERE id = 0 OR id = 1"));
+    assert!(sql.ends_with("OR id = 1"));
+}
+
+#[test]
+fn test_condition_chain_mixed_nesting() {
+    let term = and(
+        or(eq("a", "1"), eq("b", "2")),
+        parens(or(eq("c", "3"), eq("d", "4"))),
+    );
+    assert_eq!(term.sql(), "(a = 1 OR b = 2) AND (c = 3 OR d = 4)");
+}
+
+// bind() / render() tests
+#[test]
+fn test_query_bind_render() {
+    let mut qb = Q();
+    let id = qb.bind(42);
+    let name = qb.bind("bob");
+    let (sql, values) = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(eq("id", &id), eq("name", &name)))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(sql, "SELECT * FROM users WHERE id = $1 AND name = $2");
+    assert_eq!(values, vec![Value::Int(42), Value::Text("bob".to_string())]);
+}
+
+#[test]
+fn test_insert_bind_render() {
+    let mut ib = I("events");
+    let kind = ib.bind("login");
+    let ok = ib.bind(true);
+    let score = ib.bind(1.5);
+    let note = ib.bind(None::<&str>);
+    let (sql, values) = ib
+        .columns(vec!["kind", "ok", "score", "note"])
+        .values(vec![&kind, &ok, &score, &note])
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO events (kind, ok, score, note) VALUES ($1, $2, $3, $4)"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("login".to_string()),
+            Value::Bool(true),
+            Value::Float(1.5),
+            Value::Null,
+        ]
+    );
+}
+
+#[test]
+fn test_update_bind_render() {
+    let mut ub = U("users");
+    let email = ub.bind(String::from("new@example.com"));
+    let id = ub.bind(3i64);
+    let (sql, values) = ub
+        .set(vec![("email", &email)])
+        .where_(eq("id", &id))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(sql, "UPDATE users SET email = $1 WHERE id = $2");
+    assert_eq!(
+        values,
+        vec![Value::Text("new@example.com".to_string()), Value::Int(3)]
+    );
+}
+
+#[test]
+fn test_delete_bind_render() {
+    let mut db = D("sessions");
+    let user = db.bind(9);
+    let (sql, values) = db.where_(eq("user_id", &user)).build().render().unwrap();
+    assert_eq!(sql, "DELETE FROM sessions WHERE user_id = $1");
+    assert_eq!(values, vec![Value::Int(9)]);
+}
+
+#[test]
+fn test_render_without_binds() {
+    let mut qb = Q();
+    let (sql, values) = qb.select(vec!["1"]).build().render().unwrap();
+    assert_eq!(sql, "SELECT 1");
+    assert!(values.is_empty());
+}
+
+// Window function and WINDOW clause tests
+#[test]
+fn test_over_inline_spec() {
+    let spec = WindowSpec {
+        partition_by: vec!["dept"],
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Desc("salary")],
+        }),
+        frame: None,
+    };
+    let result = over(Term::Atom("row_number()"), spec).sql();
+    assert_eq!(
+        result,
+        "row_number() OVER (PARTITION BY dept ORDER BY salary DESC)"
+    );
+}
+
+#[test]
+fn test_over_empty_spec() {
+    let spec = WindowSpec {
+        partition_by: vec![],
+        order_by: None,
+        frame: None,
+    };
+    assert_eq!(over(Term::Atom("count(*)"), spec).sql(), "count(*) OVER ()");
+}
+
+#[test]
+fn test_named_window_clause() {
+    let spec = WindowSpec {
+        partition_by: vec!["region"],
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Asc("sold_at")],
+        }),
+        frame: Some(Frame::between(
+            FrameMode::Rows,
+            FrameBound::UnboundedPreceding,
+            FrameBound::CurrentRow,
+        )),
+    };
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("region"),
+            SelectExpression::Expr(over_named(Term::Atom("rank()"), "w"), Some("r")),
+            SelectExpression::Expr(
+                over_named(Term::Atom("sum(amount)"), "w"),
+                Some("running_total"),
+            ),
+        ])
+        .from("sales")
+        .group_by(vec!["region", "sold_at", "amount"])
+        .window("w", spec)
+        .order_by(vec![OrderedColumn::Asc("region")])
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT region, rank() OVER w AS r, sum(amount) OVER w AS running_total FROM sales GROUP BY region, sold_at, amount WINDOW w AS (PARTITION BY region ORDER BY sold_at ASC ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) ORDER BY region ASC"
+    );
+}
+
+#[test]
+fn test_multiple_named_windows() {
+    let by_dept = WindowSpec {
+        partition_by: vec!["dept"],
+        order_by: None,
+        frame: None,
+    };
+    let by_salary = WindowSpec {
+        partition_by: vec![],
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Desc("salary")],
+        }),
+        frame: None,
+    };
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("employees")
+        .window("d", by_dept)
+        .window("s", by_salary)
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM employees WINDOW d AS (PARTITION BY dept), s AS (ORDER BY salary DESC)"
+    );
+}
+
+// TABLESAMPLE tests
+#[test]
+fn test_table_sample_bernoulli() {
+    let sample = TableSample {
+        method: SampleMethod::Bernoulli,
+        fraction: 10.0,
+        repeatable: None,
+    };
+    assert_eq!(
+        FromSource::Sampled("big_table", sample).sql(),
+        "big_table TABLESAMPLE BERNOULLI (10)"
+    );
+}
+
+#[test]
+fn test_table_sample_system_repeatable_fractional() {
+    let sample = TableSample {
+        method: SampleMethod::System,
+        fraction: 0.5,
+        repeatable: Some(42),
+    };
+    assert_eq!(sample.sql(), "TABLESAMPLE SYSTEM (0.5) REPEATABLE (42)");
+}
+
+#[test]
+fn test_table_sample_non_finite_fraction_renders_float8_literal() {
+    let sample = |fraction| TableSample {
+        method: SampleMethod::Bernoulli,
+        fraction,
+        repeatable: None,
+    };
+    assert_eq!(
+        sample(f64::NAN).sql(),
+        "TABLESAMPLE BERNOULLI ('NaN'::float8)"
+    );
+    assert_eq!(
+        sample(f64::INFINITY).sql(),
+        "TABLESAMPLE BERNOULLI ('Infinity'::float8)"
+    );
+    assert_eq!(
+        sample(f64::NEG_INFINITY).sql(),
+        "TABLESAMPLE BERNOULLI ('-Infinity'::float8)"
+    );
+    assert!(sample(f64::NAN) == sample(f64::NAN));
+}
+
+#[test]
+fn test_from_sampled_builder() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["avg(amount)"])
+        .from_sampled(
+            "payments",
+            TableSample {
+                method: SampleMethod::System,
+                fraction: 1.0,
+                repeatable: Some(7),
+            },
+        )
+        .where_(gt("amount", "0"))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT avg(amount) FROM payments TABLESAMPLE SYSTEM (1) REPEATABLE (7) WHERE amount > 0"
+    );
+}
+
+// ONLY / descendant table tests
+#[test]
+fn test_from_only() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from_only("parent")
+        .where_(eq("id", "1"))
+        .build();
+    assert_eq!(query.sql(), "SELECT * FROM ONLY parent WHERE id = 1");
+}
+
+#[test]
+fn test_from_with_descendants() {
+    let mut qb = Q();
+    let query = qb.select(vec!["*"]).from_with_descendants("parent").build();
+    assert_eq!(query.sql(), "SELECT * FROM parent *");
+}
+
+#[test]
+fn test_join_only_source() {
+    let join = Join {
+        join_type: JoinType::Inner,
+        source: FromSource::Only("events"),
+        on: Some(eq("users.id", "events.user_id")),
+    };
+    assert_eq!(
+        join.sql(),
+        "INNER JOIN ONLY events ON users.id = events.user_id"
+    );
+}
+
+// EXPLAIN tests
+#[test]
+fn test_explain_plain() {
+    let mut qb = Q();
+    let query = qb.select(vec!["*"]).from("users").build();
+    let explain = Explain::new(query, ExplainOptions::default());
+    assert_eq!(explain.sql(), "EXPLAIN SELECT * FROM users");
+}
+
+#[test]
+fn test_explain_all_options() {
+    let mut qb = Q();
+    let query = qb.select(vec!["*"]).from("users").build();
+    let explain = Explain::new(
+        query,
+        ExplainOptions {
+            analyze: true,
+            verbose: true,
+            buffers: true,
+            format: Some(ExplainFormat::Yaml),
+        },
+    );
+    assert_eq!(
+        explain.sql(),
+        "EXPLAIN (ANALYZE true, VERBOSE true, BUFFERS true, FORMAT yaml) SELECT * FROM users"
+    );
+}
+
+#[test]
+fn test_explain_insert_update_delete() {
+    let insert = I("users").columns(vec!["name"]).values(vec!["'a'"]).build();
+    let options = ExplainOptions {
+        format: Some(ExplainFormat::Json),
+        ..Default::default()
+    };
+    assert_eq!(
+        Explain::new(insert, options.clone()).sql(),
+        "EXPLAIN (FORMAT json) INSERT INTO users (name) VALUES ('a')"
+    );
+
+    let update = U("users")
+        .set(vec![("name", "'b'")])
+        .where_(eq("id", "1"))
+        .build();
+    assert_eq!(
+        Explain::new(update, options.clone()).sql(),
+        "EXPLAIN (FORMAT json) UPDATE users SET name = 'b' WHERE id = 1"
+    );
+
+    let delete = D("users").where_(eq("id", "1")).build();
+    assert_eq!(
+        Explain::new(delete, options).sql(),
+        "EXPLAIN (FORMAT json) DELETE FROM users WHERE id = 1"
+    );
+}
+
+#[test]
+fn test_explain_format_text_xml() {
+    assert_eq!(ExplainFormat::Text.sql(), "text");
+    assert_eq!(ExplainFormat::Xml.sql(), "xml");
+}
+
+// PREPARE / EXECUTE / DEALLOCATE tests
+#[test]
+fn test_prepare_with_arg_types() {
+    let mut qb = Q();
+    let p1 = qb.param();
+    let p2 = qb.param();
+    let query = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(and(eq("age", &p1), eq("name", &p2)))
+        .build();
+    let prepare = Prepare {
+        name: "find_user",
+        arg_types: vec!["int", "text"],
+        stmt: query,
+    };
+    assert_eq!(
+        prepare.sql(),
+        "PREPARE find_user (int, text) AS SELECT id FROM users WHERE age = $1 AND name = $2"
+    );
+}
+
+#[test]
+fn test_prepare_without_arg_types() {
+    let mut qb = Q();
+    let query = qb.select(vec!["count(*)"]).from("users").build();
+    let prepare = Prepare {
+        name: "count_users",
+        arg_types: vec![],
+        stmt: query,
+    };
+    assert_eq!(
+        prepare.sql(),
+        "PREPARE count_users AS SELECT count(*) FROM users"
+    );
+}
+
+#[test]
+fn test_execute_and_deallocate() {
+    let execute = Execute {
+        name: "find_user",
+        args: vec!["30", "'bob'"],
+    };
+    assert_eq!(execute.sql(), "EXECUTE find_user (30, 'bob')");
+    let execute = Execute {
+        name: "count_users",
+        args: vec![],
+    };
+    assert_eq!(execute.sql(), "EXECUTE count_users");
+    let deallocate = Deallocate { name: "find_user" };
+    assert_eq!(deallocate.sql(), "DEALLOCATE find_user");
+}
+
+// Aggregate DISTINCT / ORDER BY tests
+#[test]
+fn test_count_distinct() {
+    assert_eq!(
+        count_distinct(Term::Atom("email")).sql(),
+        "COUNT(DISTINCT email)"
+    );
+}
+
+#[test]
+fn test_string_agg_ordered() {
+    let options = AggOptions {
+        distinct: false,
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Asc("name")],
+        }),
+    };
+    let result = string_agg(Term::Atom("name"), Term::Atom("', '"), options).sql();
+    assert_eq!(result, "STRING_AGG(name, ', ' ORDER BY name ASC)");
+}
+
+#[test]
+fn test_array_agg_distinct_ordered() {
+    let options = AggOptions {
+        distinct: true,
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Desc("score")],
+        }),
+    };
+    let result = array_agg(Term::Atom("score"), options).sql();
+    assert_eq!(result, "ARRAY_AGG(DISTINCT score ORDER BY score DESC)");
+}
+
+#[test]
+fn test_aggregate_default_options_in_select() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("dept"),
+            SelectExpression::Expr(
+                aggregate("SUM", vec![Term::Atom("salary")], AggOptions::default()),
+                Some("total"),
+            ),
+        ])
+        .from("employees")
+        .group_by(vec!["dept"])
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT dept, SUM(salary) AS total FROM employees GROUP BY dept"
+    );
+}
+
+// CTE materialization hint tests
+#[test]
+fn test_cte_materialized() {
+    let mut inner = Q();
+    let cte_query = inner
+        .select(vec!["user_id", "sum(total) AS spent"])
+        .from("orders")
+        .group_by(vec!["user_id"])
+        .build();
+    let mut qb = Q();
+    let query = qb
+        .with_materialized("spend", cte_query, true)
+        .select(vec!["*"])
+        .from("spend")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "WITH spend AS MATERIALIZED (SELECT user_id, sum(total) AS spent FROM orders GROUP BY user_id) SELECT * FROM spend"
+    );
+}
+
+#[test]
+fn test_cte_not_materialized_struct() {
+    let mut inner = Q();
+    let cte = Cte {
+        name: "u",
+        body: inner.select(vec!["id"]).from("users").build().into(),
+        materialized: Some(false),
+    };
+    assert_eq!(cte.sql(), "u AS NOT MATERIALIZED (SELECT id FROM users)");
+}
+
+#[test]
+fn test_cte_mixed_hints() {
+    let mut a = Q();
+    let first = a.select(vec!["id"]).from("a").build();
+    let mut b = Q();
+    let second = b.select(vec!["id"]).from("b").build();
+    let mut qb = Q();
+    let query = qb
+        .with("x", first)
+        .with_materialized("y", second, false)
+        .select(vec!["*"])
+        .from("x")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "WITH x AS (SELECT id FROM a), y AS NOT MATERIALIZED (SELECT id FROM b) SELECT * FROM x"
+    );
+}
+
+// Data-modifying CTE tests
+#[test]
+fn test_cte_with_delete_returning() {
+    let moved = D("queue")
+        .where_(lt("run_at", "NOW()"))
+        .returning(Columns::Star)
+        .build();
+    let mut qb = Q();
+    let query = qb
+        .with("moved", moved)
+        .select(vec!["*"])
+        .from("moved")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "WITH moved AS (DELETE FROM queue WHERE run_at < NOW() RETURNING *) SELECT * FROM moved"
+    );
+}
+
+#[test]
+fn test_cte_with_update_and_insert() {
+    let debited = U("accounts")
+        .set(vec![("balance", "balance - 10")])
+        .where_(eq("id", "1"))
+        .returning(Columns::Selected(vec!["id", "balance"]))
+        .build();
+    let logged = I("audit")
+        .columns(vec!["account_id"])
+        .values(vec!["1"])
+        .returning(Columns::Selected(vec!["id"]))
+        .build();
+    let mut qb = Q();
+    let query = qb
+        .with("debited", debited)
+        .with("logged", logged)
+        .select(vec!["debited.balance", "logged.id"])
+        .from("debited")
+        .cross_join("logged")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "WITH debited AS (UPDATE accounts SET balance = balance - 10 WHERE id = 1 RETURNING id, balance), logged AS (INSERT INTO audit (account_id) VALUES (1) RETURNING id) SELECT debited.balance, logged.id FROM debited CROSS JOIN logged"
+    );
+}
+
+#[test]
+fn test_cte_body_variants_sql() {
+    let delete = Delete {
+        with_clause: None,
+        table: "a".into(),
+        using: vec![],
+        where_clause: None,
+        returning: Some(Columns::Star),
+        params: Params::default(),
+    };
+    let cte = Cte {
+        name: "gone",
+        body: CteBody::Delete(Box::new(delete)),
+        materialized: Some(true),
+    };
+    assert_eq!(
+        cte.sql(),
+        "gone AS MATERIALIZED (DELETE FROM a RETURNING *)"
+    );
+}
+
+// DEFAULT value tests
+#[test]
+fn test_update_set_default() {
+    let update = U("users")
+        .set(vec![("status", default_value()), ("name", "'x'")])
+        .where_(eq("id", "1"))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE users SET status = DEFAULT, name = 'x' WHERE id = 1"
+    );
+}
+
+#[test]
+fn test_insert_values_default() {
+    let insert = I("users")
+        .columns(vec!["id", "name"])
+        .values(vec![default_value(), "'Alice'"])
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO users (id, name) VALUES (DEFAULT, 'Alice')"
+    );
+}
+
+// Foreign key referential action tests
+#[test]
+fn test_foreign_key_all_actions() {
+    let actions = vec![
+        (ForeignKeyAction::Cascade, "CASCADE"),
+        (ForeignKeyAction::Restrict, "RESTRICT"),
+        (ForeignKeyAction::SetNull, "SET NULL"),
+        (ForeignKeyAction::SetDefault, "SET DEFAULT"),
+        (ForeignKeyAction::NoAction, "NO ACTION"),
+    ];
+    for (action, expected) in actions {
+        assert_eq!(action.sql(), expected);
+    }
+}
+
+#[test]
+fn test_foreign_key_without_actions_or_columns() {
+    let fk = ForeignKey {
+        table: "users",
+        columns: vec![],
+        on_delete: None,
+        on_update: None,
+    };
+    assert_eq!(fk.sql(), "REFERENCES users");
+}
+
+#[test]
+fn test_foreign_key_on_update_only() {
+    let fk = ForeignKey {
+        table: "accounts",
+        columns: vec!["tenant_id", "id"],
+        on_delete: None,
+        on_update: Some(ForeignKeyAction::Cascade),
+    };
+    assert_eq!(
+        fk.sql(),
+        "REFERENCES accounts(tenant_id, id) ON UPDATE CASCADE"
+    );
+}
+
+#[test]
+fn test_create_table_with_column_defs() {
+    let mut tb = T("posts");
+    let create = tb
+        .column_def(ColumnDef {
+            name: "id",
+            data_type: "serial",
+            constraints: vec![ColumnConstraint::PrimaryKey],
+        })
+        .column_def(ColumnDef {
+            name: "author_id",
+            data_type: "integer",
+            constraints: vec![
+                ColumnConstraint::NotNull,
+                ColumnConstraint::References(ForeignKey {
+                    table: "users",
+                    columns: vec!["id"],
+                    on_delete: Some(ForeignKeyAction::SetNull),
+                    on_update: Some(ForeignKeyAction::NoAction),
+                }),
+            ],
+        })
+        .column_def(ColumnDef {
+            name: "slug",
+            data_type: "text",
+            constraints: vec![ColumnConstraint::Unique, ColumnConstraint::Default("''")],
+        })
+        .build_create_table();
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE posts (id serial PRIMARY KEY, author_id integer NOT NULL REFERENCES users(id) ON DELETE SET NULL ON UPDATE NO ACTION, slug text UNIQUE DEFAULT '')"
+    );
+}
+
+// Generated column tests
+#[test]
+fn test_generated_stored_column() {
+    let total = Term::Condition(
+        Box::new(Term::Atom("quantity")),
+        Op::O("*"),
+        Box::new(Term::Atom("unit_price")),
+    );
+    let mut tb = T("line_items");
+    let create = tb
+        .column("quantity", "integer", vec!["NOT NULL"])
+        .column("unit_price", "numeric", vec!["NOT NULL"])
+        .column_def(ColumnDef::new("total", "numeric").generated_stored(total))
+        .build_create_table();
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE line_items (quantity integer NOT NULL, unit_price numeric NOT NULL, total numeric GENERATED ALWAYS AS (quantity * unit_price) STORED)"
+    );
+}
+
+#[test]
+fn test_generated_stored_with_function_term() {
+    let col = ColumnDef::new("email_lower", "text").generated_stored(lower(Term::Atom("email")));
+    assert_eq!(
+        col.sql(),
+        "email_lower text GENERATED ALWAYS AS (LOWER(email)) STORED"
+    );
+}
+
+// Column qualification tests
+#[test]
+fn test_qualify_columns_select_where() {
+    let mut qb = Q();
+    let mut query = qb
+        .select(vec!["id", "name"])
+        .from("users")
+        .where_(and(eq("status", "'active'"), gte("age", "18")))
+        .order_by(vec![OrderedColumn::Asc("name")])
+        .build();
+    query.qualify_columns("users");
+    assert_eq!(
+        query.sql(),
+        "SELECT users.id, users.name FROM users WHERE users.status = 'active' AND users.age >= 18 ORDER BY name ASC"
+    );
+}
+
+#[test]
+fn test_qualify_columns_is_idempotent() {
+    let mut qb = Q();
+    let mut query = qb
+        .select(vec!["id", "o.total"])
+        .from("orders o")
+        .where_(eq("customer_id", "$1"))
+        .build();
+    query.qualify_columns("o");
+    let once = query.sql();
+    query.qualify_columns("o");
+    assert_eq!(query.sql(), once);
+    assert_eq!(
+        once,
+        "SELECT o.id, o.total FROM orders o WHERE o.customer_id = $1"
+    );
+}
+
+#[test]
+fn test_qualify_columns_skips_literals_keywords_and_subqueries() {
+    let mut sb = Q();
+    let sub = sb.select(vec!["user_id"]).from("banned").build();
+    let mut qb = Q();
+    let mut query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(
+            and(eq("active", "TRUE"), eq("deleted_at", "NULL")),
+            Term::Condition(
+                Box::new(Term::Atom("id")),
+                Op::In,
+                Box::new(Term::Subquery(Box::new(sub))),
+            ),
+        ))
+        .build();
+    query.qualify_columns("users");
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM users WHERE users.active = TRUE AND users.deleted_at = NULL AND users.id IN (SELECT user_id FROM banned)"
+    );
+}
+
+#[test]
+fn test_qualify_columns_in_expressions() {
+    let mut qb = Q();
+    let mut query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("id"),
+            SelectExpression::Expr(upper(Term::Atom("name")), Some("upper_name")),
+        ])
+        .from("users")
+        .build();
+    query.qualify_columns("u");
+    assert_eq!(
+        query.sql(),
+        "SELECT u.id, UPPER(u.name) AS upper_name FROM users"
+    );
+}
+
+#[test]
+fn test_qualify_columns_group_by_and_having() {
+    let mut qb = Q();
+    let mut query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("region"),
+            SelectExpression::Expr(Term::Atom("date_trunc('day', placed_at)"), Some("day")),
+            SelectExpression::Expr(Term::Atom("sum(total)"), Some("revenue")),
+        ])
+        .from("orders")
+        .group_by(vec!["region", "day"])
+        .having(and(gt("sum(total)", "100"), eq("region", "'eu'")))
+        .order_by(vec![OrderedColumn::Desc("revenue")])
+        .build();
+    query.qualify_columns("o");
+    assert_eq!(
+        query.sql(),
+        "SELECT o.region, date_trunc('day', placed_at) AS day, sum(total) AS revenue FROM orders \
+         GROUP BY o.region, day HAVING sum(total) > 100 AND o.region = 'eu' ORDER BY revenue DESC"
+    );
+}
+
+#[test]
+fn test_qualify_columns_leaves_session_keywords() {
+    let mut qb = Q();
+    let mut query = qb
+        .select(vec!["*"])
+        .from("docs")
+        .where_(or(eq("owner", "current_user"), eq("owner", "SESSION_USER")))
+        .build();
+    query.qualify_columns("d");
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM docs WHERE d.owner = current_user OR d.owner = SESSION_USER"
+    );
+}
+
+// Bulk parameter binding tests
+#[test]
+fn test_insert_bind_rows() {
+    let mut ib = I("events");
+    let (sql, values) = ib
+        .columns(vec!["kind", "user_id", "seen"])
+        .bind_rows(vec![
+            vec![Value::from("login"), Value::from(1), Value::from(true)],
+            vec![Value::from("logout"), Value::from(2), Value::from(false)],
+        ])
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO events (kind, user_id, seen) VALUES ($1, $2, $3), ($4, $5, $6)"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("login".to_string()),
+            Value::Int(1),
+            Value::Bool(true),
+            Value::Text("logout".to_string()),
+            Value::Int(2),
+            Value::Bool(false),
+        ]
+    );
+}
+
+#[test]
+fn test_insert_bind_rows_continues_numbering() {
+    let mut ib = I("events");
+    let _ = ib.param();
+    let insert = ib
+        .columns(vec!["kind"])
+        .bind_rows(vec![vec![Value::from("a")], vec![Value::Null]])
+        .build();
+    assert_eq!(insert.sql(), "INSERT INTO events (kind) VALUES ($2), ($3)");
+    assert_eq!(insert.params.len(), 3);
+    assert_eq!(
+        insert.render(),
+        Err(BuildError::UnboundParams {
+            placeholders: vec![1]
+        })
+    );
+}
+
+// EXISTS term tests
+#[test]
+fn test_exists_keeps_subquery_structure() {
+    let mut sb = Q();
+    let sub = sb
+        .select(vec!["1"])
+        .from("orders")
+        .where_(eq("orders.user_id", "users.id"))
+        .build();
+    match exists(sub) {
+        Term::Exists(q) => {
+            assert_eq!(
+                q.sql(),
+                "SELECT 1 FROM orders WHERE orders.user_id = users.id"
+            )
+        }
+        _ => panic!("expected Term::Exists"),
+    }
+}
+
+#[test]
+fn test_exists_and_not_exists_in_where() {
+    let mut ob = Q();
+    let orders = ob
+        .select(vec!["1"])
+        .from("orders")
+        .where_(eq("orders.user_id", "users.id"))
+        .build();
+    let mut bb = Q();
+    let bans = bb
+        .select(vec!["1"])
+        .from("bans")
+        .where_(eq("bans.user_id", "users.id"))
+        .build();
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(and(exists(orders), not_exists(bans)))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM users WHERE EXISTS (SELECT 1 FROM orders WHERE orders.user_id = users.id) AND NOT EXISTS (SELECT 1 FROM bans WHERE bans.user_id = users.id)"
+    );
+}
+
+// LIKE ESCAPE / ILIKE tests
+#[test]
+fn test_like_escape_in_where() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("coupons")
+        .where_(like_escape("label", "'100\\%%'", "\\"))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM coupons WHERE label LIKE '100\\%%' ESCAPE '\\'"
+    );
+}
+
+#[test]
+fn test_like_escape_accepts_terms_and_bound_pattern() {
+    let mut qb = Q();
+    let pattern = qb.bind("100!%%");
+    let (sql, values) = qb
+        .select(vec!["*"])
+        .from("coupons")
+        .where_(ilike_escape(lower(Term::Atom("label")), &pattern, "!"))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT * FROM coupons WHERE LOWER(label) ILIKE $1 ESCAPE '!'"
+    );
+    assert_eq!(values, vec![Value::Text("100!%%".to_string())]);
+}
+
+#[test]
+fn test_like_without_escape_is_unchanged() {
+    assert_eq!(like("name", "'A%'").sql(), "name LIKE 'A%'");
+    assert_eq!(ilike("name", "'a%'").sql(), "name ILIKE 'a%'");
+}
+
+#[test]
+fn test_like_term_negated_and_quote_escape() {
+    let term = Term::Like {
+        expr: Box::new(lower(Term::Atom("title"))),
+        pattern: Box::new(Term::Atom("'%o''%'")),
+        escape: Some("'"),
+        case_insensitive: true,
+        negated: true,
+    };
+    assert_eq!(term.sql(), "LOWER(title) NOT ILIKE '%o''%' ESCAPE ''''");
+}
+
+// TRIM tests
+#[test]
+fn test_trim_variants() {
+    assert_eq!(trim(Term::Atom("name")).sql(), "TRIM(name)");
+    assert_eq!(
+        trim_leading(Term::Atom("account_no"), Some(Term::Atom("'0'"))).sql(),
+        "TRIM(LEADING '0' FROM account_no)"
+    );
+    assert_eq!(
+        trim_trailing(Term::Atom("note"), None).sql(),
+        "TRIM(TRAILING FROM note)"
+    );
+    assert_eq!(
+        trim_both(Term::Atom("code"), Some(Term::Atom("'-'"))).sql(),
+        "TRIM(BOTH '-' FROM code)"
+    );
+}
+
+#[test]
+fn test_trim_in_select_and_where() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![SelectExpression::Expr(
+            trim_leading(Term::Atom("sku"), Some(Term::Atom("'0'"))),
+            Some("sku"),
+        )])
+        .from("products")
+        .where_(Term::Condition(
+            Box::new(trim(Term::Atom("name"))),
+            Op::NotEquals,
+            Box::new(Term::Atom("''")),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT TRIM(LEADING '0' FROM sku) AS sku FROM products WHERE TRIM(name) != ''"
+    );
+}
+
+// OVERLAPS tests
+#[test]
+fn test_overlaps_in_where() {
+    let mut qb = Q();
+    let start = qb.param();
+    let end = qb.param();
+    let query = qb
+        .select(vec!["id"])
+        .from("bookings")
+        .where_(and(
+            eq("room_id", "12"),
+            overlaps(
+                Term::Atom("starts_at"),
+                Term::Atom("ends_at"),
+                Term::Atom(&start),
+                Term::Atom(&end),
+            ),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM bookings WHERE room_id = 12 AND (starts_at, ends_at) OVERLAPS ($1, $2)"
+    );
+}
+
+#[test]
+fn test_overlaps_with_interval_end() {
+    let t = overlaps(
+        Term::Atom("a.starts_at"),
+        Term::Atom("a.ends_at"),
+        now(),
+        date_add(now(), interval("1 day")),
+    );
+    assert_eq!(
+        t.sql(),
+        "(a.starts_at, a.ends_at) OVERLAPS (NOW(), NOW() + INTERVAL '1 day')"
+    );
+    assert_eq!(Op::Overlaps.sql(), "OVERLAPS");
+}
+
+#[test]
+fn test_row_constructor() {
+    assert_eq!(row(vec![Term::Atom("a"), Term::Atom("b")]).sql(), "(a, b)");
+}
+
+// Positional INSERT tests
+#[test]
+fn test_insert_without_columns_is_positional() {
+    let insert = I("pairs")
+        .rows(vec![vec!["1", "'a'"], vec!["2", "'b'"]])
+        .build();
+    assert_eq!(insert.sql(), "INSERT INTO pairs VALUES (1, 'a'), (2, 'b')");
+}
+
+#[test]
+fn test_insert_select_without_columns() {
+    let mut qb = Q();
+    let query = qb.select(vec!["*"]).from("pairs_staging").build();
+    let insert = I("pairs").select(query).build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO pairs SELECT * FROM pairs_staging"
+    );
+}
+
+// Literal helper tests
+#[test]
+fn test_literal_helpers_in_values() {
+    let name = text("it's");
+    let age = num(30);
+    let price = float(19.99);
+    let active = boolean(true);
+    let insert = I("items")
+        .columns(vec!["name", "age", "price", "active"])
+        .values(vec![&name, &age, &price, &active])
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO items (name, age, price, active) VALUES ('it''s', 30, 19.99, TRUE)"
+    );
+}
+
+#[test]
+fn test_literal_helpers_in_set() {
+    let status = text("archived");
+    let flag = boolean(false);
+    let update = U("items")
+        .set(vec![("status", &status), ("active", &flag)])
+        .where_(eq("id", "1"))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE items SET status = 'archived', active = FALSE WHERE id = 1"
+    );
+}
+
+#[test]
+fn test_float_literal_edge_cases() {
+    assert_eq!(float(1.0), "1.0");
+    assert_eq!(float(-0.5), "-0.5");
+    assert_eq!(float(1e21), "1e21");
+    assert_eq!(float(f64::NAN), "'NaN'::float8");
+    assert_eq!(float(f64::NEG_INFINITY), "'-Infinity'::float8");
+    assert_eq!(num(i64::MIN), "-9223372036854775808");
+    assert_eq!(text(""), "''");
+}
+
+// CHECK constraint tests
+#[test]
+fn test_column_check_constraint() {
+    let mut tb = T("products");
+    let create = tb
+        .column("id", "serial", vec!["PRIMARY KEY"])
+        .column_def(
+            ColumnDef::new("quantity", "integer")
+                .check(and(gte("quantity", "0"), lte("quantity", "1000"))),
+        )
+        .build_create_table();
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE products (id serial PRIMARY KEY, quantity integer CHECK (quantity >= 0 AND quantity <= 1000))"
+    );
+}
+
+#[test]
+fn test_table_check_constraint() {
+    let mut tb = T("discounts");
+    let create = tb
+        .column("price", "numeric", vec!["NOT NULL"])
+        .column("sale_price", "numeric", vec![])
+        .check(or(is_null("sale_price"), lt("sale_price", "price")))
+        .build_create_table();
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE discounts (price numeric NOT NULL, sale_price numeric, CHECK (sale_price IS NULL OR sale_price < price))"
+    );
+}
+
+// DELETE RETURNING tests
+#[test]
+fn test_delete_returning_star() {
+    let delete = D("carts")
+        .where_(eq("user_id", "$1"))
+        .returning_star()
+        .build();
+    assert_eq!(
+        delete.sql(),
+        "DELETE FROM carts WHERE user_id = $1 RETURNING *"
+    );
+}
+
+#[test]
+fn test_delete_returning_expressions() {
+    let delete = D("users")
+        .where_(eq("status", "'banned'"))
+        .returning(Columns::Expressions(vec![
+            SelectExpression::Column("id"),
+            SelectExpression::Expr(now(), Some("deleted_at")),
+        ]))
+        .build();
+    assert_eq!(
+        delete.sql(),
+        "DELETE FROM users WHERE status = 'banned' RETURNING id, NOW() AS deleted_at"
+    );
+}
+
+// Placeholder style tests
+#[test]
+fn test_param_style_default_is_dollar() {
+    let mut qb = Q();
+    let a = qb.param();
+    let b = qb.bind(5);
+    assert_eq!((a.as_str(), b.as_str()), ("$1", "$2"));
+    assert_eq!(ParamStyle::default(), ParamStyle::Dollar);
+}
+
+#[test]
+fn test_param_style_question_with_bind() {
+    let mut qb = Q();
+    qb.param_style(ParamStyle::Question);
+    let status = qb.bind("active");
+    let age = qb.bind(21);
+    let (sql, values) = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(and(eq("status", &status), gte("age", &age)))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(sql, "SELECT id FROM users WHERE status = ? AND age >= ?");
+    assert_eq!(
+        values,
+        vec![Value::Text("active".to_string()), Value::Int(21)]
+    );
+}
+
+#[test]
+fn test_param_style_question_insert_bind_rows() {
+    let mut ib = I("pairs");
+    ib.param_style(ParamStyle::Question);
+    let insert = ib
+        .columns(vec!["a", "b"])
+        .bind_rows(vec![
+            vec![Value::from(1), Value::from(2)],
+            vec![Value::from(3), Value::from(4)],
+        ])
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO pairs (a, b) VALUES (?, ?), (?, ?)"
+    );
+    assert_eq!(insert.params.len(), 4);
+}
+
+// Query comment tests
+#[test]
+fn test_query_comment_prefix() {
+    let mut rb = Q();
+    let recent = rb.select(vec!["id"]).from("orders").build();
+    let mut qb = Q();
+    let query = qb
+        .comment("app=api")
+        .with("recent", recent)
+        .select(vec!["*"])
+        .from("recent")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "/* app=api */ WITH recent AS (SELECT id FROM orders) SELECT * FROM recent"
+    );
+}
+
+#[test]
+fn test_query_comment_cannot_break_out() {
+    let mut qb = Q();
+    let query = qb
+        .comment("x */ DROP TABLE users; /* y")
+        .select(vec!["1"])
+        .build();
+    assert_eq!(query.sql(), "/* x * / DROP TABLE users; / * y */ SELECT 1");
+    let mut qb = Q();
+    let query = qb.comment("*/*/").select(vec!["1"]).build();
+    let sql = query.sql();
+    let body = &sql[3..sql.len() - " */ SELECT 1".len()];
+    assert!(!body.contains("*/") && !body.contains("/*"));
+}
+
+// UPDATE ... FROM tests
+#[test]
+fn test_update_from_multiple_tables() {
+    let update = U("employees")
+        .set(vec![("salary", "salary * r.factor")])
+        .from("departments d")
+        .from("raises r")
+        .where_(and(
+            eq("employees.department_id", "d.id"),
+            eq("d.raise_id", "r.id"),
+        ))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE employees SET salary = salary * r.factor FROM departments d, raises r WHERE employees.department_id = d.id AND d.raise_id = r.id"
+    );
+}
+
+#[test]
+fn test_update_from_table_and_subquery() {
+    let mut qb = Q();
+    let latest = qb
+        .select(vec!["user_id", "max(created_at) AS last_seen"])
+        .from("logins")
+        .group_by(vec!["user_id"])
+        .build();
+    let update = U("users")
+        .set(vec![("last_seen", "l.last_seen")])
+        .from_sources(vec!["accounts a"])
+        .from_subquery(latest, "l")
+        .where_(and(
+            eq("users.id", "l.user_id"),
+            eq("users.account_id", "a.id"),
+        ))
+        .returning(Columns::Selected(vec!["users.id"]))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE users SET last_seen = l.last_seen FROM accounts a, (SELECT user_id, max(created_at) AS last_seen FROM logins GROUP BY user_id) AS l WHERE users.id = l.user_id AND users.account_id = a.id RETURNING users.id"
+    );
+}
+
+// CREATE TABLE AS tests
+#[test]
+fn test_create_table_as_with_data() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["user_id", "count(*) AS orders"])
+        .from("orders")
+        .group_by(vec!["user_id"])
+        .build();
+    let create = CreateTableAs {
+        table: "order_counts",
+        query,
+        temporary: false,
+        with_data: true,
+    };
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE order_counts AS SELECT user_id, count(*) AS orders FROM orders GROUP BY user_id"
+    );
+}
+
+#[test]
+fn test_create_temporary_table_as_without_data() {
+    let mut qb = Q();
+    let query = qb.select(vec!["*"]).from("users").build();
+    let create = CreateTableAs {
+        table: "users_scratch",
+        query,
+        temporary: true,
+        with_data: false,
+    };
+    assert_eq!(
+        create.sql(),
+        "CREATE TEMPORARY TABLE users_scratch AS SELECT * FROM users WITH NO DATA"
+    );
+}
+
+// UPDATE/DELETE conditional WHERE tests
+#[test]
+fn test_update_and_where_with_optional_filters() {
+    let tenant: Option<&str> = Some("7");
+    let update = U("invoices")
+        .set(vec![("status", "'void'")])
+        .where_opt(tenant.map(|t| eq("tenant_id", t)))
+        .and_where(eq("status", "'draft'"))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE invoices SET status = 'void' WHERE tenant_id = 7 AND status = 'draft'"
+    );
+}
+
+#[test]
+fn test_delete_and_where_keeps_where_behavior() {
+    let delete = D("logs")
+        .where_(lt("created_at", "'2024-01-01'"))
+        .where_opt(None)
+        .and_where(eq("level", "'debug'"))
+        .build();
+    assert_eq!(
+        delete.sql(),
+        "DELETE FROM logs WHERE created_at < '2024-01-01' AND level = 'debug'"
+    );
+}
+
+// Hash/Eq tests
+#[test]
+fn test_query_as_hash_map_key() {
+    use std::collections::HashMap;
+    let mut b1 = Q();
+    let active = b1
+        .select(vec!["id"])
+        .from("users")
+        .where_(eq("status", "'active'"))
+        .build();
+    let mut b2 = Q();
+    let banned = b2
+        .select(vec!["id"])
+        .from("users")
+        .where_(eq("status", "'banned'"))
+        .build();
+    let mut cache = HashMap::new();
+    cache.insert(active.clone(), 1);
+    cache.insert(active.clone(), 2);
+    cache.insert(banned, 3);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache[&active], 2);
+}
+
+#[test]
+fn test_term_and_op_equality() {
+    assert!(eq("a", "1") == eq("a", "1"));
+    assert!(eq("a", "1") != eq("a", "2"));
+    assert!(Op::O("<>") == Op::O("<>"));
+    assert!(Op::And != Op::Or);
+    assert!(exists(Q().build()) == exists(Q().build()));
+}
+
+#[test]
+fn test_float_bearing_types_are_eq() {
+    assert_eq!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+    assert_ne!(Value::Float(0.0), Value::Float(-0.0));
+    assert_ne!(Value::Int(1), Value::Float(1.0));
+    let sample = |fraction| TableSample {
+        method: SampleMethod::Bernoulli,
+        fraction,
+        repeatable: None,
+    };
+    assert!(sample(10.0) == sample(10.0));
+    assert!(sample(10.0) != sample(20.0));
+}
+
+// Keyset pagination tests
+#[test]
+fn test_keyset_paginate_first_page() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id", "name"])
+        .from("users")
+        .keyset_paginate(vec![OrderedColumn::Asc("id")], None, 20)
+        .unwrap()
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id, name FROM users ORDER BY id ASC LIMIT 20"
+    );
+    assert!(query.params.is_empty());
+}
+
+#[test]
+fn test_keyset_paginate_descending_with_existing_where() {
+    let mut qb = Q();
+    let tenant = qb.bind(3);
+    let (sql, values) = qb
+        .select(vec!["*"])
+        .from("events")
+        .where_(eq("tenant_id", &tenant))
+        .keyset_paginate(
+            vec![OrderedColumn::Desc("created_at")],
+            Some(vec![Value::from("2024-05-01")]),
+            50,
+        )
+        .unwrap()
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT * FROM events WHERE tenant_id = $1 AND created_at < $2 ORDER BY created_at DESC LIMIT 50"
+    );
+    assert_eq!(
+        values,
+        vec![Value::Int(3), Value::Text("2024-05-01".to_string())]
+    );
+}
+
+#[test]
+fn test_keyset_paginate_composite_same_direction() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("posts")
+        .keyset_paginate(
+            vec![OrderedColumn::Asc("created_at"), OrderedColumn::Asc("id")],
+            Some(vec![Value::from("2024-01-01"), Value::from(42)]),
+            10,
+        )
+        .unwrap()
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM posts WHERE (created_at, id) > ($1, $2) ORDER BY created_at ASC, id ASC LIMIT 10"
+    );
+}
+
+#[test]
+fn test_keyset_paginate_composite_mixed_direction() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("scores")
+        .keyset_paginate(
+            vec![OrderedColumn::Desc("points"), OrderedColumn::Asc("id")],
+            Some(vec![Value::from(900), Value::from(7)]),
+            25,
+        )
+        .unwrap()
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM scores WHERE (points < $1 OR (points = $2 AND id > $3)) ORDER BY points DESC, id ASC LIMIT 25"
+    );
+    assert_eq!(query.validate_params(), Ok(()));
+}
+
+// Array subscript and slice tests
+#[test]
+fn test_subscript_in_where() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![SelectExpression::Expr(
+            slice(
+                Term::Atom("tags"),
+                Some(Term::Atom("1")),
+                Some(Term::Atom("3")),
+            ),
+            Some("top_tags"),
+        )])
+        .from("posts")
+        .where_(Term::Condition(
+            Box::new(subscript(Term::Atom("tags"), Term::Atom("1"))),
+            Op::Equals,
+            Box::new(Term::Atom("'rust'")),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT tags[1:3] AS top_tags FROM posts WHERE tags[1] = 'rust'"
+    );
+}
+
+#[test]
+fn test_nested_subscript_is_left_associative() {
+    let t = subscript(
+        slice(Term::Atom("grid"), None, Some(Term::Atom("2"))),
+        Term::Atom("1"),
+    );
+    assert_eq!(t.sql(), "grid[:2][1]");
+}
+
+#[test]
+fn test_subscript_of_expression_is_parenthesized() {
+    let t = subscript(
+        Term::Aggregate("ARRAY_AGG", vec![Term::Atom("name")], AggOptions::default()),
+        Term::Atom("1"),
+    );
+    assert_eq!(t.sql(), "(ARRAY_AGG(name))[1]");
+}
+
+// Set-returning function FROM source tests
+#[test]
+fn test_from_unnest_with_ordinality() {
+    let source = FromSource::FunctionCall {
+        func: unnest(Term::Atom("ARRAY[1, 2, 3]")),
+        alias: "t",
+        column_aliases: vec!["val", "idx"],
+        with_ordinality: true,
+    };
+    assert_eq!(
+        source.sql(),
+        "unnest(ARRAY[1, 2, 3]) WITH ORDINALITY AS t (val, idx)"
+    );
+}
+
+#[test]
+fn test_from_function_without_column_aliases() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["tag"])
+        .from_function(
+            func("jsonb_array_elements_text", vec![Term::Atom("$1")]),
+            "tag",
+            vec![],
+            false,
+        )
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT tag FROM jsonb_array_elements_text($1) AS tag"
+    );
+}
+
+#[test]
+fn test_function_term_in_where() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("events")
+        .where_(Term::Condition(
+            Box::new(func(
+                "date_trunc",
+                vec![Term::Atom("'day'"), Term::Atom("created_at")],
+            )),
+            Op::Equals,
+            Box::new(current_date()),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM events WHERE date_trunc('day', created_at) = CURRENT_DATE"
+    );
+}
+
+// generate_series tests
+#[test]
+fn test_generate_series_integers_without_step() {
+    let series = generate_series(Term::Atom("1"), Term::Atom("10"), None);
+    assert_eq!(series.sql(), "generate_series(1, 10)");
+}
+
+#[test]
+fn test_generate_series_gap_filling_calendar() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["d.day", "coalesce(count(o.id), 0) AS orders"])
+        .from_function(
+            generate_series(
+                pg_cast(Term::Atom("'2024-01-01'"), "date"),
+                pg_cast(Term::Atom("'2024-01-31'"), "date"),
+                Some(Term::Atom("'1 day'")),
+            ),
+            "d",
+            vec!["day"],
+            false,
+        )
+        .left_join("orders o", eq("o.created_on", "d.day"))
+        .group_by(vec!["d.day"])
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT d.day, coalesce(count(o.id), 0) AS orders FROM generate_series('2024-01-01'::date, '2024-01-31'::date, '1 day') AS d (day) LEFT JOIN orders o ON o.created_on = d.day GROUP BY d.day"
+    );
+}
+
+// Shared term tests
+#[test]
+fn test_shared_term_renders_like_inner() {
+    let inner = coalesce(vec![Term::Atom("nickname"), Term::Atom("name")]);
+    let shared = share(inner.clone());
+    assert_eq!(shared.sql(), inner.sql());
+    assert!(share(shared.clone()) == shared);
+}
+
+#[test]
+fn test_shared_term_clones_share_storage() {
+    let shared = share(upper(Term::Atom("email")));
+    let copy = shared.clone();
+    match (&shared, &copy) {
+        (Term::Shared(a), Term::Shared(b)) => assert!(std::sync::Arc::ptr_eq(a, b)),
+        _ => panic!("expected Term::Shared"),
+    }
+}
+
+#[test]
+fn test_shared_term_qualify_does_not_touch_other_copies() {
+    let shared = share(eq("status", "'active'"));
+    let mut qb = Q();
+    let mut query = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(shared.clone())
+        .build();
+    query.qualify_columns("u");
+    assert_eq!(
+        query.sql(),
+        "SELECT u.id FROM users WHERE u.status = 'active'"
+    );
+    assert_eq!(shared.sql(), "status = 'active'");
+}
+
+// Typed upsert tests
+#[test]
+fn test_on_conflict_do_update_terms_matches_string_form() {
+    let typed = I("counters")
+        .columns(vec!["key", "hits"])
+        .values(vec!["'home'", "1"])
+        .on_conflict_do_update_terms(
+            vec!["key"],
+            vec![("hits", add(Term::Atom("counters.hits"), excluded("hits")))],
+        )
+        .build();
+    let stringly = I("counters")
+        .columns(vec!["key", "hits"])
+        .values(vec!["'home'", "1"])
+        .on_conflict_do_update(vec!["key"], vec![("hits", "counters.hits + EXCLUDED.hits")])
+        .build();
+    assert_eq!(typed.sql(), stringly.sql());
+}
+
+#[test]
+fn test_on_conflict_do_update_terms_with_params() {
+    let mut ib = I("users");
+    let email = ib.bind("a@example.com");
+    let bonus = ib.bind(5);
+    let (sql, values) = ib
+        .columns(vec!["email", "points"])
+        .values(vec![&email, "0"])
+        .on_conflict_do_update_terms(
+            vec!["email"],
+            vec![
+                (
+                    "points",
+                    add(Term::Atom("users.points"), Term::Atom(&bonus)),
+                ),
+                ("updated_at", now()),
+            ],
+        )
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO users (email, points) VALUES ($1, 0) ON CONFLICT (email) DO UPDATE SET points = users.points + $2, updated_at = NOW()"
+    );
+    assert_eq!(values.len(), 2);
+}
+
+#[test]
+fn test_arithmetic_helpers_keep_grouping() {
+    let t = div(
+        sub(Term::Atom("total"), Term::Atom("discount")),
+        add(Term::Atom("qty"), raw(num(1))),
+    );
+    assert_eq!(t.sql(), "(total - discount) / (qty + 1)");
+    assert_eq!(
+        mul(Term::Atom("price"), raw(float(1.2))).sql(),
+        "price * 1.2"
+    );
+}
+
+// Sorted ON CONFLICT assignment tests
+#[test]
+fn test_on_conflict_sorted_is_deterministic_from_hash_map() {
+    use std::collections::HashMap;
+    let updates: HashMap<&str, &str> = [
+        ("zip", "EXCLUDED.zip"),
+        ("city", "EXCLUDED.city"),
+        ("street", "EXCLUDED.street"),
+    ]
+    .into_iter()
+    .collect();
+    let mut ib = I("addresses");
+    let mut insert = ib
+        .columns(vec!["id", "street", "city", "zip"])
+        .values(vec!["1", "'Main'", "'Springfield'", "'12345'"])
+        .on_conflict_do_update(vec!["id"], updates.into_iter().collect())
+        .build();
+    insert.on_conflict = insert.on_conflict.map(OnConflict::sorted);
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO addresses (id, street, city, zip) VALUES (1, 'Main', 'Springfield', '12345') ON CONFLICT (id) DO UPDATE SET city = EXCLUDED.city, street = EXCLUDED.street, zip = EXCLUDED.zip"
+    );
+}
+
+#[test]
+fn test_on_conflict_keeps_given_order_unless_sorted() {
+    let terms =
+        OnConflict::DoUpdateTerms(vec!["id"], vec![("b", excluded("b")), ("a", excluded("a"))]);
+    assert_eq!(
+        terms.sql(),
+        "ON CONFLICT (id) DO UPDATE SET b = EXCLUDED.b, a = EXCLUDED.a"
+    );
+    assert_eq!(
+        terms.sorted().sql(),
+        "ON CONFLICT (id) DO UPDATE SET a = EXCLUDED.a, b = EXCLUDED.b"
+    );
+    assert_eq!(
+        OnConflict::DoNothing(vec!["id"]).sorted().sql(),
+        "ON CONFLICT (id) DO NOTHING"
+    );
+}
+
+// Schema-qualified table tests
+#[test]
+fn test_schema_table_plain_and_quoted() {
+    assert_eq!(
+        schema_table("analytics", "events").sql(),
+        "analytics.events"
+    );
+    assert_eq!(
+        schema_table("Analytics", "Events").sql(),
+        "\"Analytics\".\"Events\""
+    );
+    assert_eq!(schema_table("public", "user").sql(), "public.\"user\"");
+    assert_eq!(
+        schema_table("weird\"schema", "t").sql(),
+        "\"weird\"\"schema\".t"
+    );
+}
+
+#[test]
+fn test_from_qualified_and_join() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["events.id"])
+        .from_qualified("analytics", "events")
+        .build();
+    assert_eq!(query.sql(), "SELECT events.id FROM analytics.events");
+
+    let join = Join {
+        join_type: JoinType::Inner,
+        source: schema_table("auth", "users"),
+        on: Some(eq("auth.users.id", "events.user_id")),
+    };
+    assert_eq!(
+        join.sql(),
+        "INNER JOIN auth.users ON auth.users.id = events.user_id"
+    );
+}
+
+#[test]
+fn test_quote_ident_edge_cases() {
+    assert_eq!(quote_ident("_private"), "_private");
+    assert_eq!(quote_ident("col$1"), "col$1");
+    assert_eq!(quote_ident("1st"), "\"1st\"");
+    assert_eq!(quote_ident(""), "\"\"");
+    assert_eq!(quote_ident("select"), "\"select\"");
+}
+
+// NOT negation tests
+
+#[test]
+fn test_not_distributes_into_predicates() {
+    assert_eq!(
+        not(in_("status", vec!["'active'", "'pending'"])).sql(),
+        "status NOT IN ('active', 'pending')"
+    );
+    assert_eq!(
+        not(between("age", "18", "65")).sql(),
+        "age NOT BETWEEN 18 AND 65"
+    );
+    assert_eq!(not(like("name", "'A%'")).sql(), "name NOT LIKE 'A%'");
+    assert_eq!(not(is_null("deleted_at")).sql(), "deleted_at IS NOT NULL");
+    assert_eq!(not(is_not_null("deleted_at")).sql(), "deleted_at IS NULL");
+}
+
+#[test]
+fn test_not_double_negation_cancels() {
+    assert_eq!(not(not(eq("a", "1"))).sql(), "a = 1");
+    assert_eq!(not(not(in_("id", vec!["1", "2"]))).sql(), "id IN (1, 2)");
+}
+
+#[test]
+fn test_not_generic_conditions_keep_prefix() {
+    assert_eq!(not(eq("col1", "1")).sql(), "NOT col1 = 1");
+    assert_eq!(
+        not(and(eq("a", "1"), eq("b", "2"))).sql(),
+        "NOT (a = 1 AND b = 2)"
+    );
+}
+
+#[test]
+fn test_not_in_where_clause() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("orders")
+        .where_(and(
+            not(in_("status", vec!["'cancelled'", "'refunded'"])),
+            not(is_null("shipped_at")),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM orders WHERE status NOT IN ('cancelled', 'refunded') AND shipped_at IS NOT NULL"
+    );
+}
+
+#[test]
+fn test_and_parenthesizes_shared_or_operands() {
+    let either = share(or(eq("a", "1"), eq("b", "2")));
+    assert_eq!(
+        and(either.clone(), eq("c", "3")).sql(),
+        "(a = 1 OR b = 2) AND c = 3"
+    );
+    assert_eq!(eq("c", "3").and(either).sql(), "c = 3 AND (a = 1 OR b = 2)");
+    let wrapped = Term::Parens(Box::new(or(eq("a", "1"), eq("b", "2"))));
+    assert_eq!(
+        and(share(wrapped), eq("c", "3")).sql(),
+        "(a = 1 OR b = 2) AND c = 3"
+    );
+}
+
+#[test]
+fn test_not_looks_through_shared_terms() {
+    let either = share(or(eq("a", "1"), eq("b", "2")));
+    assert_eq!(not(either.clone()).sql(), "NOT (a = 1 OR b = 2)");
+    assert_eq!(not(share(is_null("email"))).sql(), "email IS NOT NULL");
+    assert_eq!(not(share(not(flag("active")))).sql(), "active");
+    assert_eq!(either.sql(), "a = 1 OR b = 2");
+}
+
+#[test]
+fn test_not_parenthesizes_compound_operands() {
+    let direct = Term::Not(Box::new(and(eq("a", "1"), eq("b", "2"))));
+    assert_eq!(direct.sql(), "NOT (a = 1 AND b = 2)");
+    let shared = Term::Not(Box::new(share(or(eq("a", "1"), eq("b", "2")))));
+    assert_eq!(shared.sql(), "NOT (a = 1 OR b = 2)");
+    assert_eq!(Term::Not(Box::new(eq("a", "1"))).sql(), "NOT a = 1");
+}
+
+// Correlated subquery tests
+
+#[test]
+fn test_correlated_reference_quotes_as_needed() {
+    assert_eq!(correlated("users", "id").sql(), "users.id");
+    assert_eq!(correlated("Users", "user").sql(), "\"Users\".\"user\"");
+}
+
+#[test]
+fn test_correlated_order_count_subquery() {
+    let mut inner = Q();
+    let order_count = inner
+        .select(vec!["COUNT(*)"])
+        .from("orders")
+        .where_(and(
+            Term::Condition(
+                Box::new(Term::Atom("orders.user_id")),
+                Op::Equals,
+                Box::new(correlated("users", "id")),
+            ),
+            Term::Condition(
+                Box::new(Term::Atom("orders.status")),
+                Op::Equals,
+                Box::new(Term::Raw(std::borrow::Cow::Borrowed("$1"))),
+            ),
+        ))
+        .build();
+
+    let mut outer = Q();
+    let query = outer
+        .select_expressions(vec![
+            SelectExpression::Column("id"),
+            SelectExpression::Column("name"),
+            SelectExpression::Subquery(Box::new(order_count), Some("order_count")),
+        ])
+        .from("users")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id, name, (SELECT COUNT(*) FROM orders WHERE orders.user_id = users.id AND orders.status = $1) AS order_count FROM users"
+    );
+}
+
+#[test]
+fn test_correlated_survives_qualify_columns() {
+    let mut inner = Q();
+    let mut query = inner
+        .select(vec!["total"])
+        .from("orders")
+        .where_(Term::Condition(
+            Box::new(Term::Atom("user_id")),
+            Op::Equals,
+            Box::new(correlated("users", "id")),
+        ))
+        .build();
+    query.qualify_columns("orders");
+    assert_eq!(
+        query.sql(),
+        "SELECT orders.total FROM orders WHERE orders.user_id = users.id"
+    );
+}
+
+// Star select expression tests
+
+#[test]
+fn test_select_star_with_computed_column() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Star,
+            SelectExpression::Expr(func("now", vec![]), Some("fetched_at")),
+        ])
+        .from("users")
+        .build();
+    assert_eq!(query.sql(), "SELECT *, now() AS fetched_at FROM users");
+}
+
+#[test]
+fn test_select_table_star_with_joined_column() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::TableStar("orders"),
+            SelectExpression::Column("users.email"),
+        ])
+        .from("orders")
+        .inner_join("users", eq("orders.user_id", "users.id"))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT orders.*, users.email FROM orders INNER JOIN users ON orders.user_id = users.id"
+    );
+}
+
+#[test]
+fn test_qualify_columns_leaves_stars_alone() {
+    let mut qb = Q();
+    let mut query = qb
+        .select_expressions(vec![
+            SelectExpression::Star,
+            SelectExpression::TableStar("u"),
+            SelectExpression::Column("name"),
+        ])
+        .from("users")
+        .build();
+    query.qualify_columns("u");
+    assert_eq!(query.sql(), "SELECT *, u.*, u.name FROM users");
+}
+
+// DISTINCT ON validation tests
+
+#[test]
+fn test_validate_distinct_on_accepts_matching_order() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["user_id", "created_at", "payload"])
+        .from("events")
+        .distinct_on(vec!["user_id"])
+        .order_by(vec![
+            OrderedColumn::Asc("user_id"),
+            OrderedColumn::Desc("created_at"),
+        ]);
+    assert_eq!(builder.validate_distinct_on(), Ok(()));
+    assert_eq!(
+        builder.build().sql(),
+        "SELECT DISTINCT ON (user_id) user_id, created_at, payload FROM events ORDER BY user_id ASC, created_at DESC"
+    );
+}
+
+#[test]
+fn test_validate_distinct_on_rejects_wrong_leading_column() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["user_id", "created_at"])
+        .from("events")
+        .distinct_on(vec!["user_id", "kind"])
+        .order_by(vec![
+            OrderedColumn::Asc("kind"),
+            OrderedColumn::Desc("created_at"),
+            OrderedColumn::Asc("user_id"),
+        ]);
+    let err = builder.validate_distinct_on().unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::DistinctOnOrderMismatch {
+            distinct_on: vec!["user_id".to_string(), "kind".to_string()],
+            order_by: vec![
+                "kind".to_string(),
+                "created_at".to_string(),
+                "user_id".to_string()
+            ],
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "ORDER BY must start with the DISTINCT ON expressions [\"user_id\", \"kind\"], found [\"kind\", \"created_at\", \"user_id\"]"
+    );
+}
+
+#[test]
+fn test_validate_distinct_on_accepts_leading_columns_in_any_order() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["a", "b", "c"])
+        .from("t")
+        .distinct_on(vec!["a", "b"])
+        .order_by(vec![
+            OrderedColumn::Asc("b"),
+            OrderedColumn::Desc("a"),
+            OrderedColumn::Asc("c"),
+        ]);
+    assert_eq!(builder.validate_distinct_on(), Ok(()));
+}
+
+#[test]
+fn test_validate_distinct_on_without_order_or_distinct_on() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["user_id"])
+        .from("events")
+        .distinct_on(vec!["user_id"]);
+    assert_eq!(builder.validate_distinct_on(), Ok(()));
+
+    let mut qb2 = Q();
+    let builder2 = qb2
+        .select(vec!["user_id"])
+        .from("events")
+        .distinct()
+        .order_by(vec![OrderedColumn::Desc("created_at")]);
+    assert_eq!(builder2.validate_distinct_on(), Ok(()));
+}
+
+#[test]
+fn test_validate_distinct_on_rejects_short_order_by() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["a", "b"])
+        .from("t")
+        .distinct_on(vec!["a", "b"])
+        .order_by(vec![OrderedColumn::Asc("a")]);
+    assert!(builder.validate_distinct_on().is_err());
+}
+
+// Typed interval tests
+
+#[test]
+fn test_interval_helpers() {
+    assert_eq!(interval_days(30).sql(), "INTERVAL '30 days'");
+    assert_eq!(interval_days(1).sql(), "INTERVAL '1 day'");
+    assert_eq!(interval_hours(2).sql(), "INTERVAL '2 hours'");
+    assert_eq!(interval_days(-7).sql(), "INTERVAL '-7 days'");
+}
+
+#[test]
+fn test_interval_struct_components() {
+    let i = Interval {
+        years: 1,
+        months: 2,
+        days: 3,
+        hours: 4,
+        minutes: 5,
+        seconds: 6,
+    };
+    assert_eq!(
+        i.sql(),
+        "INTERVAL '1 year 2 mons 3 days 4 hours 5 mins 6 secs'"
+    );
+    assert_eq!(Interval::default().sql(), "INTERVAL '0 secs'");
+    assert_eq!(Interval::default().minutes(1).sql(), "INTERVAL '1 min'");
+}
+
+#[test]
+fn test_interval_in_where_clause() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("sessions")
+        .where_(Term::Condition(
+            Box::new(Term::Atom("last_seen")),
+            Op::LessThan,
+            Box::new(date_sub(now(), interval_days(30))),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM sessions WHERE last_seen < NOW() - INTERVAL '30 days'"
+    );
+}
+
+// Raw DDL suffix tests
+
+#[test]
+fn test_create_table_raw_suffix() {
+    let mut tb = T("measurements");
+    let create = tb
+        .column("id", "bigint", vec!["PRIMARY KEY"])
+        .column("value", "double precision", vec![])
+        .raw_suffix("TABLESPACE archive")
+        .build_create_table();
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE measurements (id bigint PRIMARY KEY, value double precision) TABLESPACE archive"
+    );
+}
+
+#[test]
+fn test_create_table_struct_suffix() {
+    let create = CreateTable {
+        table: "logs",
+        columns: vec!["line text".to_string()],
+        suffix: Some("WITH (autovacuum_enabled = false)"),
+    };
+    assert_eq!(
+        create.sql(),
+        "CREATE TABLE logs (line text) WITH (autovacuum_enabled = false)"
+    );
+}
+
+#[test]
+fn test_raw_suffix_does_not_affect_drop_table() {
+    let mut tb = T("logs");
+    tb.raw_suffix("TABLESPACE archive");
+    assert_eq!(tb.build_drop_table().sql(), "DROP TABLE logs");
+}
+
+// Multiple lock clause tests
+
+#[test]
+fn test_multiple_lock_clauses_render_in_order() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["orders.id", "users.email"])
+        .from("orders")
+        .inner_join("users", eq("orders.user_id", "users.id"))
+        .lock(LockClause {
+            strength: LockStrength::Update,
+            of: vec!["orders"],
+            wait: LockWait::Wait,
+        })
+        .lock(LockClause {
+            strength: LockStrength::Share,
+            of: vec!["users"],
+            wait: LockWait::SkipLocked,
+        })
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT orders.id, users.email FROM orders INNER JOIN users ON orders.user_id = users.id FOR UPDATE OF orders FOR SHARE OF users SKIP LOCKED"
+    );
+}
+
+#[test]
+fn test_lock_clause_strengths_and_wait_modes() {
+    let mut lock = LockClause::new(LockStrength::NoKeyUpdate);
+    lock.of = vec!["a", "b"];
+    lock.wait = LockWait::NoWait;
+    assert_eq!(lock.sql(), "FOR NO KEY UPDATE OF a, b NOWAIT");
+    assert_eq!(
+        LockClause::new(LockStrength::KeyShare).sql(),
+        "FOR KEY SHARE"
+    );
+}
+
+#[test]
+fn test_lock_clauses_after_limit() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("jobs")
+        .limit(10)
+        .lock(LockClause {
+            strength: LockStrength::Update,
+            of: vec![],
+            wait: LockWait::SkipLocked,
+        })
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM jobs LIMIT 10 FOR UPDATE SKIP LOCKED"
+    );
+}
+
+// Simple CASE tests
+
+#[test]
+fn test_case_of_renders_operand() {
+    let t = case_of(
+        Term::Atom("priority"),
+        vec![
+            WhenThen {
+                when: Term::Atom("1"),
+                then: Term::Atom("'high'"),
+            },
+            WhenThen {
+                when: Term::Atom("2"),
+                then: Term::Atom("'medium'"),
+            },
+        ],
+        None,
+    );
+    assert_eq!(
+        t.sql(),
+        "CASE priority WHEN 1 THEN 'high' WHEN 2 THEN 'medium' END"
+    );
+}
+
+#[test]
+fn test_case_of_in_select() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("id"),
+            SelectExpression::Expr(
+                case_of(
+                    Term::Atom("status"),
+                    vec![WhenThen {
+                        when: Term::Atom("'active'"),
+                        then: Term::Atom("1"),
+                    }],
+                    Some(Term::Atom("0")),
+                ),
+                Some("is_active"),
+            ),
+        ])
+        .from("accounts")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id, CASE status WHEN 'active' THEN 1 ELSE 0 END AS is_active FROM accounts"
+    );
+}
+
+#[test]
+fn test_qualify_columns_qualifies_case_operand() {
+    let mut qb = Q();
+    let mut query = qb
+        .select_expressions(vec![SelectExpression::Expr(
+            case_of(
+                Term::Atom("kind"),
+                vec![WhenThen {
+                    when: Term::Atom("'a'"),
+                    then: Term::Atom("score"),
+                }],
+                None,
+            ),
+            Some("s"),
+        )])
+        .from("items")
+        .build();
+    query.qualify_columns("i");
+    assert_eq!(
+        query.sql(),
+        "SELECT CASE i.kind WHEN 'a' THEN i.score END AS s FROM items"
+    );
+}
+
+// Missing WHERE guard tests
+
+#[test]
+fn test_delete_try_build_requires_where() {
+    let db = D("users");
+    let err = db.try_build().err().unwrap();
+    assert_eq!(
+        err,
+        BuildError::MissingWhere {
+            table: "users".to_string()
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "statement on users has no WHERE clause; call all() to affect every row"
+    );
+}
+
+#[test]
+fn test_delete_try_build_with_where_or_all() {
+    let delete = D("sessions")
+        .where_(lt("expires_at", "NOW()"))
+        .try_build()
+        .unwrap();
+    assert_eq!(
+        delete.sql(),
+        "DELETE FROM sessions WHERE expires_at < NOW()"
+    );
+
+    let delete = D("sessions").all().returning_star().try_build().unwrap();
+    assert_eq!(delete.sql(), "DELETE FROM sessions RETURNING *");
+}
+
+#[test]
+fn test_update_try_build_guard() {
+    let mut ub = U("accounts");
+    assert!(matches!(
+        ub.set(vec![("balance", "0")]).try_build(),
+        Err(BuildError::MissingWhere { .. })
+    ));
+
+    let update = U("accounts")
+        .set(vec![("balance", "0")])
+        .where_(eq("id", "7"))
+        .try_build()
+        .unwrap();
+    assert_eq!(update.sql(), "UPDATE accounts SET balance = 0 WHERE id = 7");
+}
+
+#[test]
+fn test_build_still_allows_missing_where() {
+    let db = D("scratch");
+    assert_eq!(db.build().sql(), "DELETE FROM scratch");
+}
+
+// Upsert inserted flag tests
+
+#[test]
+fn test_returning_with_inserted_flag() {
+    let insert = I("counters")
+        .columns(vec!["key", "hits"])
+        .values(vec!["'home'", "1"])
+        .on_conflict_do_update_terms(
+            vec!["key"],
+            vec![("hits", add(Term::Atom("counters.hits"), excluded("hits")))],
+        )
+        .returning_with_inserted_flag(vec!["key", "hits"])
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO counters (key, hits) VALUES ('home', 1) ON CONFLICT (key) DO UPDATE SET hits = counters.hits + EXCLUDED.hits RETURNING key, hits, (xmax = 0) AS inserted"
+    );
+}
+
+#[test]
+fn test_returning_with_inserted_flag_only() {
+    let insert = I("tags")
+        .columns(vec!["name"])
+        .values(vec!["'rust'"])
+        .on_conflict_do_nothing(vec!["name"])
+        .returning_with_inserted_flag(vec![])
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO tags (name) VALUES ('rust') ON CONFLICT (name) DO NOTHING RETURNING (xmax = 0) AS inserted"
+    );
+}
+
+// Inline subquery builder tests
+
+#[test]
+fn test_query_builder_chains_from_temporary() {
+    let query = Q().select(vec!["id"]).from("users").limit(5).build();
+    assert_eq!(query.sql(), "SELECT id FROM users LIMIT 5");
+}
+
+#[test]
+fn test_subquery_helper_in_select_and_where() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("id"),
+            SelectExpression::Subquery(
+                Box::new(subquery(|q| {
+                    q.select(vec!["COUNT(*)"])
+                        .from("orders")
+                        .where_(eq("orders.user_id", "users.id"))
+                })),
+                Some("order_count"),
+            ),
+        ])
+        .from("users")
+        .where_(exists(subquery(|q| {
+            q.select(vec!["1"])
+                .from("logins")
+                .where_(eq("logins.user_id", "users.id"))
+        })))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id, (SELECT COUNT(*) FROM orders WHERE orders.user_id = users.id) AS order_count FROM users WHERE EXISTS (SELECT 1 FROM logins WHERE logins.user_id = users.id)"
+    );
+}
+
+#[test]
+fn test_from_subquery_with_nested_closures() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["t.user_id"])
+        .from_subquery_with("t", |q| {
+            q.select(vec!["user_id"]).from("orders").where_(in_subquery(
+                "product_id",
+                subquery(|p| {
+                    p.select(vec!["id"])
+                        .from("products")
+                        .where_(eq("featured", "TRUE"))
+                }),
+            ))
+        })
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT t.user_id FROM (SELECT user_id FROM orders WHERE product_id IN (SELECT id FROM products WHERE featured = TRUE)) AS t"
+    );
+}
+
+#[test]
+fn test_join_subquery_with_closure() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["u.name", "s.total"])
+        .from("users u")
+        .join_subquery_with(JoinType::Inner, "s", eq("u.id", "s.user_id"), |q| {
+            q.select(vec!["user_id", "SUM(amount) AS total"])
+                .from("payments")
+                .group_by(vec!["user_id"])
+        })
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT u.name, s.total FROM users u INNER JOIN (SELECT user_id, SUM(amount) AS total FROM payments GROUP BY user_id) AS s ON u.id = s.user_id"
+    );
+}
+
+// CTE order validation tests
+
+#[test]
+fn test_validate_cte_order_accepts_dependency_order() {
+    let paid = subquery(|q| {
+        q.select(vec!["*"])
+            .from("payments")
+            .where_(eq("status", "'paid'"))
+    });
+    let totals = subquery(|q| {
+        q.select(vec!["user_id", "SUM(amount) AS total"])
+            .from("paid")
+            .group_by(vec!["user_id"])
+    });
+    let mut qb = Q();
+    let builder = qb
+        .with("paid", paid)
+        .with("totals", totals)
+        .select(vec!["*"])
+        .from("totals");
+    assert_eq!(builder.validate_cte_order(), Ok(()));
+}
+
+#[test]
+fn test_validate_cte_order_detects_forward_join_reference() {
+    let report = subquery(|q| {
+        q.select(vec!["u.id", "r.total"])
+            .from("users u")
+            .inner_join("recent r", eq("r.user_id", "u.id"))
+    });
+    let recent = subquery(|q| q.select(vec!["user_id", "total"]).from("orders"));
+    let mut qb = Q();
+    let builder = qb
+        .with("report", report)
+        .with("recent", recent)
+        .select(vec!["*"])
+        .from("report");
+    let err = builder.validate_cte_order().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "CTE report references recent, which is defined after it"
+    );
+}
+
+#[test]
+fn test_validate_cte_order_ignores_partial_names_and_self() {
+    // "paid_archive" contains "paid" but is a different relation; "walk" names itself.
+    let first = subquery(|q| q.select(vec!["*"]).from("paid_archive"));
+    let walk = subquery(|q| q.select(vec!["*"]).from("walk"));
+    let paid = subquery(|q| q.select(vec!["*"]).from("payments"));
+    let mut qb = Q();
+    let builder = qb
+        .with("first", first)
+        .with("walk", walk)
+        .with("paid", paid)
+        .select(vec!["*"])
+        .from("first");
+    assert_eq!(builder.validate_cte_order(), Ok(()));
+}
+
+#[test]
+fn test_validate_cte_order_checks_data_modifying_ctes() {
+    let touched = U("accounts")
+        .set(vec![("seen", "TRUE")])
+        .from("targets")
+        .where_(eq("accounts.id", "targets.id"))
+        .returning(Columns::Star)
+        .build();
+    let targets = subquery(|q| q.select(vec!["id"]).from("queue"));
+    let mut qb = Q();
+    let builder = qb
+        .with("touched", touched)
+        .with("targets", targets)
+        .select(vec!["count(*)"])
+        .from("touched");
+    assert!(matches!(
+        builder.validate_cte_order(),
+        Err(BuildError::CteOrder { .. })
+    ));
+}
+
+// Insert column-value pair tests
+
+#[test]
+fn test_insert_set_columns_pairs() {
+    let insert = I("t").set_columns(vec![("a", "1"), ("b", "2")]).build();
+    assert_eq!(insert.sql(), "INSERT INTO t (a, b) VALUES (1, 2)");
+}
+
+#[test]
+fn test_insert_set_columns_with_params_and_returning() {
+    let mut ib = I("users");
+    let name = ib.param();
+    let email = ib.param();
+    let insert = ib
+        .set_columns(vec![
+            ("email", &email),
+            ("name", &name),
+            ("created_at", "DEFAULT"),
+        ])
+        .returning(Columns::Selected(vec!["id"]))
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO users (email, name, created_at) VALUES ($2, $1, DEFAULT) RETURNING id"
+    );
+}
+
+#[test]
+fn test_insert_set_columns_replaces_earlier_columns() {
+    let insert = I("t")
+        .columns(vec!["x"])
+        .set_columns(vec![("a", "1")])
+        .build();
+    assert_eq!(insert.sql(), "INSERT INTO t (a) VALUES (1)");
+}
+
+// Insert row arity tests
+
+#[test]
+fn test_insert_try_build_accepts_matching_rows() {
+    let insert = I("users")
+        .columns(vec!["name", "age"])
+        .rows(vec![vec!["'Alice'", "30"], vec!["'Bob'", "25"]])
+        .try_build()
+        .unwrap();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO users (name, age) VALUES ('Alice', 30), ('Bob', 25)"
+    );
+}
+
+#[test]
+fn test_insert_try_build_reports_ragged_row() {
+    let err = I("users")
+        .columns(vec!["name", "age"])
+        .rows(vec![
+            vec!["'Alice'", "30"],
+            vec!["'Bob'", "25"],
+            vec!["'Carol'", "41", "'extra'"],
+        ])
+        .try_build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        BuildError::RowArityMismatch {
+            row_index: 2,
+            expected: 2,
+            got: 3
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "row 2 has 3 values but 2 columns were given"
+    );
+}
+
+#[test]
+fn test_insert_try_build_checks_bound_rows_and_skips_select() {
+    let result = I("points")
+        .columns(vec!["x", "y"])
+        .bind_rows(vec![vec![Value::Int(1)]])
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(BuildError::RowArityMismatch { row_index: 0, .. })
+    ));
+
+    let insert = I("archive")
+        .columns(vec!["id", "name"])
+        .select(subquery(|q| q.select(vec!["*"]).from("users")))
+        .try_build()
+        .unwrap();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO archive (id, name) SELECT * FROM users"
+    );
+}
+
+// Boolean flag predicate tests
+
+#[test]
+fn test_flag_predicates_in_where() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(and(flag("active"), flag_not("banned")))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM users WHERE active AND NOT banned"
+    );
+}
+
+#[test]
+fn test_flag_not_double_negation() {
+    assert_eq!(not(flag_not("archived")).sql(), "archived");
+}
+
+#[test]
+fn test_flag_is_qualified() {
+    let mut qb = Q();
+    let mut query = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(flag_not("deleted"))
+        .build();
+    query.qualify_columns("u");
+    assert_eq!(query.sql(), "SELECT u.id FROM users WHERE NOT u.deleted");
+}
+
+// jsonb_set tests
+
+#[test]
+fn test_jsonb_set_renders_path_array() {
+    let t = jsonb_set(
+        Term::Atom("data"),
+        vec!["a", "b"],
+        Term::Atom("'\"x\"'"),
+        true,
+    );
+    assert_eq!(t.sql(), "jsonb_set(data, '{a,b}', '\"x\"', true)");
+}
+
+#[test]
+fn test_jsonb_set_quotes_awkward_keys() {
+    let t = jsonb_set(
+        Term::Atom("data"),
+        vec!["first name", "a,b", "it's", "0"],
+        Term::Atom("'1'"),
+        false,
+    );
+    assert_eq!(
+        t.sql(),
+        "jsonb_set(data, '{\"first name\",\"a,b\",it''s,0}', '1', false)"
+    );
+}
+
+#[test]
+fn test_jsonb_set_chain_in_update() {
+    let inner = jsonb_set(
+        Term::Atom("settings"),
+        vec!["theme"],
+        Term::Atom("'\"dark\"'"),
+        true,
+    );
+    let value = jsonb_set(inner, vec!["lang"], Term::Atom("'\"en\"'"), true).sql();
+    let update = U("users")
+        .set(vec![("settings", &value)])
+        .where_(eq("id", "1"))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE users SET settings = jsonb_set(jsonb_set(settings, '{theme}', '\"dark\"', true), '{lang}', '\"en\"', true) WHERE id = 1"
+    );
+}
+
+// HAVING validation tests
+
+#[test]
+fn test_validate_having_accepts_aggregates_and_grouped_columns() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["o.customer_id", "SUM(o.total)"])
+        .from("orders o")
+        .group_by(vec!["o.customer_id"])
+        .having(and(
+            Term::Condition(
+                Box::new(aggregate(
+                    "SUM",
+                    vec![Term::Atom("o.total")],
+                    AggOptions::default(),
+                )),
+                Op::GreaterThan,
+                Box::new(Term::Atom("1000")),
+            ),
+            ne("customer_id", "0"),
+        ));
+    assert_eq!(builder.validate_having(), Ok(()));
+    assert!(builder.try_build().is_ok());
+}
+
+#[test]
+fn test_validate_having_rejects_ungrouped_column() {
+    let mut qb = Q();
+    let err = qb
+        .select(vec!["dept", "COUNT(*)"])
+        .from("employees")
+        .group_by(vec!["dept"])
+        .having(and(gt("COUNT(*)", "5"), gt("salary", "1000")))
+        .try_build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        BuildError::InvalidHaving {
+            column: "salary".to_string()
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "HAVING references salary, which is neither aggregated nor grouped"
+    );
+}
+
+#[test]
+fn test_validate_having_accepts_statistical_aggregates() {
+    for name in ["variance", "stddev_samp", "corr", "regr_slope", "bit_xor"] {
+        let mut qb = Q();
+        let builder = qb
+            .select(vec!["dept"])
+            .from("scores")
+            .group_by(vec!["dept"])
+            .having(gt(Term::Function(name, vec![Term::Atom("score")]), "1"));
+        assert_eq!(builder.validate_having(), Ok(()), "{}", name);
+    }
+}
+
+#[test]
+fn test_validate_having_without_group_by_requires_aggregates() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["COUNT(*)"])
+        .from("events")
+        .having(Term::Condition(
+            Box::new(func("max", vec![Term::Atom("created_at")])),
+            Op::GreaterThan,
+            Box::new(Term::Atom("NOW()")),
+        ));
+    assert_eq!(builder.validate_having(), Ok(()));
+
+    let mut qb2 = Q();
+    let builder2 = qb2
+        .select(vec!["COUNT(*)"])
+        .from("events")
+        .having(eq("kind", "'click'"));
+    assert!(builder2.validate_having().is_err());
+}
+
+#[test]
+fn test_try_build_runs_distinct_on_validation() {
+    let mut qb = Q();
+    let result = qb
+        .select(vec!["a"])
+        .from("t")
+        .distinct_on(vec!["a"])
+        .order_by(vec![OrderedColumn::Asc("b")])
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(BuildError::DistinctOnOrderMismatch { .. })
+    ));
+}
+
+// DISTINCT ORDER BY validation tests
+
+#[test]
+fn test_validate_distinct_order_by_accepts_selected_and_aliased() {
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["city", "lower(country) AS country_key"])
+        .from("users")
+        .distinct()
+        .order_by(vec![
+            OrderedColumn::Asc("country_key"),
+            OrderedColumn::Asc("city"),
+        ]);
+    assert_eq!(builder.validate_distinct_order_by(), Ok(()));
+}
+
+#[test]
+fn test_validate_distinct_order_by_reports_all_missing_columns() {
+    let mut qb = Q();
+    let err = qb
+        .select(vec!["city"])
+        .from("users")
+        .distinct()
+        .order_by(vec![
+            OrderedColumn::Asc("city"),
+            OrderedColumn::Desc("created_at"),
+            OrderedColumn::Asc("id"),
+        ])
+        .try_build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        BuildError::DistinctOrderByNotSelected {
+            columns: vec!["created_at".to_string(), "id".to_string()]
+        }
+    );
+}
+
+#[test]
+fn test_validate_distinct_order_by_with_expressions_and_stars() {
+    let mut qb = Q();
+    let builder = qb
+        .select_expressions(vec![
+            SelectExpression::TableStar("u"),
+            SelectExpression::Expr(upper(Term::Atom("u.name")), Some("shout")),
+        ])
+        .from("users u")
+        .distinct()
+        .order_by(vec![
+            OrderedColumn::Asc("u.email"),
+            OrderedColumn::Asc("shout"),
+        ]);
+    assert_eq!(builder.validate_distinct_order_by(), Ok(()));
+
+    let mut qb2 = Q();
+    let builder2 = qb2
+        .select(vec!["a"])
+        .from("t")
+        .order_by(vec![OrderedColumn::Asc("b")]);
+    assert_eq!(builder2.validate_distinct_order_by(), Ok(()));
+}
+
+// Term combinator method tests
+
+#[test]
+fn test_term_method_chaining() {
+    let t = eq("a", "1").and(gt("b", "2")).or(is_null("c"));
+    assert_eq!(t.sql(), "a = 1 AND b > 2 OR c IS NULL");
+}
+
+#[test]
+fn test_and_parenthesizes_or_operands() {
+    let t = eq("status", "'open'").and(eq("owner", "1").or(eq("assignee", "1")));
+    assert_eq!(t.sql(), "status = 'open' AND (owner = 1 OR assignee = 1)");
+    let free = and(
+        or(eq("a", "1"), eq("b", "2")),
+        or(eq("c", "3"), eq("d", "4")),
+    );
+    assert_eq!(free.sql(), "(a = 1 OR b = 2) AND (c = 3 OR d = 4)");
+}
+
+#[test]
+fn test_term_not_method_matches_free_function() {
+    let cond = eq("x", "1").or(eq("y", "2"));
+    assert!(cond.clone().not() == not(cond));
+    assert_eq!(
+        between("age", "18", "65").not().sql(),
+        "age NOT BETWEEN 18 AND 65"
+    );
+}
+
+#[test]
+fn test_term_methods_in_where() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("tickets")
+        .where_(flag("open").and(like("title", "'%bug%'").or(gt("priority", "3"))))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM tickets WHERE open AND (title LIKE '%bug%' OR priority > 3)"
+    );
+}
+
+#[test]
+fn test_and_where_parenthesizes_or_conditions() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(or(eq("role", "'admin'"), eq("role", "'owner'")))
+        .and_where(flag("active"))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM users WHERE (role = 'admin' OR role = 'owner') AND active"
+    );
+
+    let delete = D("sessions")
+        .and_where(eq("user_id", "1"))
+        .and_where(or(is_null("expires_at"), lt("expires_at", "NOW()")))
+        .build();
+    assert_eq!(
+        delete.sql(),
+        "DELETE FROM sessions WHERE user_id = 1 AND (expires_at IS NULL OR expires_at < NOW())"
+    );
+}
+
+// OVERLAY, POSITION and regex SUBSTRING tests
+
+#[test]
+fn test_overlay_with_and_without_length() {
+    assert_eq!(
+        overlay(
+            Term::Atom("code"),
+            Term::Atom("'X'"),
+            Term::Atom("2"),
+            Some(Term::Atom("1"))
+        )
+        .sql(),
+        "OVERLAY(code PLACING 'X' FROM 2 FOR 1)"
+    );
+    assert_eq!(
+        overlay(
+            Term::Atom("phone"),
+            Term::Atom("'***'"),
+            Term::Atom("1"),
+            None
+        )
+        .sql(),
+        "OVERLAY(phone PLACING '***' FROM 1)"
+    );
+}
+
+#[test]
+fn test_substring_regex_and_position_in_select() {
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Expr(
+                substring_regex(Term::Atom("email"), Term::Atom("'@(.*)$'")),
+                Some("domain"),
+            ),
+            SelectExpression::Expr(
+                position(Term::Atom("'@'"), Term::Atom("email")),
+                Some("at_pos"),
+            ),
+        ])
+        .from("users")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT SUBSTRING(email FROM '@(.*)$') AS domain, POSITION('@' IN email) AS at_pos FROM users"
+    );
+}
+
+#[test]
+fn test_overlay_and_position_are_qualified() {
+    let mut qb = Q();
+    let mut query = qb
+        .select_expressions(vec![SelectExpression::Expr(
+            overlay(
+                Term::Atom("sku"),
+                Term::Atom("'-'"),
+                position(Term::Atom("'_'"), Term::Atom("sku")),
+                Some(Term::Atom("1")),
+            ),
+            Some("clean"),
+        )])
+        .from("items")
+        .build();
+    query.qualify_columns("i");
+    assert_eq!(
+        query.sql(),
+        "SELECT OVERLAY(i.sku PLACING '-' FROM POSITION('_' IN i.sku) FOR 1) AS clean FROM items"
+    );
+}
+
+// Script tests
+
+#[test]
+fn test_script_renders_heterogeneous_statements() {
+    let mut tb = T("tags");
+    let create = tb
+        .column("id", "serial", vec!["PRIMARY KEY"])
+        .column("name", "text", vec!["NOT NULL"])
+        .build_create_table();
+    let insert = I("tags")
+        .columns(vec!["name"])
+        .rows(vec![vec!["'rust'"], vec!["'sql'"]])
+        .build();
+    let mut qb = Q();
+    let query = qb.select(vec!["count(*)"]).from("tags").build();
+
+    let mut script = Script::new();
+    script.push(create).push(insert).push(query);
+    assert_eq!(script.len(), 3);
+    assert_eq!(
+        script.sql(),
+        "CREATE TABLE tags (id serial PRIMARY KEY, name text NOT NULL);\nINSERT INTO tags (name) VALUES ('rust'), ('sql');\nSELECT count(*) FROM tags;"
+    );
+}
+
+#[test]
+fn test_script_statements_iterator() {
+    let mut script = Script::new();
+    script
+        .push(DropTable { table: "old_a" })
+        .push(DropTable { table: "old_b" });
+    let rendered: Vec<String> = script.statements().collect();
+    assert_eq!(rendered, vec!["DROP TABLE old_a", "DROP TABLE old_b"]);
+}
+
+#[test]
+fn test_empty_script() {
+    let script = Script::default();
+    assert!(script.is_empty());
+    assert_eq!(script.sql(), "");
+}
+
+// Target-less ON CONFLICT tests
+
+#[test]
+fn test_on_conflict_do_nothing_without_target() {
+    assert_eq!(
+        OnConflict::DoNothing(vec![]).sql(),
+        "ON CONFLICT DO NOTHING"
+    );
+    assert_eq!(
+        OnConflict::DoNothing(vec!["a", "b"]).sql(),
+        "ON CONFLICT (a, b) DO NOTHING"
+    );
+}
+
+#[test]
+fn test_on_conflict_do_nothing_any_builder() {
+    let insert = I("page_views")
+        .columns(vec!["page_id", "visitor_id"])
+        .values(vec!["1", "42"])
+        .on_conflict_do_nothing_any()
+        .returning(Columns::Selected(vec!["page_id"]))
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO page_views (page_id, visitor_id) VALUES (1, 42) ON CONFLICT DO NOTHING RETURNING page_id"
+    );
+}
+
+// Window frame tests
+
+#[test]
+fn test_frame_bounds_render() {
+    assert_eq!(
+        Frame::between(
+            FrameMode::Groups,
+            FrameBound::Preceding(2),
+            FrameBound::Following(3)
+        )
+        .sql(),
+        "GROUPS BETWEEN 2 PRECEDING AND 3 FOLLOWING"
+    );
+    assert_eq!(
+        Frame::between(
+            FrameMode::Rows,
+            FrameBound::CurrentRow,
+            FrameBound::UnboundedFollowing
+        )
+        .sql(),
+        "ROWS BETWEEN CURRENT ROW AND UNBOUNDED FOLLOWING"
+    );
+    assert_eq!(
+        Frame::new(FrameMode::Range, FrameBound::UnboundedPreceding).sql(),
+        "RANGE UNBOUNDED PRECEDING"
+    );
+}
+
+#[test]
+fn test_moving_average_with_typed_frame() {
+    let spec = WindowSpec {
+        partition_by: vec!["sensor_id"],
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Asc("read_at")],
+        }),
+        frame: Some(Frame::between(
+            FrameMode::Rows,
+            FrameBound::Preceding(6),
+            FrameBound::CurrentRow,
+        )),
+    };
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![
+            SelectExpression::Column("read_at"),
+            SelectExpression::Expr(over(Term::Atom("avg(value)"), spec), Some("avg_7")),
+        ])
+        .from("readings")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT read_at, avg(value) OVER (PARTITION BY sensor_id ORDER BY read_at ASC ROWS BETWEEN 6 PRECEDING AND CURRENT ROW) AS avg_7 FROM readings"
+    );
+}
+
+#[test]
+fn test_range_frame_with_interval_offset_and_exclude() {
+    let spec = WindowSpec {
+        partition_by: vec!["account_id"],
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Asc("paid_at")],
+        }),
+        frame: Some(
+            Frame::between(
+                FrameMode::Range,
+                FrameBound::preceding_by(interval_days(7)),
+                FrameBound::CurrentRow,
+            )
+            .exclude(FrameExclusion::CurrentRow),
+        ),
+    };
+    assert_eq!(
+        over(Term::Atom("sum(amount)"), spec).sql(),
+        "sum(amount) OVER (PARTITION BY account_id ORDER BY paid_at ASC RANGE BETWEEN INTERVAL '7 days' PRECEDING AND CURRENT ROW EXCLUDE CURRENT ROW)"
+    );
+    assert_eq!(
+        Frame::between(
+            FrameMode::Groups,
+            FrameBound::UnboundedPreceding,
+            FrameBound::following_by("1"),
+        )
+        .exclude(FrameExclusion::Ties)
+        .sql(),
+        "GROUPS BETWEEN UNBOUNDED PRECEDING AND 1 FOLLOWING EXCLUDE TIES"
+    );
+}
+
+#[test]
+fn test_frame_offset_placeholder_is_bound() {
+    let mut qb = Q();
+    let days = qb.bind_as("7 days", "interval");
+    let spec = WindowSpec {
+        partition_by: vec![],
+        order_by: Some(OrderBy {
+            columns: vec![OrderedColumn::Asc("at")],
+        }),
+        frame: Some(Frame::new(
+            FrameMode::Range,
+            FrameBound::preceding_by(&days),
+        )),
+    };
+    let (sql, values) = qb
+        .select_expressions(vec![SelectExpression::Expr(
+            over(Term::Atom("count(*)"), spec),
+            None,
+        )])
+        .from("events")
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT count(*) OVER (ORDER BY at ASC RANGE $1::interval PRECEDING) FROM events"
+    );
+    assert_eq!(values, vec![Value::Text("7 days".to_string())]);
+}
+
+// Numeric helper tests
+
+#[test]
+fn test_greatest_and_least() {
+    assert_eq!(
+        greatest(vec![Term::Atom("a"), Term::Atom("b"), Term::Atom("c")]).sql(),
+        "GREATEST(a, b, c)"
+    );
+    assert_eq!(
+        least(vec![Term::Atom("quota"), Term::Atom("100")]).sql(),
+        "LEAST(quota, 100)"
+    );
+}
+
+#[test]
+fn test_numeric_helpers() {
+    assert_eq!(abs(Term::Atom("delta")).sql(), "ABS(delta)");
+    assert_eq!(round(Term::Atom("score"), Some(1)).sql(), "ROUND(score, 1)");
+    assert_eq!(ceil(Term::Atom("pages")).sql(), "CEIL(pages)");
+    assert_eq!(floor(Term::Atom("pages")).sql(), "FLOOR(pages)");
+    assert_eq!(mod_(Term::Atom("id"), Term::Atom("4")).sql(), "MOD(id, 4)");
+}
+
+#[test]
+fn test_clamp_in_select() {
+    let clamped = least(vec![
+        greatest(vec![Term::Atom("discount"), Term::Atom("0")]),
+        Term::Atom("50"),
+    ]);
+    let mut qb = Q();
+    let query = qb
+        .select_expressions(vec![SelectExpression::Expr(clamped, Some("discount"))])
+        .from("orders")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT LEAST(GREATEST(discount, 0), 50) AS discount FROM orders"
+    );
+}
+
+// DELETE/UPDATE target table tests
+
+#[test]
+fn test_delete_with_schema_alias_and_using() {
+    let delete = D("orders")
+        .schema("public")
+        .alias("o")
+        .using("customers c")
+        .using("regions r")
+        .where_(and(eq("o.customer_id", "c.id"), eq("c.region_id", "r.id")))
+        .returning(Columns::Selected(vec!["o.id"]))
+        .build();
+    assert_eq!(
+        delete.sql(),
+        "DELETE FROM public.orders AS o USING customers c, regions r WHERE o.customer_id = c.id AND c.region_id = r.id RETURNING o.id"
+    );
+}
+
+#[test]
+fn test_update_target_schema_keeps_table_name_as_given() {
+    let update = U("Orders")
+        .schema("Sales")
+        .set(vec![("status", "'shipped'")])
+        .where_(eq("id", "3"))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE Sales.Orders SET status = 'shipped' WHERE id = 3"
+    );
+    let unqualified = U("Orders").set(vec![("status", "'shipped'")]).build();
+    assert_eq!(unqualified.sql(), "UPDATE Orders SET status = 'shipped'");
+    let quoted = quote_ident("Orders");
+    let update = U(&quoted)
+        .schema("sales")
+        .set(vec![("status", "'shipped'")])
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE sales.\"Orders\" SET status = 'shipped'"
+    );
+}
+
+#[test]
+fn test_target_table_plain_string_unchanged() {
+    let delete = Delete {
+        with_clause: None,
+        table: TargetTable {
+            schema: None,
+            name: "users",
+            alias: Some("u"),
+        },
+        using: vec![],
+        where_clause: Some(eq("u.id", "1")),
+        returning: None,
+        params: Params::default(),
+    };
+    assert_eq!(delete.sql(), "DELETE FROM users AS u WHERE u.id = 1");
+    let mut db = D("users");
+    assert_eq!(db.all().try_build().unwrap().sql(), "DELETE FROM users");
+}
+
+// Row-lock builder tests
+
+#[test]
+fn test_lock_strength_builders() {
+    assert_eq!(
+        Q().select(vec!["*"]).from("t").for_share().build().sql(),
+        "SELECT * FROM t FOR SHARE"
+    );
+    assert_eq!(
+        Q().select(vec!["*"])
+            .from("t")
+            .for_key_share()
+            .build()
+            .sql(),
+        "SELECT * FROM t FOR KEY SHARE"
+    );
+    assert_eq!(
+        Q().select(vec!["*"])
+            .from("t")
+            .for_no_key_update()
+            .build()
+            .sql(),
+        "SELECT * FROM t FOR NO KEY UPDATE"
+    );
+}
+
+#[test]
+fn test_job_queue_skip_locked() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id", "payload"])
+        .from("jobs")
+        .where_(eq("state", "'pending'"))
+        .order_by(vec![OrderedColumn::Asc("id")])
+        .limit(10)
+        .for_update_of(vec!["jobs"])
+        .skip_locked()
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id, payload FROM jobs WHERE state = 'pending' ORDER BY id ASC LIMIT 10 FOR UPDATE OF jobs SKIP LOCKED"
+    );
+}
+
+#[test]
+fn test_lock_modifiers_apply_to_last_clause() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("a")
+        .inner_join("b", eq("a.id", "b.a_id"))
+        .for_update_of(vec!["a"])
+        .for_key_share()
+        .lock_of(vec!["b"])
+        .nowait()
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM a INNER JOIN b ON a.id = b.a_id FOR UPDATE OF a FOR KEY SHARE OF b NOWAIT"
+    );
+    let mut qb = Q();
+    assert_eq!(
+        qb.select(vec!["*"]).from("a").skip_locked().build().sql(),
+        "SELECT * FROM a"
+    );
+}
+
+// Into<Term> conversion tests
+
+#[test]
+fn test_term_from_str_and_string() {
+    assert!(Term::from("id") == Term::Atom("id"));
+    let owned = format!("{} + 1", "version");
+    assert_eq!(Term::from(owned).sql(), "version + 1");
+}
+
+#[test]
+fn test_comparison_helpers_accept_terms() {
+    let t = gte(coalesce(vec![Term::Atom("score"), Term::Atom("0")]), "10");
+    assert_eq!(t.sql(), "COALESCE(score, 0) >= 10");
+    let t = like(lower(Term::Atom("name")), "'al%'");
+    assert_eq!(t.sql(), "LOWER(name) LIKE 'al%'");
+}
+
+#[test]
+fn test_comparison_helpers_accept_placeholders() {
+    let mut qb = Q();
+    let id = qb.param();
+    let limit = format!("{}", 5);
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(eq("id", &id), lt("rank", limit)))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM users WHERE id = $1 AND rank < 5"
+    );
+}
+
+// NOT IN subquery tests
+
+#[test]
+fn test_not_in_subquery() {
+    let mut sub = Q();
+    let inactive = sub
+        .select(vec!["id"])
+        .from("accounts")
+        .where_(eq("active", "false"))
+        .build();
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("orders")
+        .where_(not_in_subquery("account_id", inactive))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM orders WHERE account_id NOT IN (SELECT id FROM accounts WHERE active = false)"
+    );
+}
+
+#[test]
+fn test_not_flips_in_subquery() {
+    let sub = subquery(|q| q.select(vec!["id"]).from("vips"));
+    let t = not(in_subquery("user_id", sub));
+    assert_eq!(t.sql(), "user_id NOT IN (SELECT id FROM vips)");
+    assert_eq!(not(t).sql(), "user_id IN (SELECT id FROM vips)");
+    assert_eq!(Op::NotIn.sql(), "NOT IN");
+}
+
+// count_affected tests
+
+#[test]
+fn test_count_affected_keeps_existing_returning() {
+    let delete = D("sessions")
+        .where_(lt("expires_at", "NOW()"))
+        .returning(Columns::Selected(vec!["id"]))
+        .build();
+    assert_eq!(
+        count_affected(delete).sql(),
+        "WITH affected AS (DELETE FROM sessions WHERE expires_at < NOW() RETURNING id) SELECT count(*) FROM affected"
+    );
+}
+
+#[test]
+fn test_count_affected_insert_adds_returning() {
+    let insert = I("tags")
+        .columns(vec!["name"])
+        .values(vec!["'rust'"])
+        .on_conflict_do_nothing_any()
+        .build();
+    assert_eq!(
+        count_affected(insert).sql(),
+        "WITH affected AS (INSERT INTO tags (name) VALUES ('rust') ON CONFLICT DO NOTHING RETURNING 1) SELECT count(*) FROM affected"
+    );
+}
+
+// having_term tests
+
+#[test]
+fn test_having_term_reuses_predicate_across_queries() {
+    let big = gt("sum(total)", "1000");
+    let mut qb = Q();
+    let by_region = qb
+        .select(vec!["region", "sum(total)"])
+        .from("sales")
+        .group_by(vec!["region"])
+        .having_term(&big)
+        .build();
+    let mut qb = Q();
+    let by_rep = qb
+        .select(vec!["rep", "sum(total)"])
+        .from("sales")
+        .group_by(vec!["rep"])
+        .having_term(&big)
+        .build();
+    assert_eq!(
+        by_region.sql(),
+        "SELECT region, sum(total) FROM sales GROUP BY region HAVING sum(total) > 1000"
+    );
+    assert_eq!(
+        by_rep.sql(),
+        "SELECT rep, sum(total) FROM sales GROUP BY rep HAVING sum(total) > 1000"
+    );
+    assert_eq!(big.sql(), "sum(total) > 1000");
+}
+
+#[test]
+fn test_having_term_with_same_predicate_in_where() {
+    let recent = gt("year", "2020");
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["year", "count(*)"])
+        .from("films")
+        .where_(recent.clone())
+        .group_by(vec!["year"])
+        .having_term(&recent)
+        .try_build()
+        .unwrap();
+    assert_eq!(
+        query.sql(),
+        "SELECT year, count(*) FROM films WHERE year > 2020 GROUP BY year HAVING year > 2020"
+    );
+}
+
+// Temporal literal tests
+
+#[test]
+fn test_temporal_literals() {
+    assert_eq!(date_lit("2024-02-29"), "DATE '2024-02-29'");
+    assert_eq!(time_lit("08:30"), "TIME '08:30'");
+    assert_eq!(
+        timestamp_lit("2024-01-01 00:00:00"),
+        "TIMESTAMP '2024-01-01 00:00:00'"
+    );
+    assert_eq!(
+        timestamptz_lit("2024-01-01 00:00:00+00"),
+        "TIMESTAMPTZ '2024-01-01 00:00:00+00'"
+    );
+    assert_eq!(date_lit("x' OR '1"), "DATE 'x'' OR ''1'");
+}
+
+#[test]
+fn test_temporal_literals_in_filter() {
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["id"])
+        .from("events")
+        .where_(and(
+            gte("starts_at", timestamptz_lit("2024-06-01 00:00+00")),
+            lt("day", date_lit("2024-07-01")),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id FROM events WHERE starts_at >= TIMESTAMPTZ '2024-06-01 00:00+00' AND day < DATE '2024-07-01'"
+    );
+}
+
+// Single-expression I/U/D builder tests
+
+#[test]
+fn test_statement_builders_chain_from_temporary() {
+    assert_eq!(
+        I("t").columns(vec!["a"]).values(vec!["1"]).build().sql(),
+        "INSERT INTO t (a) VALUES (1)"
+    );
+    assert_eq!(
+        U("t")
+            .set(vec![("a", "2")])
+            .where_(eq("a", "1"))
+            .build()
+            .sql(),
+        "UPDATE t SET a = 2 WHERE a = 1"
+    );
+    assert_eq!(
+        D("t").where_(eq("a", "2")).build().sql(),
+        "DELETE FROM t WHERE a = 2"
+    );
+}
+
+#[test]
+fn test_statement_builder_reusable_after_chain() {
+    let mut db = D("logs");
+    db.where_(lt("at", "NOW()"));
+    let without_returning = db.build();
+    let with_returning = db.returning_star().build();
+    assert_eq!(without_returning.sql(), "DELETE FROM logs WHERE at < NOW()");
+    assert_eq!(
+        with_returning.sql(),
+        "DELETE FROM logs WHERE at < NOW() RETURNING *"
+    );
+}
+
+// as_cte tests
+
+#[test]
+fn test_as_cte_with_materialized_hint() {
+    let mut cte = Q()
+        .select(vec!["id", "total"])
+        .from("orders")
+        .where_(gt("total", "100"))
+        .build()
+        .as_cte("big");
+    cte.materialized = Some(true);
+    let query = Q()
+        .with_cte(cte)
+        .select(vec!["count(*)"])
+        .from("big")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "WITH big AS MATERIALIZED (SELECT id, total FROM orders WHERE total > 100) SELECT count(*) FROM big"
+    );
+}
+
+#[test]
+fn test_with_cte_mixes_with_named_with() {
+    let active = Q()
+        .select(vec!["id"])
+        .from("users")
+        .where_(eq("active", "true"))
+        .build();
+    let query = Q()
+        .with_cte(active.as_cte("active"))
+        .with(
+            "removed",
+            D("sessions")
+                .using("active")
+                .where_(eq("sessions.user_id", "active.id"))
+                .returning_star()
+                .build(),
+        )
+        .select(vec!["count(*)"])
+        .from("removed")
+        .try_build()
+        .unwrap();
+    assert_eq!(
+        query.sql(),
+        "WITH active AS (SELECT id FROM users WHERE active = true), removed AS (DELETE FROM sessions USING active WHERE sessions.user_id = active.id RETURNING *) SELECT count(*) FROM removed"
+    );
+}
+
+#[test]
+fn test_cte_order_sees_delete_using() {
+    let result = Q()
+        .with(
+            "removed",
+            D("sessions")
+                .using("stale")
+                .where_(eq("sessions.id", "stale.id"))
+                .returning_star()
+                .build(),
+        )
+        .with_cte(
+            Q().select(vec!["id"])
+                .from("sessions")
+                .build()
+                .as_cte("stale"),
+        )
+        .select(vec!["*"])
+        .from("removed")
+        .try_build();
+    assert!(matches!(result, Err(BuildError::CteOrder { .. })));
+}
+
+// Operator parsing tests
+
+#[test]
+fn test_every_op_round_trips_through_sql() {
+    let ops = vec![
+        Op::And,
+        Op::Or,
+        Op::Equals,
+        Op::NotEquals,
+        Op::GreaterThan,
+        Op::LessThan,
+        Op::GreaterOrEqual,
+        Op::LessOrEqual,
+        Op::Like,
+        Op::In,
+        Op::NotIn,
+        Op::Exists,
+        Op::NotExists,
+        Op::Any,
+        Op::All,
+        Op::Overlaps,
+        Op::Plus,
+        Op::Minus,
+        Op::Multiply,
+        Op::Divide,
+        Op::O("||"),
+    ];
+    for op in ops {
+        let rendered = op.sql();
+        assert!(parse_op(&rendered) == op, "{} did not round-trip", rendered);
+    }
+}
+
+#[test]
+fn test_parse_op_aliases_and_fallback() {
+    assert!(parse_op("<>") == Op::NotEquals);
+    assert!(parse_op(" like ") == Op::Like);
+    assert!(Op::from("?|") == Op::O("?|"));
+}
+
+#[test]
+fn test_condition_from_filter_dsl() {
+    let filters = [("age", "<=", "30"), ("name", "like", "'a%'")];
+    let term = filters
+        .iter()
+        .map(|(col, op, val)| {
+            Term::Condition(
+                Box::new(Term::Atom(col)),
+                parse_op(op),
+                Box::new(Term::Atom(val)),
+            )
+        })
+        .reduce(and)
+        .unwrap();
+    assert_eq!(term.sql(), "age <= 30 AND name LIKE 'a%'");
+}
+
+// ANY/ALL expression tests
+
+#[test]
+fn test_all_with_expression_left_operand() {
+    let caps = subquery(|q| q.select(vec!["cap"]).from("limits"));
+    let t = all(
+        Term::Condition(
+            Box::new(Term::Atom("used")),
+            Op::Multiply,
+            Box::new(Term::Atom("2")),
+        ),
+        Op::LessOrEqual,
+        caps,
+    );
+    assert_eq!(t.sql(), "(used * 2) <= ALL (SELECT cap FROM limits)");
+}
+
+#[test]
+fn test_any_is_typed_and_qualifiable() {
+    let sub = subquery(|q| q.select(vec!["price"]).from("rivals"));
+    let t = any(lower(Term::Atom("sku")), Op::Equals, sub);
+    assert!(matches!(t, Term::AnyAll { all: false, .. }));
+    assert_eq!(t.sql(), "LOWER(sku) = ANY (SELECT price FROM rivals)");
+    let query = Q()
+        .select(vec!["*"])
+        .from("products")
+        .where_(any(
+            "price",
+            Op::GreaterThan,
+            Q().select(vec!["price"]).from("rivals").build(),
+        ))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM products WHERE price > ANY (SELECT price FROM rivals)"
+    );
+}
+
+// Row comparison against subquery tests
+
+#[test]
+fn test_row_eq_subquery_in_where() {
+    let mut qb = Q();
+    let id = qb.param();
+    let inner = Q()
+        .select(vec!["owner_id", "team_id"])
+        .from("projects")
+        .where_(eq("id", &id))
+        .build();
+    let query = qb
+        .select(vec!["*"])
+        .from("memberships")
+        .where_(row_eq_subquery(vec!["user_id", "team_id"], inner))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM memberships WHERE (user_id, team_id) = (SELECT owner_id, team_id FROM projects WHERE id = $1)"
+    );
+    assert_eq!(query.validate_params(), Ok(()));
+}
+
+#[test]
+fn test_row_eq_subquery_negated() {
+    let inner = subquery(|q| q.select(vec!["a", "b"]).from("t").limit(1));
+    assert_eq!(
+        not(row_eq_subquery(vec!["x", "y"], inner)).sql(),
+        "NOT (x, y) = (SELECT a, b FROM t LIMIT 1)"
+    );
+}
+
+// inner_join_on_eq tests
+
+#[test]
+fn test_inner_join_on_eq_chain() {
+    let query = Q()
+        .select(vec!["u.name", "o.total", "p.title"])
+        .from("users u")
+        .inner_join_on_eq("orders o", "u.id", "o.user_id")
+        .inner_join_on_eq("products p", "o.product_id", "p.id")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT u.name, o.total, p.title FROM users u INNER JOIN orders o ON u.id = o.user_id INNER JOIN products p ON o.product_id = p.id"
+    );
+}
+
+#[test]
+fn test_inner_join_on_eq_matches_inner_join() {
+    let a = Q()
+        .select(vec!["*"])
+        .from("a")
+        .inner_join_on_eq("b", "a.id", "b.a_id")
+        .build();
+    let b = Q()
+        .select(vec!["*"])
+        .from("a")
+        .inner_join("b", eq("a.id", "b.a_id"))
+        .build();
+    assert!(a == b);
+}
+
+// Parameter select expression tests
+
+#[test]
+fn test_select_param_echo() {
+    let mut qb = Q();
+    let greeting = qb.param();
+    let query = qb
+        .select_expressions(vec![SelectExpression::param(
+            &greeting,
+            "text",
+            Some("greeting"),
+        )])
+        .build();
+    assert_eq!(query.sql(), "SELECT $1::text AS greeting");
+}
+
+#[test]
+fn test_select_param_numbering_with_where_params() {
+    let mut qb = Q();
+    let tag = qb.bind("vip");
+    let min = qb.bind(100);
+    let (sql, values) = qb
+        .select_expressions(vec![
+            SelectExpression::Column("id"),
+            SelectExpression::param(&tag, "text", None),
+        ])
+        .from("accounts")
+        .where_(gte("balance", &min))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(sql, "SELECT id, $1::text FROM accounts WHERE balance >= $2");
+    assert_eq!(
+        values,
+        vec![Value::Text("vip".to_string()), Value::Int(100)]
+    );
+}
+
+// GROUP BY expression tests
+
+#[test]
+fn test_group_by_exprs_time_bucket() {
+    let hour = func(
+        "date_trunc",
+        vec![Term::Atom("'hour'"), Term::Atom("created_at")],
+    );
+    let query = Q()
+        .select_expressions(vec![
+            SelectExpression::Expr(hour.clone(), Some("hour")),
+            SelectExpression::Column("region"),
+            SelectExpression::Column("sum(amount)"),
+        ])
+        .from("sales")
+        .group_by_exprs(vec![hour, Term::Atom("region")])
+        .order_by(vec![OrderedColumn::Asc("hour")])
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT date_trunc('hour', created_at) AS hour, region, sum(amount) FROM sales GROUP BY date_trunc('hour', created_at), region ORDER BY hour ASC"
+    );
+}
+
+#[test]
+fn test_having_validation_sees_group_by_exprs() {
+    let result = Q()
+        .select(vec!["region", "count(*)"])
+        .from("sales")
+        .group_by_exprs(vec![Term::Atom("region")])
+        .having(ne("region", "'eu'"))
+        .try_build();
+    assert!(result.is_ok());
+    let result = Q()
+        .select(vec!["count(*)"])
+        .from("sales")
+        .group_by_exprs(vec![lower(Term::Atom("region"))])
+        .having(ne("country", "'fr'"))
+        .try_build();
+    assert_eq!(
+        result.err(),
+        Some(BuildError::InvalidHaving {
+            column: "country".to_string()
+        })
+    );
+}
+
+// Literal LIKE pattern tests
+
+#[test]
+fn test_contains_escapes_wildcards() {
+    assert_eq!(
+        contains("title", "50%_off\\").sql(),
+        r"title LIKE '%50\%\_off\\%' ESCAPE '\'"
+    );
+}
+
+#[test]
+fn test_starts_and_ends_with() {
+    assert_eq!(
+        starts_with("path", "/usr/").sql(),
+        r"path LIKE '/usr/%' ESCAPE '\'"
+    );
+    assert_eq!(
+        ends_with(lower(Term::Atom("email")), "@example.com").sql(),
+        r"LOWER(email) LIKE '%@example.com' ESCAPE '\'"
+    );
+}
+
+#[test]
+fn test_contains_negated_in_query() {
+    let query = Q()
+        .select(vec!["id"])
+        .from("posts")
+        .where_(not(contains("body", "o'brien")))
+        .build();
+    assert_eq!(
+        query.sql(),
+        r"SELECT id FROM posts WHERE body NOT LIKE '%o''brien%' ESCAPE '\'"
+    );
+}
+
+// Set operation tests
+
+#[test]
+fn test_union_all_with_distinct_branch() {
+    let compound = Q()
+        .select(vec!["a"])
+        .distinct()
+        .from("x")
+        .build()
+        .union_all(Q().select(vec!["a"]).from("y").build());
+    assert_eq!(
+        compound.sql(),
+        "(SELECT DISTINCT a FROM x) UNION ALL (SELECT a FROM y)"
+    );
+}
+
+#[test]
+fn test_branch_order_and_limit_stay_in_branch() {
+    let newest = Q()
+        .select(vec!["id", "created_at"])
+        .distinct_on(vec!["author_id"])
+        .from("posts")
+        .order_by(vec![
+            OrderedColumn::Asc("author_id"),
+            OrderedColumn::Desc("created_at"),
+        ])
+        .build();
+    let pinned = Q()
+        .select(vec!["id", "created_at"])
+        .from("pinned_posts")
+        .limit(3)
+        .build();
+    let compound = newest
+        .union(pinned)
+        .order_by(vec![OrderedColumn::Desc("created_at")])
+        .limit(20)
+        .offset(40);
+    assert_eq!(
+        compound.sql(),
+        "(SELECT DISTINCT ON (author_id) id, created_at FROM posts ORDER BY author_id ASC, created_at DESC) UNION (SELECT id, created_at FROM pinned_posts LIMIT 3) ORDER BY created_at DESC LIMIT 20 OFFSET 40"
+    );
+}
+
+#[test]
+fn test_set_operators_chain() {
+    let q = |t| Q().select(vec!["id"]).from(t).build();
+    let compound = q("a")
+        .combine(SetOperator::Intersect, q("b"))
+        .combine(SetOperator::ExceptAll, q("c"))
+        .union_all(q("d"));
+    assert_eq!(
+        compound.sql(),
+        "(SELECT id FROM a) INTERSECT (SELECT id FROM b) EXCEPT ALL (SELECT id FROM c) UNION ALL (SELECT id FROM d)"
+    );
+}
+
+#[test]
+fn test_set_query_renumbers_bound_branches() {
+    let mut qa = Q();
+    let a = qa.bind(1);
+    let first = qa.select(vec!["id"]).from("a").where_(eq("id", &a)).build();
+    let mut qb = Q();
+    let b = qb.bind(2);
+    let second = qb.select(vec!["id"]).from("b").where_(eq("id", &b)).build();
+    let compound = first.union(table_stmt("c")).union(second);
+    assert_eq!(
+        compound.sql(),
+        "(SELECT id FROM a WHERE id = $1) UNION (TABLE c) UNION (SELECT id FROM b WHERE id = $2)"
+    );
+    let (sql, values) = compound.render().unwrap();
+    assert_eq!(
+        sql,
+        "(SELECT id FROM a WHERE id = $1) UNION (TABLE c) UNION (SELECT id FROM b WHERE id = $2)"
+    );
+    assert_eq!(values, vec![Value::Int(1), Value::Int(2)]);
+}
+
+// COMMENT ON tests
+
+#[test]
+fn test_comment_on_table_and_index() {
+    let table = Comment {
+        target: CommentTarget::Table("orders"),
+        text: Some("One row per checkout"),
+    };
+    assert_eq!(
+        table.sql(),
+        "COMMENT ON TABLE orders IS 'One row per checkout'"
+    );
+    let index = Comment {
+        target: CommentTarget::Index("orders_user_idx"),
+        text: Some("Speeds up per-user history"),
+    };
+    assert_eq!(
+        index.sql(),
+        "COMMENT ON INDEX orders_user_idx IS 'Speeds up per-user history'"
+    );
+}
+
+#[test]
+fn test_comment_escapes_quotes_and_removes() {
+    let column = Comment {
+        target: CommentTarget::Column("users", "nick"),
+        text: Some("user's display name"),
+    };
+    assert_eq!(
+        column.sql(),
+        "COMMENT ON COLUMN users.nick IS 'user''s display name'"
+    );
+    let removed = Comment {
+        target: CommentTarget::Constraint("users_pkey", "users"),
+        text: None,
+    };
+    assert_eq!(
+        removed.sql(),
+        "COMMENT ON CONSTRAINT users_pkey ON users IS NULL"
+    );
+}
+
+// filter tests
+
+#[test]
+fn test_filter_binds_values_in_order() {
+    let (sql, values) = Q()
+        .select(vec!["id"])
+        .from("users")
+        .filter("status", Op::Equals, "active")
+        .filter("age", Op::GreaterOrEqual, 18)
+        .order_by(vec![OrderedColumn::Asc("id")])
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT id FROM users WHERE status = $1 AND age >= $2 ORDER BY id ASC"
+    );
+    assert_eq!(
+        values,
+        vec![Value::Text("active".to_string()), Value::Int(18)]
+    );
+}
+
+#[test]
+fn test_filter_after_where_and_with_question_style() {
+    let mut qb = Q();
+    qb.param_style(ParamStyle::Question);
+    let query = qb
+        .select(vec!["*"])
+        .from("items")
+        .where_(or(eq("a", "1"), eq("b", "2")))
+        .filter("price", parse_op("<"), 10)
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM items WHERE (a = 1 OR b = 2) AND price < ?"
+    );
+}
+
+// ON CONFLICT DO UPDATE from EXCLUDED tests
+
+#[test]
+fn test_upsert_all_provided_columns() {
+    let insert = I("products")
+        .columns(vec!["sku", "name", "price", "stock"])
+        .values(vec!["'A1'", "'Widget'", "9.99", "5"])
+        .on_conflict_do_update_excluded(vec!["sku"], vec!["name", "price", "stock"])
+        .returning(Columns::Selected(vec!["sku"]))
+        .build();
+    assert_eq!(
+        insert.sql(),
+        "INSERT INTO products (sku, name, price, stock) VALUES ('A1', 'Widget', 9.99, 5) ON CONFLICT (sku) DO UPDATE SET name = EXCLUDED.name, price = EXCLUDED.price, stock = EXCLUDED.stock RETURNING sku"
+    );
+}
+
+#[test]
+fn test_excluded_row_form_single_column_and_sorted() {
+    assert_eq!(
+        OnConflict::DoUpdateExcludedRow(vec!["id"], vec!["name"]).sql(),
+        "ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name"
+    );
+    assert_eq!(
+        OnConflict::DoUpdateExcludedRow(vec!["id"], vec!["b", "a"])
+            .sorted()
+            .sql(),
+        "ON CONFLICT (id) DO UPDATE SET (a, b) = (EXCLUDED.a, EXCLUDED.b)"
+    );
+}
+
+// NULLS NOT DISTINCT tests
+
+#[test]
+fn test_unique_constraints_nulls_not_distinct() {
+    assert_eq!(
+        TableConstraint::Unique {
+            columns: vec!["email"],
+            nulls_not_distinct: true
+        }
+        .sql(),
+        "UNIQUE NULLS NOT DISTINCT (email)"
+    );
+    assert_eq!(
+        TableConstraint::Unique {
+            columns: vec!["a", "b"],
+            nulls_not_distinct: false
+        }
+        .sql(),
+        "UNIQUE (a, b)"
+    );
+    let col = ColumnDef {
+        name: "email",
+        data_type: "text",
+        constraints: vec![ColumnConstraint::UniqueNullsNotDistinct],
+    };
+    assert_eq!(col.sql(), "email text UNIQUE NULLS NOT DISTINCT");
+}
+
+#[test]
+fn test_create_index_variants() {
+    assert_eq!(
+        CreateIndex::new("orders", vec!["user_id", "created_at"]).sql(),
+        "CREATE INDEX ON orders (user_id, created_at)"
+    );
+    assert_eq!(
+        CreateIndex::new("users", vec!["email"])
+            .unique()
+            .nulls_not_distinct()
+            .sql(),
+        "CREATE UNIQUE INDEX ON users (email) NULLS NOT DISTINCT"
+    );
+}
+
+// WHERE fragment tests
+
+#[test]
+fn test_where_sql_matches_statements() {
+    let pred = and(eq("tenant_id", "7"), lt("expires_at", "NOW()"));
+    let fragment = pred.where_sql();
+    assert_eq!(fragment, "WHERE tenant_id = 7 AND expires_at < NOW()");
+    let delete = D("tokens").where_(pred.clone()).build();
+    assert_eq!(delete.sql(), format!("DELETE FROM tokens {}", fragment));
+    let update = U("tokens")
+        .set(vec![("revoked", "true")])
+        .where_(pred)
+        .build();
+    assert_eq!(
+        update.sql(),
+        format!("UPDATE tokens SET revoked = true {}", fragment)
+    );
+}
+
+#[test]
+fn test_where_clause_sql_on_built_query() {
+    let query = Q().select(vec!["*"]).from("t").build();
+    assert_eq!(query.where_clause.where_clause_sql(), "");
+    let query = Q()
+        .select(vec!["*"])
+        .from("t")
+        .where_(or(eq("a", "1"), eq("b", "2")))
+        .build();
+    assert_eq!(
+        query.where_clause.where_clause_sql(),
+        "WHERE a = 1 OR b = 2"
+    );
+}
+
+// TABLE shorthand tests
+
+#[test]
+fn test_table_stmt_as_cte_body() {
+    let query = Q()
+        .with("snapshot", table_stmt("accounts"))
+        .select(vec!["count(*)"])
+        .from("snapshot")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "WITH snapshot AS (TABLE accounts) SELECT count(*) FROM snapshot"
+    );
+}
+
+#[test]
+fn test_table_stmt_with_order_and_limit() {
+    let stmt = table_stmt("events")
+        .order_by(vec![OrderedColumn::Desc("id")])
+        .limit(5)
+        .offset(10)
+        .lock(LockClause::new(LockStrength::Share));
+    assert_eq!(
+        stmt.sql(),
+        "TABLE events ORDER BY id DESC LIMIT 5 OFFSET 10 FOR SHARE"
+    );
+    assert_eq!(
+        table_stmt("archive_2023")
+            .union_all(table_stmt("archive_2024"))
+            .sql(),
+        "(TABLE archive_2023) UNION ALL (TABLE archive_2024)"
+    );
+}
+
+#[test]
+fn test_table_stmt_mixes_with_select_branches() {
+    let compound = Q()
+        .select(vec!["*"])
+        .from("live")
+        .build()
+        .union(table_stmt("archive"))
+        .order_by(vec![OrderedColumn::Asc("id")]);
+    assert_eq!(
+        compound.sql(),
+        "(SELECT * FROM live) UNION (TABLE archive) ORDER BY id ASC"
+    );
+    assert_eq!(compound.stats().union_branches, 2);
+}
+
+#[test]
+fn test_table_stmt_converts_to_select() {
+    let query: Query = table_stmt("events").limit(3).into();
+    assert_eq!(query.sql(), "SELECT * FROM events LIMIT 3");
+}
+
+#[test]
+fn test_select_less_query_with_where_is_not_table_shorthand() {
+    let query = Q().from("users").where_(eq("active", "true")).build();
+    assert!(!query.sql().contains("TABLE"));
+    assert_eq!(query.sql(), " FROM users WHERE active = true");
+}
+
+// WITHIN GROUP tests
+
+#[test]
+fn test_percentiles_per_endpoint() {
+    let by_latency = || OrderBy {
+        columns: vec![OrderedColumn::Asc("response_time")],
+    };
+    let query = Q()
+        .select_expressions(vec![
+            SelectExpression::Column("endpoint"),
+            SelectExpression::Expr(
+                within_group(percentile_cont(0.5), by_latency()),
+                Some("p50"),
+            ),
+            SelectExpression::Expr(
+                within_group(percentile_cont(0.99), by_latency()),
+                Some("p99"),
+            ),
+        ])
+        .from("requests")
+        .group_by(vec!["endpoint"])
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT endpoint, PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY response_time ASC) AS p50, PERCENTILE_CONT(0.99) WITHIN GROUP (ORDER BY response_time ASC) AS p99 FROM requests GROUP BY endpoint"
+    );
+}
+
+#[test]
+fn test_mode_within_group_in_having() {
+    let top = within_group(
+        mode(),
+        OrderBy {
+            columns: vec![OrderedColumn::Asc("browser")],
+        },
+    );
+    assert_eq!(top.sql(), "MODE() WITHIN GROUP (ORDER BY browser ASC)");
+    let result = Q()
+        .select(vec!["country"])
+        .from("visits")
+        .group_by(vec!["country"])
+        .having(ne(top, "'other'"))
+        .try_build();
+    assert!(result.is_ok());
+}
+
+// Subquery column alias tests
+
+#[test]
+fn test_from_subquery_as_joined_star_subqueries() {
+    let users = Q().select(vec!["*"]).from("users").build();
+    let query = Q()
+        .select(vec!["u.id", "u.name", "o.total"])
+        .from_subquery_as(users, "u", vec!["id", "name"])
+        .inner_join("orders o", eq("o.user_id", "u.id"))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT u.id, u.name, o.total FROM (SELECT * FROM users) AS u (id, name) INNER JOIN orders o ON o.user_id = u.id"
+    );
+}
+
+#[test]
+fn test_aliased_subquery_without_columns_renders_plain_alias() {
+    let from = FromSource::AliasedSubquery {
+        query: Box::new(Q().select(vec!["id"]).from("users").build()),
+        alias: "u",
+        column_aliases: vec![],
+    };
+    assert_eq!(from.sql(), "(SELECT id FROM users) AS u");
+}
+
+// INSERT ... SELECT parameter tests
+
+#[test]
+fn test_insert_select_continues_after_insert_params() {
+    let mut qb = Q();
+    let status = qb.bind("active");
+    let query = qb
+        .select(vec!["id", "email"])
+        .from("users")
+        .where_(eq("status", &status))
+        .build();
+    let mut ib = I("mailing_list");
+    let source = ib.bind("signup");
+    let (sql, values) = ib
+        .columns(vec!["user_id", "email"])
+        .select(query)
+        .on_conflict_do_update(vec!["user_id"], vec![("source", &source)])
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO mailing_list (user_id, email) SELECT id, email FROM users WHERE status = $2 ON CONFLICT (user_id) DO UPDATE SET source = $1"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("signup".to_string()),
+            Value::Text("active".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_insert_select_question_style_binds_in_appearance_order() {
+    let mut qb = Q();
+    let status = qb.bind("active");
+    let query = qb
+        .select(vec!["id", "email"])
+        .from("users")
+        .where_(eq("status", &status))
+        .build();
+    let mut ib = I("mailing_list");
+    ib.param_style(ParamStyle::Question);
+    let source = ib.bind("signup");
+    let (sql, values) = ib
+        .columns(vec!["user_id", "email"])
+        .select(query)
+        .on_conflict_do_update(vec!["user_id"], vec![("source", &source)])
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO mailing_list (user_id, email) SELECT id, email FROM users WHERE status = ? ON CONFLICT (user_id) DO UPDATE SET source = ?"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("active".to_string()),
+            Value::Text("signup".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_insert_select_without_insert_params_keeps_numbering() {
+    let mut qb = Q();
+    let cutoff = qb.bind(30);
+    let query = qb
+        .select(vec!["*"])
+        .from("events")
+        .where_(lt("age_days", &cutoff))
+        .build();
+    let (sql, values) = I("recent_events").select(query).build().render().unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO recent_events SELECT * FROM events WHERE age_days < $1"
+    );
+    assert_eq!(values, vec![Value::Int(30)]);
+}
+
+#[test]
+fn test_renumber_params_skips_quoted_text() {
+    assert_eq!(
+        renumber_params(r#"SELECT "$1", '$2' || $1, $10 FROM t"#, 2),
+        r#"SELECT "$1", '$2' || $3, $12 FROM t"#
+    );
+}
+
+#[test]
+fn test_renumber_params_respects_token_boundaries() {
+    assert_eq!(
+        renumber_params(r"SELECT a$1, E'it\'s $1', $fn$ SELECT $1 $fn$, $1 -- $1", 1),
+        r"SELECT a$1, E'it\'s $1', $fn$ SELECT $1 $fn$, $2 -- $1"
+    );
+}
+
+// OrderBy columns_sql tests
+
+#[test]
+fn test_order_by_reused_in_aggregate_and_query() {
+    let order_by = OrderBy {
+        columns: vec![OrderedColumn::Desc("score"), OrderedColumn::Asc("name")],
+    };
+    let names = string_agg(
+        Term::Atom("name"),
+        Term::Atom("', '"),
+        AggOptions {
+            distinct: false,
+            order_by: Some(order_by.clone()),
+        },
+    );
+    let query = Q()
+        .select_expressions(vec![SelectExpression::Expr(names, Some("names"))])
+        .from("players")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT STRING_AGG(name, ', ' ORDER BY score DESC, name ASC) AS names FROM players"
+    );
+    assert_eq!(order_by.columns_sql(), "score DESC, name ASC");
+}
+
+#[test]
+fn test_order_by_columns_sql_single_column() {
+    let order_by = OrderBy {
+        columns: vec![OrderedColumn::Asc("id")],
+    };
+    assert_eq!(order_by.columns_sql(), "id ASC");
+    let query = Q()
+        .select(vec!["id"])
+        .from("t")
+        .order_by(order_by.columns)
+        .build();
+    assert_eq!(query.sql(), "SELECT id FROM t ORDER BY id ASC");
+}
+
+// Aggregated RETURNING tests
+
+#[test]
+fn test_returning_aggregated_delete_ids() {
+    let delete = D("sessions")
+        .where_(lt("expires_at", "now()"))
+        .returning(Columns::Selected(vec!["id"]))
+        .build();
+    let query = returning_aggregated(delete, array_agg(Term::Atom("id"), AggOptions::default()));
+    assert_eq!(
+        query.sql(),
+        "WITH affected AS (DELETE FROM sessions WHERE expires_at < now() RETURNING id) SELECT ARRAY_AGG(id) FROM affected"
+    );
+}
+
+#[test]
+fn test_returning_aggregated_insert_with_sum() {
+    let insert = I("payments")
+        .columns(vec!["amount"])
+        .rows(vec![vec!["10"], vec!["25"]])
+        .returning(Columns::Selected(vec!["amount"]))
+        .build();
+    let query = returning_aggregated(insert, func("sum", vec![Term::Atom("amount")]));
+    assert_eq!(
+        query.sql(),
+        "WITH affected AS (INSERT INTO payments (amount) VALUES (10), (25) RETURNING amount) SELECT sum(amount) FROM affected"
+    );
+}
+
+#[test]
+fn test_returning_aggregated_carries_bound_values() {
+    let mut ub = U("jobs");
+    let state = ub.bind("queued");
+    let failed = ub.bind("failed");
+    let update = ub
+        .set(vec![("state", &state)])
+        .where_(eq("state", &failed))
+        .returning(Columns::Selected(vec!["id"]))
+        .build();
+    let (sql, values) = returning_aggregated(update, func("count", vec![Term::Atom("*")]))
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "WITH affected AS (UPDATE jobs SET state = $1 WHERE state = $2 RETURNING id) SELECT count(*) FROM affected"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("queued".to_string()),
+            Value::Text("failed".to_string())
+        ]
+    );
+}
+
+// Typed bind tests
+
+#[test]
+fn test_bind_as_in_insert_values() {
+    let mut ib = I("events");
+    let id = ib.bind_as("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f", "uuid");
+    let payload = ib.bind_as("{}", "jsonb");
+    let (sql, values) = ib
+        .columns(vec!["id", "payload"])
+        .values(vec![&id, &payload])
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "INSERT INTO events (id, payload) VALUES ($1::uuid, $2::jsonb)"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f".to_string()),
+            Value::Text("{}".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_bind_as_continues_numbering_with_bind() {
+    let mut ub = U("accounts");
+    let balance = ub.bind(100);
+    let owner = ub.bind_as("8f14e45f-ceea-467f-a8f8-3a2b1c9d0e7f", "uuid");
+    let update = ub
+        .set(vec![("balance", &balance)])
+        .where_(eq("owner_id", &owner))
+        .build();
+    assert_eq!(
+        update.sql(),
+        "UPDATE accounts SET balance = $1 WHERE owner_id = $2::uuid"
+    );
+}
+
+// Query stats tests
+
+#[test]
+fn test_stats_deeply_nested_query() {
+    let innermost = subquery(|q| q.select(vec!["user_id"]).from("bans"));
+    let middle = subquery(|q| {
+        q.select(vec!["id"])
+            .from("users")
+            .inner_join("teams", eq("teams.id", "users.team_id"))
+            .where_(not_in_subquery("id", innermost))
+    });
+    let recent = Q().select(vec!["*"]).from("orders").build();
+    let query = Q()
+        .with("recent", recent)
+        .select(vec!["*"])
+        .from_subquery(middle, "u")
+        .left_join("recent", eq("recent.user_id", "u.id"))
+        .left_join("refunds", eq("refunds.user_id", "u.id"))
+        .where_(exists(subquery(|q| {
+            q.select(vec!["1"])
+                .from("flags")
+                .where_(eq("flags.user_id", "u.id"))
+        })))
+        .build();
+    assert_eq!(
+        query.stats(),
+        QueryStats {
+            joins: 3,
+            subqueries: 3,
+            ctes: 1,
+            union_branches: 0,
+            max_depth: 2,
+        }
+    );
+}
+
+#[test]
+fn test_stats_plain_query_and_union() {
+    let plain = Q().select(vec!["id"]).from("users").build();
+    assert_eq!(plain.stats(), QueryStats::default());
+    let nested = Q()
+        .select(vec!["id"])
+        .from("admins")
+        .where_(in_subquery(
+            "id",
+            subquery(|q| q.select(vec!["admin_id"]).from("grants")),
+        ))
+        .build();
+    let stats = plain.union(nested).stats();
+    assert_eq!(stats.union_branches, 2);
+    assert_eq!(stats.subqueries, 1);
+    assert_eq!(stats.max_depth, 1);
+}
+
+// Scalar subquery tests
+
+#[test]
+fn test_try_scalar_subquery_in_select_list() {
+    let max_price = try_scalar_subquery(Q().select(vec!["max(price)"]).from("items").build())
+        .expect("one column");
+    let query = Q()
+        .select_expressions(vec![
+            SelectExpression::Column("name"),
+            SelectExpression::Expr(max_price, Some("top_price")),
+        ])
+        .from("shops")
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT name, (SELECT max(price) FROM items) AS top_price FROM shops"
+    );
+}
+
+#[test]
+fn test_try_scalar_subquery_rejects_wildcards() {
+    let star = try_scalar_subquery(Q().select(vec!["*"]).from("items").build());
+    assert_eq!(
+        star.err(),
+        Some(BuildError::NonScalarSubquery {
+            columns: vec!["*".to_string()]
+        })
+    );
+    let table = try_scalar_subquery(table_stmt("items").into());
+    assert!(matches!(table, Err(BuildError::NonScalarSubquery { .. })));
+    let err = try_scalar_subquery(Q().select(vec!["items.*"]).from("items").build())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "scalar subquery must select exactly one column, found [\"items.*\"]"
+    );
+}
+
+// Index column ordering tests
+
+#[test]
+fn test_create_index_mixed_column_orders() {
+    let index = CreateIndex::new(
+        "t",
+        vec![
+            OrderedColumn::Desc("a").nulls_last(),
+            OrderedColumn::Asc("b"),
+            OrderedColumn::from("c"),
+            OrderedColumn::Column("d").nulls_first(),
+        ],
+    )
+    .name("t_sort_idx");
+    assert_eq!(
+        index.sql(),
+        "CREATE INDEX t_sort_idx ON t (a DESC NULLS LAST, b ASC, c, d ASC NULLS FIRST)"
+    );
+}
+
+#[test]
+fn test_order_by_with_nulls_placement() {
+    let query = Q()
+        .select(vec!["id", "due_at"])
+        .from("tasks")
+        .order_by(vec![
+            OrderedColumn::Asc("due_at").nulls_last(),
+            OrderedColumn::Column("id"),
+        ])
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT id, due_at FROM tasks ORDER BY due_at ASC NULLS LAST, id"
+    );
+}
+
+// Top-N per group tests
+
+#[test]
+fn test_rank_filter_latest_order_per_customer() {
+    let orders = Q()
+        .select(vec!["customer_id", "id", "placed_at"])
+        .from("orders")
+        .where_(eq("status", "'paid'"))
+        .build();
+    let query = rank_filter(
+        orders,
+        vec!["customer_id"],
+        vec![OrderedColumn::Desc("placed_at"), OrderedColumn::Desc("id")],
+        1,
+        "rn",
+    );
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM (SELECT customer_id, id, placed_at, row_number() OVER (PARTITION BY customer_id ORDER BY placed_at DESC, id DESC) AS rn FROM orders WHERE status = 'paid') AS s WHERE rn <= 1"
+    );
+}
+
+#[test]
+fn test_rank_filter_without_partition_on_table_query() {
+    let query = rank_filter(
+        table_stmt("scores").into(),
+        vec![],
+        vec![OrderedColumn::Desc("points")],
+        10,
+        "rn",
+    );
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM (SELECT *, row_number() OVER (ORDER BY points DESC) AS rn FROM scores) AS s WHERE rn <= 10"
+    );
+}
+
+#[test]
+fn test_rank_filter_wraps_distinct_and_grouped_queries() {
+    let pairs = Q()
+        .select(vec!["team", "player"])
+        .distinct()
+        .from("appearances")
+        .build();
+    let query = rank_filter(
+        pairs,
+        vec!["team"],
+        vec![OrderedColumn::Asc("player")],
+        2,
+        "player_rank",
+    );
+    assert_eq!(
+        query.sql(),
+        "SELECT * FROM (SELECT *, row_number() OVER (PARTITION BY team ORDER BY player ASC) AS player_rank \
+         FROM (SELECT DISTINCT team, player FROM appearances) AS d) AS s WHERE player_rank <= 2"
+    );
+
+    let mut totals = Q();
+    let limit = totals.bind(100);
+    let totals = totals
+        .select(vec!["region", "customer_id", "sum(total) AS spent"])
+        .from("orders")
+        .group_by(vec!["region", "customer_id"])
+        .having(gt("sum(total)", &limit))
+        .build();
+    let (sql, values) = rank_filter(
+        totals,
+        vec!["region"],
+        vec![OrderedColumn::Desc("spent")],
+        3,
+        "spend_rank",
+    )
+    .render()
+    .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT * FROM (SELECT *, row_number() OVER (PARTITION BY region ORDER BY spent DESC) AS spend_rank \
+         FROM (SELECT region, customer_id, sum(total) AS spent FROM orders GROUP BY region, customer_id \
+         HAVING sum(total) > $1) AS d) AS s WHERE spend_rank <= 3"
+    );
+    assert_eq!(values, vec![Value::Int(100)]);
+}
+
+// DISTINCT ON latest tests
+
+#[test]
+fn test_distinct_on_latest_multiple_keys_passes_validation() {
+    let mut qb = Q();
+    qb.select(vec!["tenant_id", "device_id", "reading", "taken_at"])
+        .from("readings")
+        .distinct_on_latest(vec!["tenant_id", "device_id"], "taken_at", true);
+    assert!(qb.validate_distinct_on().is_ok());
+    assert_eq!(
+        qb.build().sql(),
+        "SELECT DISTINCT ON (tenant_id, device_id) tenant_id, device_id, reading, taken_at FROM readings ORDER BY tenant_id, device_id, taken_at DESC NULLS LAST"
+    );
+}
+
+#[test]
+fn test_distinct_on_latest_before_select() {
+    let mut qb = Q();
+    let builder = qb
+        .distinct_on_latest(vec!["device_id"], "reported_at", true)
+        .select(vec!["device_id", "battery"])
+        .from("readings");
+    assert!(builder.validate_distinct_on().is_ok());
+    assert_eq!(
+        builder.build().sql(),
+        "SELECT DISTINCT ON (device_id) device_id, battery FROM readings \
+         ORDER BY device_id, reported_at DESC NULLS LAST"
+    );
+}
+
+#[test]
+fn test_distinct_on_latest_without_select_list() {
+    let query = Q()
+        .from("readings")
+        .distinct_on_latest(vec!["device_id"], "reported_at", true)
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT DISTINCT ON (device_id) * FROM readings \
+         ORDER BY device_id, reported_at DESC NULLS LAST"
+    );
+}
+
+#[test]
+fn test_distinct_on_earliest() {
+    let query = Q()
+        .select(vec!["user_id", "created_at"])
+        .from("logins")
+        .distinct_on_latest(vec!["user_id"], "created_at", false)
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT DISTINCT ON (user_id) user_id, created_at FROM logins ORDER BY user_id, created_at ASC NULLS LAST"
+    );
+}
+
+// NULL-handling helper tests
+
+#[test]
+fn test_default_to_in_select_and_where() {
+    let mut qb = Q();
+    let region = qb.bind("eu");
+    let query = qb
+        .select_expressions(vec![SelectExpression::Expr(
+            default_to("display_name", "'anonymous'"),
+            Some("name"),
+        )])
+        .from("users")
+        .where_(nullsafe_eq(default_to("region", "'us'"), &region))
+        .build();
+    assert_eq!(
+        query.sql(),
+        "SELECT COALESCE(display_name, 'anonymous') AS name FROM users WHERE COALESCE(region, 'us') IS NOT DISTINCT FROM $1"
+    );
+}
+
+#[test]
+fn test_nullsafe_eq_round_trips_through_parse_op() {
+    assert!(parse_op("is not distinct from") == Op::IsNotDistinctFrom);
+    assert!(parse_op("IS DISTINCT FROM") == Op::IsDistinctFrom);
+    let t = nullsafe_eq("a.parent_id", "b.parent_id");
+    assert!(not(not(t.clone())) == t);
+}
+
+// Placeholder scanning tests
+
+#[test]
+fn test_validate_params_ignores_quoted_and_identifier_dollars() {
+    let mut qb = Q();
+    let p1 = qb.param();
+    let query = qb
+        .select(vec!["a$2", "'$3'", "E'it\\'s $4'", "\"col$5\"", "$$ $6 $$"])
+        .from("users")
+        .where_(eq("id", &p1))
+        .build();
+    assert_eq!(query.validate_params(), Ok(()));
+}
+
+#[test]
+fn test_validate_params_huge_index() {
+    let mut qb = Q();
+    let big = p(4_000_000_000);
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(eq("id", &big))
+        .build();
+    let err = query.validate_params().unwrap_err();
+    match err {
+        BuildError::InvalidParams {
+            missing,
+            duplicates,
+        } => {
+            assert_eq!(missing.len(), 65535);
+            assert!(duplicates.is_empty());
+        }
+        _ => panic!("expected InvalidParams"),
+    }
+}
+
+// Term walker tests
+
+#[test]
+fn test_deep_having_chain_is_validated() {
+    let mut term = gt("COUNT(*)", "0");
+    for _ in 1..10_000 {
+        term = and(term, gt("SUM(amount)", "1"));
+    }
+    let mut qb = Q();
+    let builder = qb
+        .select(vec!["dept"])
+        .from("orders")
+        .group_by(vec!["dept"])
+        .having(and(term, eq("region", "'eu'")));
+    assert!(builder.validate_having().is_err());
+}
+
+#[test]
+fn test_stats_sees_subqueries_inside_shared_terms() {
+    let mut sb = Q();
+    let sub = sb.select(vec!["id"]).from("banned").build();
+    let shared = share(not(in_subquery("user_id", sub)));
+    let mut qb = Q();
+    let query = qb
+        .select(vec!["*"])
+        .from("orders")
+        .where_(and(shared.clone(), coalesce(vec![shared, "true".into()])))
+        .build();
+    assert_eq!(query.stats().subqueries, 2);
+}
+
+// Nested parameter tests
+
+#[test]
+fn test_render_merges_subquery_and_cte_values() {
+    let mut cb = Q();
+    let region = cb.bind("eu");
+    let regional = cb
+        .select(vec!["id"])
+        .from("customers")
+        .where_(eq("region", &region))
+        .build();
+    let mut sb = Q();
+    let min_total = sb.bind(100);
+    let big_spenders = sb
+        .select(vec!["customer_id"])
+        .from("orders")
+        .where_(gt("total", &min_total))
+        .build();
+    let mut qb = Q();
+    let status = qb.bind("active");
+    let (sql, values) = qb
+        .with("regional", regional)
+        .select(vec!["*"])
+        .from("regional")
+        .where_(and(eq("status", &status), in_subquery("id", big_spenders)))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "WITH regional AS (SELECT id FROM customers WHERE region = $2) SELECT * FROM regional WHERE status = $1 AND id IN (SELECT customer_id FROM orders WHERE total > $3)"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("active".to_string()),
+            Value::Text("eu".to_string()),
+            Value::Int(100)
+        ]
+    );
+}
+
+#[test]
+fn test_render_keeps_outer_placeholders_in_subqueries() {
+    let mut qb = Q();
+    let owner = qb.bind(7);
+    let mut sb = Q();
+    let owned = sb
+        .select(vec!["project_id"])
+        .from("memberships")
+        .where_(eq("user_id", &owner))
+        .build();
+    let (sql, values) = qb
+        .select(vec!["*"])
+        .from("projects")
+        .where_(or(eq("owner_id", &owner), in_subquery("id", owned)))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT * FROM projects WHERE owner_id = $1 OR id IN (SELECT project_id FROM memberships WHERE user_id = $1)"
+    );
+    assert_eq!(values, vec![Value::Int(7)]);
+}
+
+#[test]
+fn test_render_rejects_mixed_param_and_bind() {
+    let mut qb = Q();
+    let id = qb.param();
+    let status = qb.bind("active");
+    let query = qb
+        .select(vec!["*"])
+        .from("users")
+        .where_(and(eq("id", &id), eq("status", &status)))
+        .build();
+    let err = query.render().unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::UnboundParams {
+            placeholders: vec![1]
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "placeholders [1] have no bound value to line up with"
+    );
+}
+
+// Positional placeholder order tests
+
+#[test]
+fn test_question_style_orders_values_by_appearance() {
+    let mut qb = Q();
+    qb.param_style(ParamStyle::Question);
+    let age = qb.bind(21);
+    let status = qb.bind("active");
+    let (sql, values) = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(and(
+            eq("status", &status),
+            or(gte("age", &age), lt("age", &age)),
+        ))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT id FROM users WHERE status = ? AND (age >= ? OR age < ?)"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("active".to_string()),
+            Value::Int(21),
+            Value::Int(21)
+        ]
+    );
+}
+
+#[test]
+fn test_question_style_rejects_unbound_out_of_order() {
+    let mut qb = Q();
+    qb.param_style(ParamStyle::Question);
+    let a = qb.param();
+    let b = qb.param();
+    let query = qb
+        .select(vec!["*"])
+        .from("t")
+        .where_(and(eq("b", &b), eq("a", &a)))
+        .build();
+    assert_eq!(query.sql(), "SELECT * FROM t WHERE b = ? AND a = ?");
+    assert_eq!(
+        query.render(),
+        Err(BuildError::UnboundParams {
+            placeholders: vec![1, 2]
+        })
+    );
+}
+
+// Keyset pagination error tests
+
+#[test]
+fn test_keyset_paginate_rejects_value_count_mismatch() {
+    let mut qb = Q();
+    let result = qb.select(vec!["*"]).from("posts").keyset_paginate(
+        vec![OrderedColumn::Asc("created_at"), OrderedColumn::Asc("id")],
+        Some(vec![Value::from("2024-01-01")]),
+        10,
+    );
+    assert_eq!(
+        result.err(),
+        Some(BuildError::KeysetArity { keys: 2, values: 1 })
+    );
+}
+
+#[test]
+fn test_keyset_paginate_rejects_empty_keys() {
+    let mut qb = Q();
+    let result = qb
+        .select(vec!["*"])
+        .from("posts")
+        .keyset_paginate(vec![], Some(vec![]), 10);
+    assert_eq!(result.err(), Some(BuildError::EmptyKeyset));
+    let mut qb = Q();
+    let result = qb
+        .select(vec!["*"])
+        .from("posts")
+        .keyset_paginate(vec![], None, 10);
+    assert_eq!(result.err(), Some(BuildError::EmptyKeyset));
+}
+
+#[test]
+fn test_keyset_paginate_mixed_direction_question_style() {
+    let mut qb = Q();
+    qb.param_style(ParamStyle::Question);
+    let (sql, values) = qb
+        .select(vec!["*"])
+        .from("scores")
+        .keyset_paginate(
+            vec![OrderedColumn::Desc("points"), OrderedColumn::Asc("id")],
+            Some(vec![Value::from(900), Value::from(7)]),
+            25,
+        )
+        .unwrap()
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "SELECT * FROM scores WHERE (points < ? OR (points = ? AND id > ?)) ORDER BY points DESC, id ASC LIMIT 25"
+    );
+    assert_eq!(
+        values,
+        vec![Value::Int(900), Value::Int(900), Value::Int(7)]
+    );
+}
+
+// DML WITH clause tests
+
+#[test]
+fn test_insert_from_data_modifying_cte() {
+    let mut db = D("orders");
+    let cutoff = db.bind("2020-01-01");
+    let moved = db
+        .where_(lt("placed_at", &cutoff))
+        .returning(Columns::Star)
+        .build();
+    let mut ib = I("orders_archive");
+    let source = ib.bind("archive");
+    let (sql, values) = ib
+        .with("moved", moved)
+        .columns(vec!["id", "source"])
+        .select(Q().select(vec!["id", &source]).from("moved").build())
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "WITH moved AS (DELETE FROM orders WHERE placed_at < $2 RETURNING *) \
+         INSERT INTO orders_archive (id, source) SELECT id, $1 FROM moved"
+    );
+    assert_eq!(
+        values,
+        vec![
+            Value::Text("archive".to_string()),
+            Value::Text("2020-01-01".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_update_and_delete_with_ctes() {
+    let mut qb = Q();
+    let days = qb.bind(30);
+    let idle = qb
+        .select(vec!["id"])
+        .from("users")
+        .where_(gt("idle_days", &days))
+        .build();
+    let mut ub = U("users");
+    let state = ub.bind("dormant");
+    let (sql, values) = ub
+        .with("idle", idle.clone())
+        .set(vec![("state", &state)])
+        .from("idle")
+        .where_(eq("users.id", "idle.id"))
+        .build()
+        .render()
+        .unwrap();
+    assert_eq!(
+        sql,
+        "WITH idle AS (SELECT id FROM users WHERE idle_days > $2) \
+         UPDATE users SET state = $1 FROM idle WHERE users.id = idle.id"
+    );
+    assert_eq!(
+        values,
+        vec![Value::Text("dormant".to_string()), Value::Int(30)]
+    );
+
+    let delete = D("sessions")
+        .with_cte(idle.as_cte("idle"))
+        .using("idle")
+        .where_(eq("sessions.user_id", "idle.id"))
+        .build();
+    assert_eq!(
+        delete.sql(),
+        "WITH idle AS (SELECT id FROM users WHERE idle_days > $1) \
+         DELETE FROM sessions USING idle WHERE sessions.user_id = idle.id"
+    );
+    assert_eq!(delete.params.len(), 1);
+}
+
+#[test]
+fn test_stats_count_ctes_of_data_modifying_cte_bodies() {
+    let recent = Q().select(vec!["id"]).from("orders").build();
+    let update = U("orders")
+        .with("recent", recent)
+        .set(vec![("seen", "true")])
+        .from("recent")
+        .where_(eq("orders.id", "recent.id"))
+        .returning(Columns::Star)
+        .build();
+    let query = Q()
+        .with("seen", update)
+        .select(vec!["*"])
+        .from("seen")
+        .build();
+    let stats = query.stats();
+    assert_eq!(stats.ctes, 2);
+    assert_eq!(stats.max_depth, 2);
+}